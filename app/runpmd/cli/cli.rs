@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Editor, Helper};
+use crate::cmd_module::CmdModuleOps;
+
+pub type CmdModule = HashMap<String, Box<dyn CmdModuleOps>>;
+
+/// Command files a command file can load in a chain.
+const MAX_LOAD_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmdStatus {
+    Continue,
+    /// `exit` or `quit` was run.
+    Exit,
+}
+
+/// Command line, commands are dispatched by their first word to the registered
+/// [`CmdModuleOps`]. `exit`, `quit` and `load` are handled by the command line.
+pub struct Cli {
+    modules: CmdModule,
+    exit_on_error: bool,
+}
+
+// Splits a command file into commands, each with the number of its first line.
+// Lines starting with `#` are comments, a line ending with `\` continues on the next one.
+fn script_commands(text: &str) -> Vec<(usize, String)> {
+    let mut commands = Vec::new();
+    let mut command: Option<(usize, String)> = None;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if command.is_none() && (line.is_empty() || line.starts_with('#')) {
+            continue;
+        }
+        let (line, continued) = match line.strip_suffix('\\') {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        let (_, text) = command.get_or_insert_with(|| (index + 1, String::new()));
+        text.push_str(line);
+        text.push(' ');
+        if !continued {
+            commands.extend(command.take());
+        }
+    }
+    commands.extend(command);
+    commands
+}
+
+// Set by SIGINT while a command runs, the prompt reads Ctrl-C as a key.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Routes SIGINT to the command line, Ctrl-C stops command files and the
+/// non-interactive wait instead of ending runpmd.
+pub fn handle_interrupts() -> Result<(), String> {
+    ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::Relaxed)).map_err(|err| err.to_string())
+}
+
+// Returns and clears a pending Ctrl-C.
+fn interrupted() -> bool {
+    INTERRUPTED.swap(false, Ordering::Relaxed)
+}
+
+/// Waits until enter is pressed, stdin is closed or Ctrl-C is pressed.
+pub fn wait_for_exit() {
+    println!("Press enter to exit");
+    let mut stdin = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+    while !interrupted() {
+        let rc = unsafe { libc::poll(&mut stdin, 1, 100) };
+        if rc > 0 {
+            let _ = io::stdin().read_line(&mut String::new());
+            break;
+        }
+        if rc < 0 && io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            break;
+        }
+    }
+}
+
+// Continuation lines end with `\`, they are joined into one command.
+fn join_lines(line: &str) -> String {
+    line.replace("\\\n", " ")
+}
+
+// Line editor helper, completes commands from the registered modules.
+struct CliHelper<'a> {
+    cli: &'a Cli,
+    files: FilenameCompleter,
+}
+
+impl Completer for CliHelper<'_> {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let words = join_lines(&line[..start])
+            .split_ascii_whitespace()
+            .map(|word| word.to_string())
+            .collect::<Vec<_>>();
+        if words.len() == 1 && words[0] == "load" {
+            return self.files.complete(line, pos, ctx);
+        }
+        let prefix = &line[start..];
+        let candidates = self
+            .cli
+            .complete(&words)
+            .into_iter()
+            .filter(|keyword| keyword.starts_with(prefix))
+            .map(|keyword| Pair { display: keyword.clone(), replacement: keyword + " " })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Validator for CliHelper<'_> {
+    fn validate(&self, ctx: &mut ValidationContext<'_>) -> rustyline::Result<ValidationResult> {
+        Ok(match ctx.input().ends_with('\\') {
+            true => ValidationResult::Incomplete,
+            false => ValidationResult::Valid(None),
+        })
+    }
+}
+
+impl Hinter for CliHelper<'_> {
+    type Hint = String;
+}
+
+impl Highlighter for CliHelper<'_> {}
+
+impl Helper for CliHelper<'_> {}
+
+impl Cli {
+    /// `exit_on_error` stops command files at the first failed command.
+    pub fn new(modules: CmdModule, exit_on_error: bool) -> Self {
+        Cli { modules, exit_on_error }
+    }
+
+    // Keywords that can follow `words`, the first word selects the module.
+    fn complete(&self, words: &[String]) -> Vec<String> {
+        let Some(cmd) = words.first() else {
+            let mut keywords = self.modules.keys().cloned().collect::<Vec<_>>();
+            keywords.extend(["exit", "quit", "load"].map(String::from));
+            keywords.sort();
+            return keywords;
+        };
+        match self.modules.get(cmd) {
+            Some(op) => op.complete(words),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn run_command(&self, command: &str) -> Result<CmdStatus, String> {
+        self.run(command, 0)
+    }
+
+    fn run(&self, command: &str, depth: usize) -> Result<CmdStatus, String> {
+        let mut input = command
+            .split_ascii_whitespace()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>();
+        let Some(cmd) = input.first() else {
+            return Ok(CmdStatus::Continue);
+        };
+
+        match cmd.as_str() {
+            "exit" | "quit" => return Ok(CmdStatus::Exit),
+            "load" => {
+                return match input.as_slice() {
+                    [_, path] => self.load_file(Path::new(path), depth + 1),
+                    _ => Err("usage: load <file>".to_string()),
+                };
+            }
+            _ => (),
+        }
+
+        let Some(op) = self.modules.get(cmd) else {
+            return Err(format!("Unknown command: {cmd}"));
+        };
+        op.parse_cmd(&mut input).map(|_| CmdStatus::Continue)
+    }
+
+    /// Runs the commands of a file, errors are reported with the file name and line.
+    pub fn load(&self, path: &Path) -> Result<CmdStatus, String> {
+        self.load_file(path, 1)
+    }
+
+    fn load_file(&self, path: &Path, depth: usize) -> Result<CmdStatus, String> {
+        if depth > MAX_LOAD_DEPTH {
+            return Err(format!("{}: more than {MAX_LOAD_DEPTH} nested command files", path.display()));
+        }
+        let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+
+        let mut failed = 0;
+        for (line_no, command) in script_commands(&text) {
+            if interrupted() {
+                return Err(format!("{}:{line_no}: interrupted", path.display()));
+            }
+            match self.run(&command, depth) {
+                Ok(CmdStatus::Continue) => (),
+                Ok(CmdStatus::Exit) => return Ok(CmdStatus::Exit),
+                Err(err) => {
+                    let err = format!("{}:{line_no}: {err}", path.display());
+                    if self.exit_on_error {
+                        return Err(err);
+                    }
+                    println!("{err}");
+                    failed += 1;
+                }
+            }
+        }
+        match failed {
+            0 => println!("Read CLI commands from {}", path.display()),
+            _ => println!("Read CLI commands from {}, {failed} commands failed", path.display()),
+        }
+        Ok(CmdStatus::Continue)
+    }
+
+    /// Runs the interactive command line, the history is kept in `history` if set.
+    ///
+    /// Ctrl-C clears the edited line, Ctrl-D or `exit` leaves the command line.
+    pub fn run_interactive(&self, history: Option<&Path>) {
+        let mut editor = match Editor::<CliHelper, DefaultHistory>::new() {
+            Ok(editor) => editor,
+            Err(err) => {
+                println!("command line: {err}");
+                return;
+            }
+        };
+        editor.set_helper(Some(CliHelper { cli: self, files: FilenameCompleter::new() }));
+        if let Some(path) = history {
+            // the history file is created on exit
+            let _ = editor.load_history(path);
+        }
+
+        loop {
+            let command = match editor.readline(">>> ") {
+                Ok(line) => join_lines(&line),
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(err) => {
+                    println!("command line: {err}");
+                    break;
+                }
+            };
+            if !command.trim().is_empty() {
+                let _ = editor.add_history_entry(command.trim());
+            }
+            // a Ctrl-C pressed at the prompt is not meant for this command
+            interrupted();
+            match self.run_command(&command) {
+                Ok(CmdStatus::Continue) => (),
+                Ok(CmdStatus::Exit) => break,
+                Err(err) => println!("{err}"),
+            }
+        }
+
+        if let Some(path) = history {
+            if let Err(err) = editor.save_history(path) {
+                println!("{}: {err}", path.display());
+            }
+        }
+        println!("Live long and prosper");
+    }
+}
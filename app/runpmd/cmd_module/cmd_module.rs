@@ -1,11 +1,20 @@
 #[path = "flow/flow.rs"]
 pub mod flow;
 
+#[path = "fwd/fwd.rs"]
+pub mod fwd;
+
 #[path = "port/port.rs"]
 pub mod port;
 
-// Command line parser is running in a dedicated thread.
-// All ModuleOps objects must implement Send and Sync traits.
+// Command line parser is running on the main lcore and shares RunPmd with the
+// forwarding workers. All ModuleOps objects must implement Send and Sync traits.
 pub trait CmdModuleOps: Send + Sync {
-    fn parse_cmd(&self, input: &mut Vec<String>);
+    // Runs the command in `input`, errors are reported to the caller.
+    fn parse_cmd(&self, input: &mut Vec<String>) -> Result<(), String>;
+
+    // Returns keywords that can follow the words in `input`, used by tab completion.
+    fn complete(&self, _input: &[String]) -> Vec<String> {
+        Vec::new()
+    }
 }
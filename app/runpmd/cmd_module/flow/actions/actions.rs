@@ -3,7 +3,8 @@ use rdpdk::cmdline::arg::{Arg, ArgData};
 use rdpdk::cmdline::param::Param;
 use rdpdk::dpdk_raw::rte_ethdev::{
     rte_flow_action, rte_flow_action_ethdev, rte_flow_action_type,
-    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_DROP, rte_flow_action_type_RTE_FLOW_ACTION_TYPE_END,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_COUNT, rte_flow_action_type_RTE_FLOW_ACTION_TYPE_DROP,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_END,
     rte_flow_action_type_RTE_FLOW_ACTION_TYPE_REPRESENTED_PORT,
 };
 use std::cell::RefCell;
@@ -55,7 +56,7 @@ impl ActionsParserContext {
 pub trait ActionOps {
     fn name(&self) -> &str;
 
-    fn parse_action(&self, input: &mut Vec<String>, context: &mut ActionsParserContext);
+    fn parse_action(&self, input: &mut Vec<String>, context: &mut ActionsParserContext) -> Result<(), String>;
 }
 
 type ParamMap = HashMap<String, Box<dyn ActionOps>>;
@@ -83,19 +84,19 @@ impl Action {
         }
     }
 
-    fn parse(&self, input: &mut Vec<String>, context: &mut ActionsParserContext) {
-        self.cmd.parse_action(input, context);
+    fn parse(&self, input: &mut Vec<String>, context: &mut ActionsParserContext) -> Result<(), String> {
+        self.cmd.parse_action(input, context)?;
         match self.param {
-            None => (),
+            None => Ok(()),
             Some(ref pmap) => {
                 if context.data.is_none() {
                     context.data = Some(ArgData::new())
                 }
                 loop {
-                    match pmap.get(&input[0]) {
-                        None => return,
+                    match input.first().and_then(|word| pmap.get(word)) {
+                        None => return Ok(()),
                         Some(op) => {
-                            op.parse_action(input, context);
+                            op.parse_action(input, context)?;
                         }
                     }
                 }
@@ -109,6 +110,7 @@ type ActionsMap = HashMap<String, Action>;
 pub struct FlowActions {
     map: RefCell<ActionsMap>,
     context: ActionsParserContext,
+    names: Vec<String>, // parsed action names
 }
 
 impl FlowActions {
@@ -116,10 +118,12 @@ impl FlowActions {
         let mut adb = FlowActions {
             map: RefCell::new(HashMap::new()),
             context: ActionsParserContext::new(),
+            names: Vec::new(),
         };
 
         adb.register(Action::from(ActionEnd::new(), None));
         adb.register(Action::from(ActionDrop::new(), None));
+        adb.register(Action::from(ActionCount::new(), None));
         adb.register(Action::from(ActionSeparator::new(), None));
 
         adb
@@ -131,20 +135,47 @@ impl FlowActions {
             .insert(action.cmd.name().to_string(), action);
     }
 
-    pub fn parse_actions(&mut self, input: &mut Vec<String>) {
+    // Action names, `/` and `end` included.
+    pub fn keywords(&self) -> Vec<String> {
+        self.map.borrow().keys().cloned().collect()
+    }
+
+    // Parameter names of an action.
+    pub fn params(&self, action: &str) -> Vec<String> {
+        match self.map.borrow().get(action).and_then(|action| action.param.as_ref()) {
+            Some(pmap) => pmap.keys().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn parse_actions(&mut self, input: &mut Vec<String>) -> Result<(), String> {
         loop {
-            match self.map.borrow().get(&input[0]) {
-                None => return,
+            let name = match input.first() {
+                None => return Ok(()),
+                Some(name) => name.clone(),
+            };
+            match self.map.borrow().get(&name) {
+                None => return Ok(()),
                 Some(action) => {
-                    action.parse(input, &mut self.context);
+                    if !matches!(name.as_str(), "/" | "end") {
+                        self.names.push(name);
+                    }
+                    action.parse(input, &mut self.context)?;
                 }
             }
-            if input.len() == 0 {
-                return;
-            }
         }
     }
 
+    // Names of the parsed actions, `/` and `end` excluded.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    // END terminated actions, empty until `end` is parsed.
+    pub fn raw_actions(&self) -> &[rte_flow_action] {
+        &self.context.raw_actions
+    }
+
     pub fn get_raw_actions(&self) -> *const rte_flow_action {
         self.context.raw_actions.as_ptr() as *const rte_flow_action
     }
@@ -156,24 +187,26 @@ impl ActionOps for Param {
     }
 
     // Default flow actions parser
-    fn parse_action(&self, input: &mut Vec<String>, context: &mut ActionsParserContext) {
+    fn parse_action(&self, input: &mut Vec<String>, context: &mut ActionsParserContext) -> Result<(), String> {
         if self.id.is_some() {
             context.id = self.id.unwrap() as rte_flow_action_type;
             input.remove(0);
         } else if self.args.is_some() {
             let (arg_op, offset) = self.args.as_ref().unwrap().get(0).unwrap();
-            let arg = arg_op.serialize(&input[1]).unwrap();
+            let value = input.get(1).ok_or(format!("{}: missing value", self.name))?;
+            let arg = arg_op.serialize(value).map_err(|err| format!("{}: {err}", self.name))?;
             let slice = &arg.data[0..arg.size];
             context
                 .data
                 .as_mut()
                 .unwrap()
                 .or_from_slice(slice, *offset)
-                .unwrap();
+                .map_err(|err| format!("{}: {err}", self.name))?;
 
             input.remove(0);
             input.remove(0);
         }
+        Ok(())
     }
 }
 
@@ -189,8 +222,9 @@ impl ActionOps for ActionSeparator {
         "/"
     }
 
-    fn parse_action(&self, input: &mut Vec<String>, _context: &mut ActionsParserContext) {
+    fn parse_action(&self, input: &mut Vec<String>, _context: &mut ActionsParserContext) -> Result<(), String> {
         input.remove(0);
+        Ok(())
     }
 }
 
@@ -206,13 +240,14 @@ impl ActionOps for ActionEnd {
         "end"
     }
 
-    fn parse_action(&self, input: &mut Vec<String>, context: &mut ActionsParserContext) {
+    fn parse_action(&self, input: &mut Vec<String>, context: &mut ActionsParserContext) -> Result<(), String> {
         context.actions.push(DpdkAction::from(
             rte_flow_action_type_RTE_FLOW_ACTION_TYPE_END,
             None,
         ));
         context.build_raw_actions();
         input.remove(0);
+        Ok(())
     }
 }
 
@@ -227,12 +262,34 @@ impl ActionOps for ActionDrop {
     fn name(&self) -> &str {
         "drop"
     }
-    fn parse_action(&self, input: &mut Vec<String>, context: &mut ActionsParserContext) {
+    fn parse_action(&self, input: &mut Vec<String>, context: &mut ActionsParserContext) -> Result<(), String> {
         context.actions.push(DpdkAction::from(
             rte_flow_action_type_RTE_FLOW_ACTION_TYPE_DROP,
             None,
         ));
         input.remove(0);
+        Ok(())
+    }
+}
+
+struct ActionCount;
+impl ActionCount {
+    fn new() -> Box<dyn ActionOps> {
+        Box::new(ActionCount)
+    }
+}
+
+impl ActionOps for ActionCount {
+    fn name(&self) -> &str {
+        "count"
+    }
+    fn parse_action(&self, input: &mut Vec<String>, context: &mut ActionsParserContext) -> Result<(), String> {
+        context.actions.push(DpdkAction::from(
+            rte_flow_action_type_RTE_FLOW_ACTION_TYPE_COUNT,
+            None,
+        ));
+        input.remove(0);
+        Ok(())
     }
 }
 
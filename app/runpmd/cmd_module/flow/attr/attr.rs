@@ -46,7 +46,7 @@ impl AttrContext {
 
 trait AttrOps {
     fn name(&self) -> &str;
-    fn parse_attr(&self, input: &mut Vec<String>, context: &mut AttrContext);
+    fn parse_attr(&self, input: &mut Vec<String>, context: &mut AttrContext) -> Result<(), String>;
 }
 
 type AttrMap = HashMap<String, Box<dyn AttrOps>>;
@@ -75,20 +75,29 @@ impl FlowAttributes {
         attr
     }
 
-    pub fn parse_attr(&mut self, input: &mut Vec<String>) {
+    pub fn parse_attr(&mut self, input: &mut Vec<String>) -> Result<(), String> {
         loop {
-            match self.map.get(&input[0]) {
+            match input.first().and_then(|word| self.map.get(word)) {
                 None => {
                     self.context.build_raw_attr();
-                    return;
+                    return Ok(());
                 }
                 Some(op) => {
-                    op.parse_attr(input, &mut self.context);
+                    op.parse_attr(input, &mut self.context)?;
                 }
             }
         }
     }
 
+    // Attribute names.
+    pub fn keywords(&self) -> Vec<String> {
+        self.map.keys().cloned().collect()
+    }
+
+    pub fn raw_attr(&self) -> &rte_flow_attr {
+        &self.context.raw_attr
+    }
+
     pub fn get_raw_attr(&self) -> *const rte_flow_attr {
         &self.context.raw_attr as *const rte_flow_attr
     }
@@ -98,8 +107,11 @@ impl AttrOps for Param {
     fn name(&self) -> &str {
         &self.name
     }
-    fn parse_attr(&self, input: &mut Vec<String>, context: &mut AttrContext) {
-        let arg = ArgInt::<u32>::new().strton(&input[1]).unwrap();
+    fn parse_attr(&self, input: &mut Vec<String>, context: &mut AttrContext) -> Result<(), String> {
+        let value = input.get(1).ok_or(format!("{}: missing value", self.name))?;
+        let arg = ArgInt::<u32>::new()
+            .strton(value)
+            .map_err(|err| format!("{}: {err}", self.name))?;
 
         match input[0].as_str() {
             "group" => {
@@ -108,11 +120,12 @@ impl AttrOps for Param {
             "priority" => {
                 context.priority = Some(arg);
             }
-            _ => return,
+            _ => return Ok(()),
         }
 
         input.remove(0);
         input.remove(0);
+        Ok(())
     }
 }
 
@@ -134,9 +147,10 @@ impl AttrOps for Ingress {
     fn name(&self) -> &str {
         "ingress"
     }
-    fn parse_attr(&self, input: &mut Vec<String>, context: &mut AttrContext) {
+    fn parse_attr(&self, input: &mut Vec<String>, context: &mut AttrContext) -> Result<(), String> {
         context.domain = Some(Domain::Ingress);
         input.remove(0);
+        Ok(())
     }
 }
 
@@ -152,9 +166,10 @@ impl AttrOps for Egress {
     fn name(&self) -> &str {
         "egress"
     }
-    fn parse_attr(&self, input: &mut Vec<String>, context: &mut AttrContext) {
+    fn parse_attr(&self, input: &mut Vec<String>, context: &mut AttrContext) -> Result<(), String> {
         context.domain = Some(Domain::Egress);
         input.remove(0);
+        Ok(())
     }
 }
 
@@ -168,8 +183,9 @@ impl AttrOps for Transfer {
     fn name(&self) -> &str {
         "transfer"
     }
-    fn parse_attr(&self, input: &mut Vec<String>, context: &mut AttrContext) {
+    fn parse_attr(&self, input: &mut Vec<String>, context: &mut AttrContext) -> Result<(), String> {
         context.domain = Some(Domain::Transfer);
         input.remove(0);
+        Ok(())
     }
 }
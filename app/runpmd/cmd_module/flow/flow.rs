@@ -4,16 +4,28 @@ pub mod actions;
 pub mod attr;
 #[path = "items/items.rs"]
 pub mod items;
+#[path = "table.rs"]
+pub mod table;
 
 
 use actions::FlowActions;
 use attr::FlowAttributes;
 use items::FlowItems;
-use rdpdk::dpdk_raw::rte_ethdev::{rte_flow_create, rte_flow_error};
+use table::{FlowEntry, FlowTable};
+use rdpdk::dpdk_raw::rte_ethdev::{
+    rte_flow_action, rte_flow_create, rte_flow_error, rte_flow_query, rte_flow_query_count,
+    rte_flow_validate, rust_get_rte_errno,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_COUNT,
+};
+use rdpdk::flow::error::FlowError;
+use rdpdk::flow::Flow;
 use std::collections::HashMap;
 use std::mem;
+use std::ptr::NonNull;
 use std::str::FromStr;
+use std::sync::Arc;
 use crate::cmd_module::CmdModuleOps;
+use crate::RunPmd;
 
 //           ┌──────────────────────────────────┐
 //           │                                  │
@@ -24,46 +36,211 @@ use crate::cmd_module::CmdModuleOps;
 //                          │   dyn FlowCmdOps
 //                          ▼
 //           ┌───────────────────────────────────────────┐
-//           │ ┌────────────────┐ ┌───────────────┐      │
-//           │ │                │ │               │      │
-//           │ │ FlowCreateCmd  │ │FlowRemoveCmd  │      │
-//           │ │                │ │               │      │
-//           │ └────────────────┘ └───────────────┘      │
-//           └───────────────────────────────────────────┘
+//           │ ┌────────────────┐ ┌────────────────┐     │
+//           │ │                │ │                │     │
+//           │ │ FlowCreateCmd  │ │FlowValidateCmd │     │
+//           │ │                │ │                │     │
+//           │ └────────────────┘ └────────────────┘     │
+//           │ ┌────────────────┐ ┌────────────────┐     │
+//           │ │ FlowDestroyCmd │ │  FlowQueryCmd  │     │
+//           │ └────────────────┘ └────────────────┘     │
+//           │ ┌──────────────┐ ┌─────────────┐ ┌──────┐ │
+//           │ │ FlowFlushCmd │ │ FlowListCmd │ │ ...  │ │
+//           │ └──────────────┘ └─────────────┘ └──────┘ │
+//           └─────────────────────┬─────────────────────┘
+//                                 │
+//                                 ▼
+//                    FlowTable, rules by port and ID
 //
 
 type CmdMap = HashMap<String, Box<dyn FlowCmdOps>>;
 
 pub struct FlowCmd {
     commands: CmdMap,
+    runpmd: Arc<RunPmd>,
 }
 
 unsafe impl Send for FlowCmd {}
 unsafe impl Sync for FlowCmd {}
 
 impl FlowCmd {
-    pub fn new() -> Self {
+    pub fn new(runpmd: Arc<RunPmd>) -> Self {
         let mut map = FlowCmd {
             commands: CmdMap::new(),
+            runpmd,
         };
-        map.commands.insert("create".to_string(), FlowCreateCmd::new());
+        for op in [
+            FlowCreateCmd::new(),
+            FlowValidateCmd::new(),
+            FlowDestroyCmd::new(),
+            FlowFlushCmd::new(),
+            FlowListCmd::new(),
+            FlowQueryCmd::new(),
+        ] {
+            map.commands.insert(op.name().to_string(), op);
+        }
         map
     }
 }
 
+// Position in a flow rule, used by tab completion.
+enum RuleState {
+    Attr,
+    Item(Option<String>),
+    PatternEnd,
+    Action(Option<String>),
+    Done,
+}
+
+// Words expected before the next keyword.
+enum Pending {
+    None,
+    Value,
+    Modifier,
+}
+
+// Keywords that can follow `words` of a flow rule:
+// <attributes> pattern <items> / ... end actions <actions> / ... end
+fn complete_rule(words: &[String]) -> Vec<String> {
+    let attrs = FlowAttributes::new();
+    let items = FlowItems::new();
+    let actions = FlowActions::new();
+    let mut state = RuleState::Attr;
+    let mut pending = Pending::None;
+
+    for word in words {
+        match pending {
+            Pending::Modifier => {
+                pending = Pending::Value;
+                continue;
+            }
+            Pending::Value => {
+                pending = Pending::None;
+                continue;
+            }
+            Pending::None => (),
+        }
+        state = match (state, word.as_str()) {
+            (RuleState::Attr, "pattern") => RuleState::Item(None),
+            (RuleState::Attr, "group" | "priority") => {
+                pending = Pending::Value;
+                RuleState::Attr
+            }
+            (RuleState::Item(_), "end") => RuleState::PatternEnd,
+            (RuleState::Item(_), "/") => RuleState::Item(None),
+            (RuleState::Item(None), item) => RuleState::Item(Some(item.to_string())),
+            (RuleState::Item(Some(item)), _) => {
+                pending = Pending::Modifier;
+                RuleState::Item(Some(item))
+            }
+            (RuleState::PatternEnd, "actions") => RuleState::Action(None),
+            (RuleState::Action(_), "end") => RuleState::Done,
+            (RuleState::Action(_), "/") => RuleState::Action(None),
+            (RuleState::Action(None), action) => RuleState::Action(Some(action.to_string())),
+            (RuleState::Action(Some(action)), _) => {
+                pending = Pending::Value;
+                RuleState::Action(Some(action))
+            }
+            (state, _) => state,
+        };
+    }
+
+    let without_separator = |keywords: Vec<String>| {
+        keywords.into_iter().filter(|keyword| keyword != "/").collect::<Vec<_>>()
+    };
+    let mut keywords = match (pending, state) {
+        (Pending::Modifier, _) => ["is", "spec", "mask", "last", "prefix"].map(String::from).to_vec(),
+        (Pending::Value, _) => Vec::new(),
+        (_, RuleState::Attr) => [attrs.keywords(), vec!["pattern".to_string()]].concat(),
+        (_, RuleState::Item(None)) => without_separator(items.keywords()),
+        (_, RuleState::Item(Some(item))) => [items.fields(&item), vec!["/".to_string()]].concat(),
+        (_, RuleState::PatternEnd) => vec!["actions".to_string()],
+        (_, RuleState::Action(None)) => without_separator(actions.keywords()),
+        (_, RuleState::Action(Some(action))) => [actions.params(&action), vec!["/".to_string()]].concat(),
+        (_, RuleState::Done) => Vec::new(),
+    };
+    keywords.sort();
+    keywords
+}
+
 impl CmdModuleOps for FlowCmd {
-    fn parse_cmd(&self, input: &mut Vec<String>) {
+    fn parse_cmd(&self, input: &mut Vec<String>) -> Result<(), String> {
         input.remove(0); // flow
+        match input.first() {
+            Some(cmd) if self.commands.contains_key(cmd) => (),
+            Some(cmd) => return Err(format!("unknown flow command: {cmd}")),
+            None => return Err("missing flow command".to_string()),
+        }
+        let mut flows = self.runpmd.flows.lock().unwrap();
         loop {
-            match self.commands.get(&input[0]) {
-                None => return,
-                Some(op) => op.parse(input),
-            }
-            if input.len() == 0 {
-                return;
+            match input.first().and_then(|cmd| self.commands.get(cmd)) {
+                None => return Ok(()),
+                Some(op) => op.parse(input, &mut flows)?,
             }
         }
     }
+
+    // flow <command> <port> <rule>
+    fn complete(&self, input: &[String]) -> Vec<String> {
+        match (input.get(1).map(|cmd| cmd.as_str()), input.len()) {
+            (_, 1) => self.commands.keys().cloned().collect(),
+            (_, 2) => Vec::new(),
+            (Some("create" | "validate"), _) => complete_rule(&input[3..]),
+            (Some("destroy"), len) if len % 2 == 1 => vec!["rule".to_string()],
+            (Some("query"), 4) => vec!["count".to_string()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+// Flow rule of `flow create` and `flow validate`.
+struct ParsedRule {
+    port: u16,
+    attr: FlowAttributes,
+    items: FlowItems,
+    actions: FlowActions,
+}
+
+impl ParsedRule {
+    // <command> <port> <attributes> pattern <items> end actions <actions> end
+    fn parse(input: &mut Vec<String>, command: &str) -> Result<Self, String> {
+        let usage = format!("flow {command} <port> <attributes> pattern <items> end actions <actions> end");
+        let port = parse_port(input, &usage)?;
+
+        let mut attr: FlowAttributes = FlowAttributes::new();
+        attr.parse_attr(input)?;
+
+        parse_keyword(input, "pattern", &usage)?;
+
+        let mut items: FlowItems = FlowItems::new();
+        items.parse_pattern(input)?;
+        if items.raw_pattern().is_empty() {
+            return Err(format!("usage: {usage}"));
+        }
+
+        parse_keyword(input, "actions", &usage)?;
+
+        let mut actions: FlowActions = FlowActions::new();
+        actions.parse_actions(input)?;
+        if actions.raw_actions().is_empty() {
+            return Err(format!("usage: {usage}"));
+        }
+
+        Ok(ParsedRule { port, attr, items, actions })
+    }
+
+    // Decodes the `rte_flow_error` of a failed call on the rule.
+    fn error(&self, call: &'static str, errno: Option<i32>, flow_error: &rte_flow_error) -> String {
+        FlowError::from_raw(
+            call,
+            self.port,
+            errno,
+            flow_error,
+            self.items.raw_pattern(),
+            self.actions.raw_actions(),
+        )
+        .to_string()
+    }
 }
 
 struct FlowCreateCmd;
@@ -77,48 +254,258 @@ impl FlowCmdOps for FlowCreateCmd {
     fn name(&self) -> &str {
         "create"
     }
-    fn parse(&self, input: &mut Vec<String>) {
-        let port = u16::from_str(&input[1]).unwrap();
-
-        let mut attr: FlowAttributes = FlowAttributes::new();
-        input.remove(0); // create
-        input.remove(0); // <port>
-        attr.parse_attr(input);
-
-        input.remove(0); // pattern
-
-        let mut items: FlowItems = FlowItems::new();
-        items.parse_pattern(input);
-
-        input.remove(0); // actions
-
-        let mut actions: FlowActions = FlowActions::new();
-        actions.parse_actions(input);
+    fn parse(&self, input: &mut Vec<String>, flows: &mut FlowTable) -> Result<(), String> {
+        let rule = ParsedRule::parse(input, self.name())?;
 
         let mut flow_error: rte_flow_error = unsafe { mem::zeroed() };
         let err_ptr: *mut rte_flow_error = &mut flow_error;
 
         let flow = unsafe {
             rte_flow_create(
-                port,
-                attr.get_raw_attr(),
-                items.get_raw_pattern(),
-                actions.get_raw_actions(),
+                rule.port,
+                rule.attr.get_raw_attr(),
+                rule.items.get_raw_pattern(),
+                rule.actions.get_raw_actions(),
                 err_ptr,
             )
         };
 
-        if flow as u64 != 0 {
-            println!("Flow created");
+        if let Some(flow) = NonNull::new(flow) {
+            let entry = FlowEntry::new(
+                rule.attr.raw_attr(),
+                rule.items.names().to_vec(),
+                rule.actions.names().to_vec(),
+                unsafe { Flow::from_raw(rule.port, flow) },
+            );
+            let id = flows.insert(rule.port, entry);
+            println!("Flow rule #{id} created");
+            Ok(())
         } else {
-            println!("Flow failed");
+            // rte_flow_create reports the error code in rte_errno only
+            let errno = -unsafe { rust_get_rte_errno() };
+            Err(rule.error("rte_flow_create", Some(errno), &flow_error))
+        }
+    }
+}
+
+// flow validate <port> <attributes> pattern <items> end actions <actions> end
+struct FlowValidateCmd;
+impl FlowValidateCmd {
+    pub fn new() -> Box<dyn FlowCmdOps> {
+        Box::new(FlowValidateCmd)
+    }
+}
+
+impl FlowCmdOps for FlowValidateCmd {
+    fn name(&self) -> &str {
+        "validate"
+    }
+    fn parse(&self, input: &mut Vec<String>, _flows: &mut FlowTable) -> Result<(), String> {
+        let rule = ParsedRule::parse(input, self.name())?;
+
+        let mut flow_error: rte_flow_error = unsafe { mem::zeroed() };
+        let rc = unsafe {
+            rte_flow_validate(
+                rule.port,
+                rule.attr.get_raw_attr(),
+                rule.items.get_raw_pattern(),
+                rule.actions.get_raw_actions(),
+                &mut flow_error,
+            )
+        };
+        if rc < 0 {
+            return Err(rule.error("rte_flow_validate", Some(rc), &flow_error));
         }
+        println!("Flow rule validated");
+        Ok(())
+    }
+}
+
+// Takes `<command> <port>` from the input.
+fn parse_port(input: &mut Vec<String>, usage: &str) -> Result<u16, String> {
+    match input.get(1).map(|port| u16::from_str(port)) {
+        Some(Ok(port)) => {
+            input.drain(..2);
+            Ok(port)
+        }
+        _ => {
+            input.clear();
+            Err(format!("usage: {usage}"))
+        }
+    }
+}
+
+// Takes `keyword` from the input.
+fn parse_keyword(input: &mut Vec<String>, keyword: &str, usage: &str) -> Result<(), String> {
+    match input.first() {
+        Some(word) if word == keyword => {
+            input.remove(0);
+            Ok(())
+        }
+        _ => {
+            input.clear();
+            Err(format!("usage: {usage}"))
+        }
+    }
+}
+
+// Takes a rule ID from the input.
+fn parse_rule_id(input: &mut Vec<String>, port: u16, flows: &FlowTable) -> Result<u32, String> {
+    if input.is_empty() {
+        return Err("missing flow rule ID".to_string());
+    }
+    let id = input.remove(0);
+    match u32::from_str(&id) {
+        Ok(id) if flows.get(port, id).is_some() => Ok(id),
+        Ok(_) => Err(format!("port {port}: no flow rule #{id}")),
+        Err(_) => Err(format!("invalid flow rule ID {id}")),
+    }
+}
+
+// flow destroy <port> rule <id> [rule <id> ...]
+struct FlowDestroyCmd;
+impl FlowDestroyCmd {
+    pub fn new() -> Box<dyn FlowCmdOps> {
+        Box::new(FlowDestroyCmd)
+    }
+}
+
+impl FlowCmdOps for FlowDestroyCmd {
+    fn name(&self) -> &str {
+        "destroy"
+    }
+    fn parse(&self, input: &mut Vec<String>, flows: &mut FlowTable) -> Result<(), String> {
+        let usage = "flow destroy <port> rule <id> [rule <id> ...]";
+        let port = parse_port(input, usage)?;
+        if input.first().map(|word| word.as_str()) != Some("rule") {
+            input.clear();
+            return Err(format!("usage: {usage}"));
+        }
+
+        let mut result = Ok(());
+        while input.first().map(|word| word.as_str()) == Some("rule") {
+            if input.len() < 2 {
+                input.clear();
+                return Err(format!("usage: {usage}"));
+            }
+            input.remove(0); // rule
+            let id = match parse_rule_id(input, port, flows) {
+                Ok(id) => id,
+                Err(err) => {
+                    result = Err(err);
+                    continue;
+                }
+            };
+            match flows.remove(port, id).unwrap().flow.destroy() {
+                Ok(()) => println!("Flow rule #{id} destroyed"),
+                Err(err) => result = Err(err.to_string()),
+            }
+        }
+        result
+    }
+}
+
+// flow flush <port>
+struct FlowFlushCmd;
+impl FlowFlushCmd {
+    pub fn new() -> Box<dyn FlowCmdOps> {
+        Box::new(FlowFlushCmd)
+    }
+}
+
+impl FlowCmdOps for FlowFlushCmd {
+    fn name(&self) -> &str {
+        "flush"
+    }
+    fn parse(&self, input: &mut Vec<String>, flows: &mut FlowTable) -> Result<(), String> {
+        let port = parse_port(input, "flow flush <port>")?;
+        rdpdk::flow::flush(port).map_err(|err| err.to_string())?;
+        flows.clear(port);
+        Ok(())
+    }
+}
+
+// flow list <port>
+struct FlowListCmd;
+impl FlowListCmd {
+    pub fn new() -> Box<dyn FlowCmdOps> {
+        Box::new(FlowListCmd)
+    }
+}
+
+impl FlowCmdOps for FlowListCmd {
+    fn name(&self) -> &str {
+        "list"
+    }
+    fn parse(&self, input: &mut Vec<String>, flows: &mut FlowTable) -> Result<(), String> {
+        let port = parse_port(input, "flow list <port>")?;
+        println!("{:<7} {:<7} {:<7} {:<7} Rule", "ID", "Group", "Prio", "Attr");
+        for (id, entry) in flows.rules(port) {
+            println!(
+                "{:<7} {:<7} {:<7} {:<7} {}",
+                id,
+                entry.group,
+                entry.priority,
+                entry.attr,
+                entry.summary()
+            );
+        }
+        Ok(())
+    }
+}
+
+// flow query <port> <id> count
+struct FlowQueryCmd;
+impl FlowQueryCmd {
+    pub fn new() -> Box<dyn FlowCmdOps> {
+        Box::new(FlowQueryCmd)
+    }
+}
+
+impl FlowCmdOps for FlowQueryCmd {
+    fn name(&self) -> &str {
+        "query"
+    }
+    fn parse(&self, input: &mut Vec<String>, flows: &mut FlowTable) -> Result<(), String> {
+        let usage = "flow query <port> <id> count";
+        let port = parse_port(input, usage)?;
+        if input.len() < 2 || input[1] != "count" {
+            input.clear();
+            return Err(format!("usage: {usage}"));
+        }
+        let id = parse_rule_id(input, port, flows);
+        input.remove(0); // count
+        let id = id?;
+
+        let action = rte_flow_action {
+            type_: rte_flow_action_type_RTE_FLOW_ACTION_TYPE_COUNT,
+            conf: std::ptr::null(),
+        };
+        let mut count: rte_flow_query_count = unsafe { mem::zeroed() };
+        let mut flow_error: rte_flow_error = unsafe { mem::zeroed() };
+        let rc = unsafe {
+            rte_flow_query(
+                port,
+                flows.get(port, id).unwrap().flow.as_raw(),
+                &action,
+                &mut count as *mut rte_flow_query_count as *mut std::os::raw::c_void,
+                &mut flow_error,
+            )
+        };
+        FlowError::check(rc, "rte_flow_query", port, &flow_error).map_err(|err| err.to_string())?;
+
+        println!("COUNT:");
+        println!(" hits_set: {}", count.hits_set());
+        println!(" bytes_set: {}", count.bytes_set());
+        println!(" hits: {}", count.hits);
+        println!(" bytes: {}", count.bytes);
+        Ok(())
     }
 }
 
 pub trait FlowCmdOps: Send + Sync {
     fn name(&self) -> &str;
 
-    fn parse(&self, input: &mut Vec<String>);
+    fn parse(&self, input: &mut Vec<String>, flows: &mut FlowTable) -> Result<(), String>;
 }
 
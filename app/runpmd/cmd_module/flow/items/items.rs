@@ -1,5 +1,6 @@
 mod l2net;
 mod l3net;
+mod l4net;
 
 use rdpdk::cmdline::arg::{ArgData};
 use rdpdk::cmdline::param::Param;
@@ -13,7 +14,7 @@ use std::ptr::addr_of;
 
 pub trait ItemOps {
     fn name(&self) -> &str;
-    fn parse_item(&self, input: &mut Vec<String>, context: &mut ItemsParserContext);
+    fn parse_item(&self, input: &mut Vec<String>, context: &mut ItemsParserContext) -> Result<(), String>;
 }
 
 impl ItemOps for Param {
@@ -21,29 +22,45 @@ impl ItemOps for Param {
         &self.name
     }
 
-    fn parse_item(&self, input: &mut Vec<String>, context: &mut ItemsParserContext) {
+    fn parse_item(&self, input: &mut Vec<String>, context: &mut ItemsParserContext) -> Result<(), String> {
         if self.id.is_some() {
             context.id = self.id.unwrap() as rte_flow_item_type;
             context.size = self.size.unwrap();
             input.remove(0);
         } else if self.args.is_some() {
             let (arg_ops, arg_offset) = self.args.as_ref().unwrap().get(0).unwrap();
-            let arg = arg_ops.serialize(&input[2]).unwrap();
-            let src = &arg.data[0..arg.size];
-            match input[1].as_str() {
-                "is" => context.data.as_mut().unwrap().is_mod(src, *arg_offset),
-                "spec" => context.data.as_mut().unwrap().spec_mod(src, *arg_offset),
-                "mask" => context.data.as_mut().unwrap().mask_mod(src, *arg_offset),
-                "last" => context.data.as_mut().unwrap().last_mod(src, *arg_offset),
-                _ => {
-                    panic!("Unknown item modifier '{}'", input[1]);
+            let (modifier, value) = match (input.get(1), input.get(2)) {
+                (Some(modifier), Some(value)) => (modifier.as_str(), value.as_str()),
+                _ => return Err(format!("{}: missing modifier or value", self.name)),
+            };
+            let arg = match modifier {
+                "prefix" => {
+                    let len = value
+                        .parse::<usize>()
+                        .map_err(|_| format!("{}: invalid prefix length \"{value}\"", self.name))?;
+                    arg_ops.prefix(len).map_err(|err| format!("{}: {err}", self.name))?
                 }
+                _ => arg_ops.serialize(value).map_err(|err| format!("{}: {err}", self.name))?,
             };
+            let src = &arg.data[0..arg.size];
+            let data = context.data.as_mut().unwrap();
+            match modifier {
+                "is" => {
+                    let mask = arg_ops.mask(&arg);
+                    data.is_mod(src, &mask.data[0..mask.size], *arg_offset)
+                }
+                "spec" => data.spec_mod(src, *arg_offset),
+                "mask" | "prefix" => data.mask_mod(src, *arg_offset),
+                "last" => data.last_mod(src, *arg_offset),
+                _ => Err(format!("unknown item modifier \"{modifier}\"")),
+            }
+            .map_err(|err| format!("{}: {err}", self.name))?;
 
             for _ in 0..3 {
                 input.remove(0);
             }
         }
+        Ok(())
     }
 }
 
@@ -71,19 +88,19 @@ impl Item {
         }
     }
 
-    pub fn parse(&self, input: &mut Vec<String>, context: &mut ItemsParserContext) {
-        self.cmd.parse_item(input, context);
+    pub fn parse(&self, input: &mut Vec<String>, context: &mut ItemsParserContext) -> Result<(), String> {
+        self.cmd.parse_item(input, context)?;
         match self.param {
-            None => (),
+            None => Ok(()),
             Some(ref pdb) => {
                 if context.data.is_none() {
                     context.data = Some(ItemData::new());
                 }
                 loop {
-                    match pdb.get(&input[0]) {
-                        None => return,
+                    match input.first().and_then(|word| pdb.get(word)) {
+                        None => return Ok(()),
                         Some(op) => {
-                            op.parse_item(input, context);
+                            op.parse_item(input, context)?;
                         }
                     }
                 }
@@ -96,6 +113,7 @@ type ItemsMap = HashMap<String, Item>;
 pub struct FlowItems {
     map: RefCell<ItemsMap>,
     pub context: ItemsParserContext,
+    names: Vec<String>, // parsed item names
 }
 
 impl FlowItems {
@@ -103,13 +121,21 @@ impl FlowItems {
         let mut map = FlowItems {
             map: RefCell::new(ItemsMap::new()),
             context: ItemsParserContext::new(),
+            names: Vec::new(),
         };
 
         map.register(Item::from(ItemEnd::new(), None));
         map.register(Item::from(ItemSeparator::new(), None));
 
         l2net::flow_item_create_eth(&mut map);
+        l2net::flow_item_create_vlan(&mut map);
+        l2net::flow_item_create_mpls(&mut map);
         l3net::flow_item_create_ipv4(&mut map);
+        l3net::flow_item_create_ipv6(&mut map);
+        l3net::flow_item_create_icmp(&mut map);
+        l3net::flow_item_create_icmp6(&mut map);
+        l4net::flow_item_create_udp(&mut map);
+        l4net::flow_item_create_tcp(&mut map);
         map
     }
 
@@ -119,17 +145,47 @@ impl FlowItems {
             .insert(item.cmd.name().to_string(), item);
     }
 
-    pub fn parse_pattern(&mut self, input: &mut Vec<String>) {
+    // Item names, `/` and `end` included.
+    pub fn keywords(&self) -> Vec<String> {
+        self.map.borrow().keys().cloned().collect()
+    }
+
+    // Field names of an item.
+    pub fn fields(&self, item: &str) -> Vec<String> {
+        match self.map.borrow().get(item).and_then(|item| item.param.as_ref()) {
+            Some(pdb) => pdb.keys().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn parse_pattern(&mut self, input: &mut Vec<String>) -> Result<(), String> {
         loop {
-            match self.map.borrow().get(&input[0]) {
-                None => return,
+            let name = match input.first() {
+                None => return Ok(()),
+                Some(name) => name.clone(),
+            };
+            match self.map.borrow().get(&name) {
+                None => return Ok(()),
                 Some(item) => {
-                    item.parse(input, &mut self.context);
+                    if !matches!(name.as_str(), "/" | "end") {
+                        self.names.push(name);
+                    }
+                    item.parse(input, &mut self.context)?;
                 }
             }
         }
     }
 
+    // Names of the parsed items, `/` and `end` excluded.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    // END terminated pattern, empty until `end` is parsed.
+    pub fn raw_pattern(&self) -> &[rte_flow_item] {
+        &self.context.raw
+    }
+
     pub fn get_raw_pattern(&self) -> *const rte_flow_item {
         self.context.raw.as_ptr() as *const rte_flow_item
     }
@@ -150,21 +206,22 @@ impl ItemData {
         }
     }
 
-    pub fn spec_mod(&mut self, src: &[u8], offset: usize) {
-        self.spec.or_from_slice(src, offset).unwrap();
+    pub fn spec_mod(&mut self, src: &[u8], offset: usize) -> Result<(), String> {
+        self.spec.or_from_slice(src, offset)
     }
 
-    pub fn mask_mod(&mut self, src: &[u8], offset: usize) {
-        self.mask.or_from_slice(src, offset).unwrap();
+    pub fn mask_mod(&mut self, src: &[u8], offset: usize) -> Result<(), String> {
+        self.mask.or_from_slice(src, offset)
     }
 
-    pub fn last_mod(&mut self, src: &[u8], offset: usize) {
-        self.last.or_from_slice(src, offset).unwrap();
+    pub fn last_mod(&mut self, src: &[u8], offset: usize) -> Result<(), String> {
+        self.last.or_from_slice(src, offset)
     }
 
-    pub fn is_mod(&mut self, src: &[u8], offset: usize) {
-        self.spec_mod(src, offset);
-        self.mask_mod(&ArgData::DEFAULT_MASK[0..src.len()], offset);
+    // Spec and mask of a whole field, `mask` selects the field bits in `src`.
+    pub fn is_mod(&mut self, src: &[u8], mask: &[u8], offset: usize) -> Result<(), String> {
+        self.spec_mod(src, offset)?;
+        self.mask_mod(mask, offset)
     }
 }
 
@@ -244,13 +301,14 @@ impl ItemOps for ItemSeparator {
         "/"
     }
 
-    fn parse_item(&self, input: &mut Vec<String>, context: &mut ItemsParserContext) {
+    fn parse_item(&self, input: &mut Vec<String>, context: &mut ItemsParserContext) -> Result<(), String> {
         context.items.push(DpdkItem::from(
             context.id as rte_flow_item_type,
             context.data.take(),
         ));
         context.flush();
         input.remove(0);
+        Ok(())
     }
 }
 
@@ -267,7 +325,7 @@ impl ItemOps for ItemEnd {
         "end"
     }
 
-    fn parse_item(&self, input: &mut Vec<String>, context: &mut ItemsParserContext) {
+    fn parse_item(&self, input: &mut Vec<String>, context: &mut ItemsParserContext) -> Result<(), String> {
         context.items.push(DpdkItem::from(
             rte_flow_item_type_RTE_FLOW_ITEM_TYPE_END,
             None,
@@ -275,6 +333,7 @@ impl ItemOps for ItemEnd {
         context.build_raw_pattern();
         context.flush();
         input.remove(0);
+        Ok(())
     }
 }
 
@@ -2,14 +2,24 @@ use std::mem::offset_of;
 use rdpdk::dpdk_raw::ethdev_driver::rte_ether_hdr;
 use rdpdk::dpdk_raw::rte_ethdev::{
     rte_flow_item_eth,
-    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ETH
+    rte_flow_item_vlan,
+    rte_flow_item_mpls,
+    rte_vlan_hdr,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ETH,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_VLAN,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_MPLS,
 };
 use rdpdk::cmdline::arg::Arg;
-use rdpdk::cmdline::arg::arg_int::{ArgInt, ByteOrder};
+use rdpdk::cmdline::arg::arg_int::{ArgBitField, ArgInt, ByteOrder};
 use rdpdk::cmdline::arg::arg_net::EthAddrArg;
 use rdpdk::cmdline::param::Param;
 use crate::cmd_module::flow::items::{FlowItems, Item};
 
+// First C bit-field of a 32 bits unit, `has_vlan` or `has_more_vlan`.
+fn first_bit_field() -> ArgBitField {
+    let shift = if cfg!(target_endian = "little") { 0 } else { 31 };
+    ArgBitField::new_with_order(4, shift, 1, ByteOrder::HostOrder)
+}
 
 pub(super) fn flow_item_create_eth(items_db: &mut FlowItems) {
     let src_arg: Box<dyn Arg> = Box::new(EthAddrArg::new());
@@ -39,6 +49,16 @@ pub(super) fn flow_item_create_eth(items_db: &mut FlowItems) {
         Some(vec![(type_arg, type_offset)]),
     ));
 
+    // tagged packets of any TPID, `type` matches a given one
+    let has_vlan_arg: Box<dyn Arg> = Box::new(first_bit_field());
+    let has_vlan_offset = offset_of!(rte_flow_item_eth, _bitfield_1);
+    let has_vlan = Box::new(Param::from(
+        "has_vlan",
+        None,
+        None,
+        Some(vec![(has_vlan_arg, has_vlan_offset)]),
+    ));
+
     let eth = Box::new(Param::from(
         "eth",
         Some(rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ETH as isize),
@@ -46,5 +66,118 @@ pub(super) fn flow_item_create_eth(items_db: &mut FlowItems) {
         None,
     ));
 
-    items_db.register(Item::from(eth, Some(vec![src, dst, eth_type])));
+    items_db.register(Item::from(eth, Some(vec![src, dst, eth_type, has_vlan])));
+}
+
+// QinQ is a stack of vlan items, outer first: the eth `type` and the outer vlan
+// `inner_type` hold the TPID of the next tag, `has_more_vlan` matches any.
+pub(super) fn flow_item_create_vlan(items_db: &mut FlowItems) {
+    let tci_offset = offset_of!(rte_vlan_hdr, vlan_tci);
+    let tci_arg: Box<dyn Arg> = Box::new(ArgInt::<u16>::new_with_order(ByteOrder::BigEndian));
+    let tci = Box::new(Param::from(
+        "tci",
+        None,
+        None,
+        Some(vec![(tci_arg, tci_offset)]),
+    ));
+
+    let pcp_arg: Box<dyn Arg> = Box::new(ArgBitField::new(2, 13, 3));
+    let pcp = Box::new(Param::from(
+        "pcp",
+        None,
+        None,
+        Some(vec![(pcp_arg, tci_offset)]),
+    ));
+
+    let dei_arg: Box<dyn Arg> = Box::new(ArgBitField::new(2, 12, 1));
+    let dei = Box::new(Param::from(
+        "dei",
+        None,
+        None,
+        Some(vec![(dei_arg, tci_offset)]),
+    ));
+
+    let vid_arg: Box<dyn Arg> = Box::new(ArgBitField::new(2, 0, 12));
+    let vid = Box::new(Param::from(
+        "vid",
+        None,
+        None,
+        Some(vec![(vid_arg, tci_offset)]),
+    ));
+
+    let inner_type_arg: Box<dyn Arg> = Box::new(ArgInt::<u16>::new_with_order(ByteOrder::BigEndian));
+    let inner_type_offset = offset_of!(rte_vlan_hdr, eth_proto);
+    let inner_type = Box::new(Param::from(
+        "inner_type",
+        None,
+        None,
+        Some(vec![(inner_type_arg, inner_type_offset)]),
+    ));
+
+    let has_more_vlan_arg: Box<dyn Arg> = Box::new(first_bit_field());
+    let has_more_vlan_offset = offset_of!(rte_flow_item_vlan, _bitfield_1);
+    let has_more_vlan = Box::new(Param::from(
+        "has_more_vlan",
+        None,
+        None,
+        Some(vec![(has_more_vlan_arg, has_more_vlan_offset)]),
+    ));
+
+    let vlan = Box::new(Param::from(
+        "vlan",
+        Some(rte_flow_item_type_RTE_FLOW_ITEM_TYPE_VLAN as isize),
+        Some(size_of::<rte_flow_item_vlan>()),
+        None,
+    ));
+
+    items_db.register(Item::from(
+        vlan,
+        Some(vec![tci, pcp, dei, vid, inner_type, has_more_vlan]),
+    ));
+}
+
+pub(super) fn flow_item_create_mpls(items_db: &mut FlowItems) {
+    // label (20 bits), traffic class (3 bits), bottom of stack (1 bit)
+    let label_tc_s_offset = offset_of!(rte_flow_item_mpls, label_tc_s);
+    let label_arg: Box<dyn Arg> = Box::new(ArgBitField::new(3, 4, 20));
+    let label = Box::new(Param::from(
+        "label",
+        None,
+        None,
+        Some(vec![(label_arg, label_tc_s_offset)]),
+    ));
+
+    let tc_arg: Box<dyn Arg> = Box::new(ArgBitField::new(3, 1, 3));
+    let tc = Box::new(Param::from(
+        "tc",
+        None,
+        None,
+        Some(vec![(tc_arg, label_tc_s_offset)]),
+    ));
+
+    let s_arg: Box<dyn Arg> = Box::new(ArgBitField::new(3, 0, 1));
+    let s = Box::new(Param::from(
+        "s",
+        None,
+        None,
+        Some(vec![(s_arg, label_tc_s_offset)]),
+    ));
+
+    let ttl_arg: Box<dyn Arg> = Box::new(ArgInt::<u8>::new());
+    let ttl_offset = offset_of!(rte_flow_item_mpls, ttl);
+    let ttl = Box::new(Param::from(
+        "ttl",
+        None,
+        None,
+        Some(vec![(ttl_arg, ttl_offset)]),
+    ));
+
+    let mpls = Box::new(Param::from(
+        "mpls",
+        Some(rte_flow_item_type_RTE_FLOW_ITEM_TYPE_MPLS as isize),
+        Some(size_of::<rte_flow_item_mpls>()),
+        None,
+    ));
+
+    items_db.register(Item::from(mpls, Some(vec![label, tc, s, ttl])));
 }
@@ -1,10 +1,18 @@
 use std::mem::offset_of;
 use rdpdk::dpdk_raw::rte_ethdev::{
     rte_flow_item_type_RTE_FLOW_ITEM_TYPE_IPV4,
-    rte_ipv4_hdr};
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_IPV6,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ICMP,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ICMP6,
+    rte_flow_item_ipv6,
+    rte_flow_item_icmp,
+    rte_flow_item_icmp6,
+    rte_icmp_hdr,
+    rte_ipv4_hdr,
+    rte_ipv6_hdr};
 use rdpdk::cmdline::arg::Arg;
-use rdpdk::cmdline::arg::arg_int::ArgInt;
-use rdpdk::cmdline::arg::arg_net::Ipv4AddrArg;
+use rdpdk::cmdline::arg::arg_int::{ArgBitField, ArgInt};
+use rdpdk::cmdline::arg::arg_net::{Ipv4AddrArg, Ipv6AddrArg};
 use rdpdk::cmdline::param::Param;
 use crate::cmd_module::flow::items::{FlowItems, Item};
 
@@ -43,4 +51,124 @@ pub(super) fn flow_item_create_ipv4(items_db: &mut FlowItems) {
         None,
     ));
     items_db.register(Item::from(ipv4, Some(vec![src, dst, next_proto])));
-}
\ No newline at end of file
+}
+
+pub(super) fn flow_item_create_ipv6(items_db: &mut FlowItems) {
+    let src_arg: Box<dyn Arg> = Box::new(Ipv6AddrArg::new());
+    let src_offset = offset_of!(rte_ipv6_hdr, src_addr);
+    let src = Box::new(Param::from(
+        "src",
+        None,
+        None,
+        Some(vec![(src_arg, src_offset)]),
+    ));
+
+    let dst_arg: Box<dyn Arg> = Box::new(Ipv6AddrArg::new());
+    let dst_offset = offset_of!(rte_ipv6_hdr, dst_addr);
+    let dst = Box::new(Param::from(
+        "dst",
+        None,
+        None,
+        Some(vec![(dst_arg, dst_offset)]),
+    ));
+
+    // version (4 bits), traffic class (8 bits), flow label (20 bits)
+    let vtc_flow_offset = offset_of!(rte_ipv6_hdr, __bindgen_anon_1);
+    let tc_arg: Box<dyn Arg> = Box::new(ArgBitField::new(4, 20, 8));
+    let tc = Box::new(Param::from(
+        "tc",
+        None,
+        None,
+        Some(vec![(tc_arg, vtc_flow_offset)]),
+    ));
+
+    let flow_arg: Box<dyn Arg> = Box::new(ArgBitField::new(4, 0, 20));
+    let flow = Box::new(Param::from(
+        "flow",
+        None,
+        None,
+        Some(vec![(flow_arg, vtc_flow_offset)]),
+    ));
+
+    let proto_arg: Box<dyn Arg> = Box::new(ArgInt::<u8>::new());
+    let proto_offset = offset_of!(rte_ipv6_hdr, proto);
+    let proto = Box::new(Param::from(
+        "proto",
+        None,
+        None,
+        Some(vec![(proto_arg, proto_offset)]),
+    ));
+
+    let hop_arg: Box<dyn Arg> = Box::new(ArgInt::<u8>::new());
+    let hop_offset = offset_of!(rte_ipv6_hdr, hop_limits);
+    let hop = Box::new(Param::from(
+        "hop",
+        None,
+        None,
+        Some(vec![(hop_arg, hop_offset)]),
+    ));
+
+    let ipv6 = Box::new(Param::from(
+        "ipv6",
+        Some(rte_flow_item_type_RTE_FLOW_ITEM_TYPE_IPV6 as isize),
+        Some(size_of::<rte_flow_item_ipv6>()),
+        None,
+    ));
+    items_db.register(Item::from(ipv6, Some(vec![src, dst, tc, flow, proto, hop])));
+}
+
+pub(super) fn flow_item_create_icmp(items_db: &mut FlowItems) {
+    let type_arg: Box<dyn Arg> = Box::new(ArgInt::<u8>::new());
+    let type_offset = offset_of!(rte_icmp_hdr, icmp_type);
+    let icmp_type = Box::new(Param::from(
+        "type",
+        None,
+        None,
+        Some(vec![(type_arg, type_offset)]),
+    ));
+
+    let code_arg: Box<dyn Arg> = Box::new(ArgInt::<u8>::new());
+    let code_offset = offset_of!(rte_icmp_hdr, icmp_code);
+    let code = Box::new(Param::from(
+        "code",
+        None,
+        None,
+        Some(vec![(code_arg, code_offset)]),
+    ));
+
+    let icmp = Box::new(Param::from(
+        "icmp",
+        Some(rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ICMP as isize),
+        Some(size_of::<rte_flow_item_icmp>()),
+        None,
+    ));
+    items_db.register(Item::from(icmp, Some(vec![icmp_type, code])));
+}
+
+pub(super) fn flow_item_create_icmp6(items_db: &mut FlowItems) {
+    let type_arg: Box<dyn Arg> = Box::new(ArgInt::<u8>::new());
+    let type_offset = offset_of!(rte_flow_item_icmp6, type_);
+    let icmp6_type = Box::new(Param::from(
+        "type",
+        None,
+        None,
+        Some(vec![(type_arg, type_offset)]),
+    ));
+
+    let code_arg: Box<dyn Arg> = Box::new(ArgInt::<u8>::new());
+    let code_offset = offset_of!(rte_flow_item_icmp6, code);
+    let code = Box::new(Param::from(
+        "code",
+        None,
+        None,
+        Some(vec![(code_arg, code_offset)]),
+    ));
+
+    let icmp6 = Box::new(Param::from(
+        "icmp6",
+        Some(rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ICMP6 as isize),
+        Some(size_of::<rte_flow_item_icmp6>()),
+        None,
+    ));
+    items_db.register(Item::from(icmp6, Some(vec![icmp6_type, code])));
+}
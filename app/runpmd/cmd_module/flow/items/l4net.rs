@@ -0,0 +1,77 @@
+use std::mem::offset_of;
+use rdpdk::dpdk_raw::rte_ethdev::{
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_UDP,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_TCP,
+    rte_flow_item_udp,
+    rte_flow_item_tcp,
+    rte_udp_hdr,
+    rte_tcp_hdr};
+use rdpdk::cmdline::arg::Arg;
+use rdpdk::cmdline::arg::arg_int::{ArgInt, ByteOrder};
+use rdpdk::cmdline::param::Param;
+use crate::cmd_module::flow::items::{FlowItems, Item};
+
+pub(super) fn flow_item_create_udp(items_db: &mut FlowItems) {
+    let src_arg: Box<dyn Arg> = Box::new(ArgInt::<u16>::new_with_order(ByteOrder::BigEndian));
+    let src_offset = offset_of!(rte_udp_hdr, src_port);
+    let src = Box::new(Param::from(
+        "src",
+        None,
+        None,
+        Some(vec![(src_arg, src_offset)]),
+    ));
+
+    let dst_arg: Box<dyn Arg> = Box::new(ArgInt::<u16>::new_with_order(ByteOrder::BigEndian));
+    let dst_offset = offset_of!(rte_udp_hdr, dst_port);
+    let dst = Box::new(Param::from(
+        "dst",
+        None,
+        None,
+        Some(vec![(dst_arg, dst_offset)]),
+    ));
+
+    let udp = Box::new(Param::from(
+        "udp",
+        Some(rte_flow_item_type_RTE_FLOW_ITEM_TYPE_UDP as isize),
+        Some(size_of::<rte_flow_item_udp>()),
+        None,
+    ));
+    items_db.register(Item::from(udp, Some(vec![src, dst])));
+}
+
+pub(super) fn flow_item_create_tcp(items_db: &mut FlowItems) {
+    let src_arg: Box<dyn Arg> = Box::new(ArgInt::<u16>::new_with_order(ByteOrder::BigEndian));
+    let src_offset = offset_of!(rte_tcp_hdr, src_port);
+    let src = Box::new(Param::from(
+        "src",
+        None,
+        None,
+        Some(vec![(src_arg, src_offset)]),
+    ));
+
+    let dst_arg: Box<dyn Arg> = Box::new(ArgInt::<u16>::new_with_order(ByteOrder::BigEndian));
+    let dst_offset = offset_of!(rte_tcp_hdr, dst_port);
+    let dst = Box::new(Param::from(
+        "dst",
+        None,
+        None,
+        Some(vec![(dst_arg, dst_offset)]),
+    ));
+
+    let flags_arg: Box<dyn Arg> = Box::new(ArgInt::<u8>::new());
+    let flags_offset = offset_of!(rte_tcp_hdr, tcp_flags);
+    let flags = Box::new(Param::from(
+        "flags",
+        None,
+        None,
+        Some(vec![(flags_arg, flags_offset)]),
+    ));
+
+    let tcp = Box::new(Param::from(
+        "tcp",
+        Some(rte_flow_item_type_RTE_FLOW_ITEM_TYPE_TCP as isize),
+        Some(size_of::<rte_flow_item_tcp>()),
+        None,
+    ));
+    items_db.register(Item::from(tcp, Some(vec![src, dst, flags])));
+}
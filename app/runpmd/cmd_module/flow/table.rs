@@ -0,0 +1,85 @@
+use std::collections::{BTreeMap, HashMap};
+use rdpdk::dpdk_raw::rte_ethdev::rte_flow_attr;
+use rdpdk::flow::Flow;
+use std::mem;
+
+/// Flow rule created with `flow create`.
+pub struct FlowEntry {
+    pub group: u32,
+    pub priority: u32,
+    /// `i`, `e` and `t` for ingress, egress and transfer, `-` if not set.
+    pub attr: String,
+    /// Item names, without `end`.
+    pub pattern: Vec<String>,
+    /// Action names, without `end`.
+    pub actions: Vec<String>,
+    pub flow: Flow,
+}
+
+impl FlowEntry {
+    pub fn new(attr: &rte_flow_attr, pattern: Vec<String>, actions: Vec<String>, flow: Flow) -> Self {
+        let flag = |set: u32, c: char| if set != 0 { c } else { '-' };
+        FlowEntry {
+            group: attr.group,
+            priority: attr.priority,
+            attr: [flag(attr.ingress(), 'i'), flag(attr.egress(), 'e'), flag(attr.transfer(), 't')]
+                .iter()
+                .collect(),
+            pattern,
+            actions,
+            flow,
+        }
+    }
+
+    // testpmd `flow list` rule column, ETH IPV4 => DROP
+    pub fn summary(&self) -> String {
+        let upper = |names: &[String]| names.iter().map(|name| name.to_uppercase()).collect::<Vec<_>>().join(" ");
+        format!("{} => {}", upper(&self.pattern), upper(&self.actions))
+    }
+}
+
+#[derive(Default)]
+struct PortFlows {
+    next_id: u32,
+    rules: BTreeMap<u32, FlowEntry>,
+}
+
+/// Flow rules of each port by rule ID, IDs grow from 0 within a port and are not reused.
+#[derive(Default)]
+pub struct FlowTable {
+    ports: HashMap<u16, PortFlows>,
+}
+
+impl FlowTable {
+    /// Adds a rule and returns its ID.
+    pub fn insert(&mut self, port_id: u16, entry: FlowEntry) -> u32 {
+        let port = self.ports.entry(port_id).or_default();
+        let id = port.next_id;
+        port.next_id += 1;
+        port.rules.insert(id, entry);
+        id
+    }
+
+    pub fn get(&self, port_id: u16, id: u32) -> Option<&FlowEntry> {
+        self.ports.get(&port_id)?.rules.get(&id)
+    }
+
+    pub fn remove(&mut self, port_id: u16, id: u32) -> Option<FlowEntry> {
+        self.ports.get_mut(&port_id)?.rules.remove(&id)
+    }
+
+    /// Forgets the rules of a port after a flush or a port close, the rules themselves
+    /// are not destroyed.
+    pub fn clear(&mut self, port_id: u16) {
+        if let Some(port) = self.ports.get_mut(&port_id) {
+            for entry in mem::take(&mut port.rules).into_values() {
+                entry.flow.into_raw();
+            }
+        }
+    }
+
+    /// Rules of a port by increasing ID.
+    pub fn rules(&self, port_id: u16) -> impl Iterator<Item = (&u32, &FlowEntry)> {
+        self.ports.get(&port_id).into_iter().flat_map(|port| &port.rules)
+    }
+}
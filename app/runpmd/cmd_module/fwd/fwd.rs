@@ -0,0 +1,77 @@
+use std::sync::Arc;
+use crate::cmd_module::CmdModuleOps;
+use crate::fwd::{self, fwd_engine, fwd_engine_names};
+use crate::fwd::topology::PortTopology;
+use crate::RunPmd;
+
+// set fwd <mode>
+// set port-topology paired|chained|loop|custom <streams>
+// show config fwd
+// start
+// stop
+pub struct FwdCmd {
+    runpmd: Arc<RunPmd>,
+}
+
+impl FwdCmd {
+    pub fn new(runpmd: Arc<RunPmd>) -> Self {
+        FwdCmd { runpmd }
+    }
+
+    fn set_fwd(&self, input: &[String]) -> Result<(), String> {
+        let usage = || format!("usage: set fwd {}", fwd_engine_names().join("|"));
+        let Some(name) = input.get(2) else {
+            return Err(usage());
+        };
+        let Some(engine) = fwd_engine(name) else {
+            return Err(format!("unknown forwarding mode {name}\n{}", usage()));
+        };
+        if self.runpmd.engine.lock().unwrap().is_running() {
+            return Err("stop packet forwarding before changing the mode".to_string());
+        }
+        self.runpmd.fwd.lock().unwrap().engine = engine;
+        println!("Set {} packet forwarding mode", engine.name());
+        Ok(())
+    }
+
+    fn set_port_topology(&self, input: &[String]) -> Result<(), String> {
+        let args = input[2..].iter().map(|arg| arg.as_str()).collect::<Vec<_>>();
+        let topology = PortTopology::parse(&args)?;
+        if self.runpmd.engine.lock().unwrap().is_running() {
+            return Err("stop packet forwarding before changing the port topology".to_string());
+        }
+        println!("Set port topology {topology}");
+        self.runpmd.fwd.lock().unwrap().topology = topology;
+        Ok(())
+    }
+}
+
+impl CmdModuleOps for FwdCmd {
+    fn parse_cmd(&self, input: &mut Vec<String>) -> Result<(), String> {
+        let result = match (input[0].as_str(), input.get(1).map(|arg| arg.as_str())) {
+            ("set", Some("fwd")) => self.set_fwd(input),
+            ("set", Some("port-topology")) => self.set_port_topology(input),
+            ("show", Some("config")) if input.len() == 3 && input[2] == "fwd" => {
+                fwd::show_fwd_config(&self.runpmd);
+                Ok(())
+            }
+            ("start", None) => fwd::start_forwarding(&self.runpmd),
+            ("stop", None) => fwd::stop_forwarding(&self.runpmd),
+            _ => Err(format!("unknown command: {}", input.join(" "))),
+        };
+        input.clear();
+        result
+    }
+
+    fn complete(&self, input: &[String]) -> Vec<String> {
+        let words: Vec<&str> = match input.iter().map(|word| word.as_str()).collect::<Vec<_>>()[..] {
+            ["set"] => vec!["fwd", "port-topology"],
+            ["set", "fwd"] => fwd_engine_names(),
+            ["set", "port-topology"] => vec!["paired", "chained", "loop", "custom"],
+            ["show"] => vec!["config"],
+            ["show", "config"] => vec!["fwd"],
+            _ => Vec::new(),
+        };
+        words.into_iter().map(String::from).collect()
+    }
+}
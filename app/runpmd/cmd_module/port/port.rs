@@ -1,8 +1,11 @@
 use std::collections::HashMap;
-use rdpdk::dpdk_raw::rte_ethdev::{rte_eth_promiscuous_disable, rte_eth_promiscuous_enable};
+use std::sync::Arc;
+use rdpdk::port::DpdkPort;
+use rdpdk::port::error::PortError;
 use rdpdk::cmdline::arg::*;
 use rdpdk::cmdline::arg::arg_int::ArgInt;
 use crate::cmd_module::CmdModuleOps;
+use crate::RunPmd;
 
 // port [set|show] [all | <port ID>] <command>
 pub struct PortArg;
@@ -32,25 +35,60 @@ enum PortCmdType {
 struct PortParserContext {
     port_type: PortType,
     cmd_type: PortCmdType,
+    runpmd: Arc<RunPmd>,
 }
 
 impl PortParserContext {
-    pub fn new() -> Self {
+    pub fn new(runpmd: Arc<RunPmd>) -> Self {
         PortParserContext {
             port_type: PortType::None,
             cmd_type: PortCmdType::None,
+            runpmd,
         }
     }
+
+    // Runs the operation on the selected ports, errors of all ports are reported.
+    fn for_each_port<F>(&self, op: F) -> Result<(), String>
+    where
+        F: Fn(&mut dyn DpdkPort) -> Result<(), PortError>,
+    {
+        let ports = &self.runpmd.ports;
+        let selected = match self.port_type {
+            PortType::AllPorts => &ports[..],
+            PortType::SinglePort(port_id) if (port_id as usize) < ports.len() => {
+                &ports[port_id as usize..port_id as usize + 1]
+            }
+            PortType::SinglePort(port_id) => return Err(format!("invalid port {port_id}")),
+            PortType::None => return Ok(()),
+        };
+        let mut errors = Vec::new();
+        for port in selected {
+            let mut port = port.lock().unwrap();
+            if let Err(err) = op(&mut **port) {
+                errors.push(err.to_string());
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors.join("\n")) }
+    }
+}
+
+fn parse_on_off(arg: Option<&String>) -> Option<bool> {
+    match arg.map(|arg| arg.as_str()) {
+        Some("on" | "1") => Some(true),
+        Some("off" | "0") => Some(false),
+        _ => None,
+    }
 }
 
 trait PortOps {
     fn name(&self) -> &str;
-    fn parse_port_cmd(&self, input: &mut Vec<String>, context: &mut PortParserContext);
+    fn parse_port_cmd(&self, input: &mut Vec<String>, context: &mut PortParserContext) -> Result<(), String>;
 }
 
 type PortCmdMap = HashMap<String, Box<dyn PortOps>>;
 pub struct PortModule {
     commands: PortCmdMap,
+    runpmd: Arc<RunPmd>,
 }
 
 unsafe impl Send for PortModule {}
@@ -58,48 +96,80 @@ unsafe impl Sync for PortModule {}
 
 impl CmdModuleOps for PortModule {
 
-    fn parse_cmd(&self, input: &mut Vec<String>) {
+    fn parse_cmd(&self, input: &mut Vec<String>) -> Result<(), String> {
         input.remove(0); // port
 
-        let mut context = PortParserContext::new();
+        let mut context = PortParserContext::new(self.runpmd.clone());
+        let usage = "usage: port set|show <port>|all <command>".to_string();
 
-        context.cmd_type = match input[0].as_str() {
-            "set" => PortCmdType::Set,
-            "show" => PortCmdType::Show,
-            _ => {
-                return;
-            }
+        context.cmd_type = match input.first().map(|cmd| cmd.as_str()) {
+            Some("set") => PortCmdType::Set,
+            Some("show") => PortCmdType::Show,
+            _ => return Err(usage),
         };
 
-        context.port_type = match input[1].as_str() {
-            "all" => PortType::AllPorts,
-            _ => {
-                let port = u16::from_str_radix(&input[1], 10).unwrap();
-                PortType::SinglePort(port)
-            }
+        context.port_type = match input.get(1).map(|port| port.as_str()) {
+            Some("all") => PortType::AllPorts,
+            Some(port) => match u16::from_str_radix(port, 10) {
+                Ok(port) => PortType::SinglePort(port),
+                Err(_) => return Err(format!("invalid port {port}")),
+            },
+            None => return Err(usage),
         };
 
         input.remove(0);
         input.remove(0);
 
+        match input.first() {
+            Some(cmd) if self.commands.contains_key(cmd) => (),
+            Some(cmd) => return Err(format!("unknown port command: {cmd}")),
+            None => return Err(usage),
+        }
         loop {
-            if input.len() == 0 { return; }
+            if input.len() == 0 { return Ok(()); }
             match self.commands.get(&input[0]) {
-                None => return,
-                Some(op) => op.parse_port_cmd(input, &mut context),
+                None => return Ok(()),
+                Some(op) => op.parse_port_cmd(input, &mut context)?,
+            }
+        }
+    }
+
+    // port set|show <port>|all <command> <args>
+    fn complete(&self, input: &[String]) -> Vec<String> {
+        let keywords = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+        let on_off = || keywords(&["on", "off"]);
+        let set = input.get(1).is_some_and(|cmd| cmd == "set");
+        match input.len() {
+            1 => keywords(&["set", "show"]),
+            2 => {
+                let ports = (0..self.runpmd.ports.len()).map(|port_id| port_id.to_string());
+                std::iter::once("all".to_string()).chain(ports).collect()
             }
+            3 => self.commands.keys().cloned().collect(),
+            _ if !set => Vec::new(),
+            4 if input[3] == "promisc" => on_off(),
+            4 if input[3] == "vlan" => {
+                keywords(&["strip", "filter", "extend", "qinq", "add", "rm", "stripq", "pvid"])
+            }
+            5 if input[3] == "vlan" && matches!(input[4].as_str(), "strip" | "filter" | "extend" | "qinq") => on_off(),
+            5 if input[3] == "vlan" && input[4] == "pvid" => keywords(&["off"]),
+            6 if input[3] == "vlan" && input[4] == "stripq" => on_off(),
+            _ => Vec::new(),
         }
     }
 }
 
 impl PortModule {
-    pub fn new() -> Self {
+    pub fn new(runpmd: Arc<RunPmd>) -> Self {
         let mut map = PortModule {
-            commands: PortCmdMap::new()
+            commands: PortCmdMap::new(),
+            runpmd,
         };
 
         let promisc_cmd = PortCmdPromisc::new();
         map.commands.insert(promisc_cmd.name().to_string(), promisc_cmd);
+        let vlan_cmd = PortCmdVlan::new();
+        map.commands.insert(vlan_cmd.name().to_string(), vlan_cmd);
         map
     }
 }
@@ -111,45 +181,136 @@ impl PortCmdPromisc {
     }
 }
 
+// port set <port> promisc on|off
+// port show <port> promisc
 impl PortOps for PortCmdPromisc {
     fn name(&self) -> &str {
         "promisc"
     }
 
-    fn parse_port_cmd(&self, input: &mut Vec<String>, context: &mut PortParserContext) {
-
-        let activate:bool = match input[1].as_str() {
-            "on" | "1" => {
-                true
+    fn parse_port_cmd(&self, input: &mut Vec<String>, context: &mut PortParserContext) -> Result<(), String> {
+        match context.cmd_type {
+            PortCmdType::Set => {
+                let Some(activate) = parse_on_off(input.get(1)) else {
+                    input.clear();
+                    return Err("usage: port set <port> promisc on|off".to_string());
+                };
+                input.drain(..2);
+                context.for_each_port(|port| port.set_promiscuous(activate))
             },
-            "off" | "0" => {
-                false
+            PortCmdType::Show => {
+                input.remove(0);
+                context.for_each_port(|port| {
+                    let promisc = if port.promiscuous()? { "on" } else { "off" };
+                    println!("port {}: promiscuous {promisc}", port.port_id());
+                    Ok(())
+                })
             },
             _ => {
-                return;
+                input.clear();
+                Ok(())
             }
-        };
-        input.remove(0);
-        input.remove(0);
+        }
+    }
+}
+
+struct PortCmdVlan;
+impl PortCmdVlan {
+    pub fn new() -> Box<dyn PortOps> {
+        Box::new(PortCmdVlan)
+    }
+
+    const USAGE: &'static str = "usage: port set <port> vlan \
+        strip|filter|extend|qinq on|off | add|rm <vlan ID> | stripq <queue> on|off | pvid <vlan ID>|off";
+
+    // Consumes the vlan command arguments, returns `None` if they are not valid.
+    fn parse_set(
+        input: &mut Vec<String>,
+    ) -> Option<Box<dyn Fn(&mut dyn DpdkPort) -> Result<(), PortError>>> {
+        let arg = |i: usize| input.get(i);
+        let vlan_id = |i: usize| arg(i).and_then(|id| id.parse::<u16>().ok());
+
+        let (op, nb_args): (Box<dyn Fn(&mut dyn DpdkPort) -> Result<(), PortError>>, usize) =
+            match arg(1)?.as_str() {
+                offload @ ("strip" | "filter" | "extend" | "qinq") => {
+                    let on = parse_on_off(arg(2))?;
+                    let offload = offload.to_string();
+                    let op = move |port: &mut dyn DpdkPort| {
+                        let vlan = port.vlan_query()?;
+                        let vlan = match offload.as_str() {
+                            "strip" => vlan.strip(on),
+                            "filter" => vlan.filter(on),
+                            "extend" => vlan.extend(on),
+                            _ => vlan.qinq_strip(on),
+                        };
+                        port.vlan_update(&vlan)
+                    };
+                    (Box::new(op), 3)
+                }
+                cmd @ ("add" | "rm") => {
+                    let (vlan_id, on) = (vlan_id(2)?, cmd == "add");
+                    (Box::new(move |port: &mut dyn DpdkPort| port.vlan_filter(vlan_id, on)), 3)
+                }
+                "stripq" => {
+                    let queue_id = arg(2)?.parse::<u16>().ok()?;
+                    let on = parse_on_off(arg(3))?;
+                    let op = move |port: &mut dyn DpdkPort| port.set_vlan_strip_on_queue(queue_id, on);
+                    (Box::new(op), 4)
+                }
+                "pvid" => {
+                    let pvid = match arg(2)?.as_str() {
+                        "off" => None,
+                        _ => Some(vlan_id(2)?),
+                    };
+                    (Box::new(move |port: &mut dyn DpdkPort| port.set_vlan_pvid(pvid)), 3)
+                }
+                _ => return None,
+            };
+        input.drain(..nb_args);
+        Some(op)
+    }
+}
+
+// port set <port> vlan strip|filter|extend|qinq on|off
+// port set <port> vlan add|rm <vlan ID>
+// port set <port> vlan stripq <queue> on|off
+// port set <port> vlan pvid <vlan ID>|off
+// port show <port> vlan
+impl PortOps for PortCmdVlan {
+    fn name(&self) -> &str {
+        "vlan"
+    }
 
+    fn parse_port_cmd(&self, input: &mut Vec<String>, context: &mut PortParserContext) -> Result<(), String> {
         match context.cmd_type {
-            PortCmdType::Set => {
-                match context.port_type {
-                    PortType::AllPorts => {},
-                    PortType::SinglePort(port) => unsafe {
-                        if activate {
-                            rte_eth_promiscuous_enable(port);
-                        } else {
-                            rte_eth_promiscuous_disable(port);
-                        }
-
-                    },
-                    _ => {return}
+            PortCmdType::Set => match Self::parse_set(input) {
+                Some(op) => context.for_each_port(op),
+                None => {
+                    input.clear();
+                    Err(Self::USAGE.to_string())
                 }
             },
-            PortCmdType::Show => {},
-            _ => {return}
+            PortCmdType::Show => {
+                input.remove(0);
+                context.for_each_port(|port| {
+                    let vlan = port.vlan_query()?;
+                    let on_off = |on: bool| if on { "on" } else { "off" };
+                    println!(
+                        "port {}: strip {} filter {} extend {} qinq strip {} filter IDs {:?}",
+                        port.port_id(),
+                        on_off(vlan.strip),
+                        on_off(vlan.filter),
+                        on_off(vlan.extend),
+                        on_off(vlan.qinq_strip),
+                        vlan.filter_ids,
+                    );
+                    Ok(())
+                })
+            }
+            _ => {
+                input.clear();
+                Ok(())
+            }
         }
     }
 }
-
@@ -0,0 +1,137 @@
+use rdpdk::dpdk_raw::rte_mbuf::{
+    RTE_MBUF_F_RX_IP_CKSUM_BAD,
+    RTE_MBUF_F_RX_IP_CKSUM_MASK,
+    RTE_MBUF_F_RX_L4_CKSUM_BAD,
+    RTE_MBUF_F_RX_L4_CKSUM_MASK,
+};
+use rdpdk::port::io_engine::IoStream;
+use rdpdk::port::mbuf::{fold_cksum, raw_cksum, L4Cksum, Mbuf};
+use rdpdk::port::offload::TxOffloads;
+use crate::fwd::{ForwardingEngine, FwdStream, StreamStats};
+
+const ETHER_TYPE_IPV4: u16 = 0x0800;
+const ETHER_TYPE_IPV6: u16 = 0x86dd;
+const ETHER_TYPE_VLAN: u16 = 0x8100;
+const ETHER_TYPE_QINQ: u16 = 0x88a8;
+const IPPROTO_TCP: u8 = 6;
+const IPPROTO_UDP: u8 = 17;
+
+/// IPv4 header checksum, the header checksum field must be zero.
+pub fn ipv4_hdr_cksum(hdr: &[u8]) -> u16 {
+    !fold_cksum(raw_cksum(0, hdr))
+}
+
+// L3 and L4 headers of a packet.
+struct PktInfo {
+    l2_len: usize,
+    ipv4: bool,
+    l3_len: usize,
+    l4_len: usize,
+    proto: u8,
+}
+
+fn parse(data: &[u8]) -> Option<PktInfo> {
+    let mut l2_len = 14;
+    let mut ether_type = u16::from_be_bytes([*data.get(12)?, *data.get(13)?]);
+    while ether_type == ETHER_TYPE_VLAN || ether_type == ETHER_TYPE_QINQ {
+        ether_type = u16::from_be_bytes([*data.get(l2_len + 2)?, *data.get(l2_len + 3)?]);
+        l2_len += 4;
+    }
+    let ip = data.get(l2_len..)?;
+    let (ipv4, l3_len, ip_len, proto) = match ether_type {
+        ETHER_TYPE_IPV4 if ip.len() >= 20 => {
+            let l3_len = (ip[0] & 0x0f) as usize * 4;
+            (true, l3_len, u16::from_be_bytes([ip[2], ip[3]]) as usize, ip[9])
+        }
+        ETHER_TYPE_IPV6 if ip.len() >= 40 => {
+            (false, 40, 40 + u16::from_be_bytes([ip[4], ip[5]]) as usize, ip[6])
+        }
+        _ => return None,
+    };
+    if l3_len < 20 || ip_len < l3_len || ip.len() < ip_len {
+        return None;
+    }
+    Some(PktInfo { l2_len, ipv4, l3_len, l4_len: ip_len - l3_len, proto })
+}
+
+// Computes the checksums the Tx port does not offload, multi-segment packets are
+// left unchanged.
+fn sw_cksum(data: &mut [u8], info: &PktInfo, ip_cksum: bool, l4_cksum: bool) {
+    let ip = &mut data[info.l2_len..];
+    if ip_cksum && info.ipv4 {
+        ip[10..12].fill(0);
+        let cksum = ipv4_hdr_cksum(&ip[..info.l3_len]);
+        ip[10..12].copy_from_slice(&cksum.to_be_bytes());
+    }
+    if !l4_cksum {
+        return;
+    }
+    let offset = match info.proto {
+        IPPROTO_TCP if info.l4_len >= 20 => 16,
+        IPPROTO_UDP if info.l4_len >= 8 => 6,
+        _ => return,
+    };
+    let mut sum = if info.ipv4 { raw_cksum(0, &ip[12..20]) } else { raw_cksum(0, &ip[8..40]) };
+    sum += info.proto as u32 + info.l4_len as u32;
+    let l4 = &mut ip[info.l3_len..info.l3_len + info.l4_len];
+    l4[offset..offset + 2].fill(0);
+    let cksum = match !fold_cksum(raw_cksum(sum, l4)) {
+        0 if info.proto == IPPROTO_UDP => 0xffff,
+        cksum => cksum,
+    };
+    l4[offset..offset + 2].copy_from_slice(&cksum.to_be_bytes());
+}
+
+fn fix_cksum(mbuf: &mut Mbuf, offloads: TxOffloads) {
+    let Some(info) = parse(mbuf.data()) else {
+        return;
+    };
+    let l4 = match info.proto {
+        IPPROTO_TCP => L4Cksum::Tcp,
+        IPPROTO_UDP => L4Cksum::Udp,
+        _ => L4Cksum::None,
+    };
+    let hw_ip = info.ipv4 && offloads.contains(TxOffloads::IPV4_CKSUM);
+    let hw_l4 = match l4 {
+        L4Cksum::Tcp => offloads.contains(TxOffloads::TCP_CKSUM),
+        L4Cksum::Udp => offloads.contains(TxOffloads::UDP_CKSUM),
+        L4Cksum::None => false,
+    };
+    if mbuf.nb_segs() == 1 {
+        sw_cksum(mbuf.data_mut(), &info, !hw_ip, !hw_l4);
+    }
+    if hw_ip || hw_l4 {
+        let l4 = if hw_l4 { l4 } else { L4Cksum::None };
+        mbuf.offload_cksum(info.l2_len as u16, hw_ip, l4);
+    }
+}
+
+/// Counts packets with bad Rx checksums and recomputes IP, TCP and UDP checksums,
+/// in hardware when the Tx port offloads them.
+pub struct CsumFwd;
+
+impl ForwardingEngine for CsumFwd {
+    fn name(&self) -> &'static str {
+        "csum"
+    }
+
+    fn forward(&self, stream: &mut IoStream, fs: &mut FwdStream) {
+        if fs.rx(stream) == 0 {
+            return;
+        }
+        let (mut bad_ip, mut bad_l4) = (0, 0);
+        for mbuf in fs.pkts.iter_mut() {
+            let ol_flags = mbuf.ol_flags();
+            if ol_flags & RTE_MBUF_F_RX_IP_CKSUM_MASK as u64 == RTE_MBUF_F_RX_IP_CKSUM_BAD as u64 {
+                bad_ip += 1;
+            }
+            if ol_flags & RTE_MBUF_F_RX_L4_CKSUM_MASK as u64 == RTE_MBUF_F_RX_L4_CKSUM_BAD as u64 {
+                bad_l4 += 1;
+            }
+            fix_cksum(mbuf, fs.tx_port.offloads);
+        }
+        StreamStats::add(&fs.stats.bad_ip_cksum, bad_ip);
+        StreamStats::add(&fs.stats.bad_l4_cksum, bad_l4);
+        fs.tx(stream);
+    }
+}
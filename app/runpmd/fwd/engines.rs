@@ -0,0 +1,84 @@
+use rdpdk::port::io_engine::IoStream;
+use crate::fwd::{ForwardingEngine, FwdStream};
+
+/// Forwards packets unchanged.
+pub struct IoFwd;
+
+impl ForwardingEngine for IoFwd {
+    fn name(&self) -> &'static str {
+        "io"
+    }
+
+    fn forward(&self, stream: &mut IoStream, fs: &mut FwdStream) {
+        if fs.rx(stream) == 0 {
+            return;
+        }
+        fs.tx(stream);
+    }
+}
+
+/// Sets the source address to the Tx port address and the destination to its peer.
+pub struct MacFwd;
+
+impl ForwardingEngine for MacFwd {
+    fn name(&self) -> &'static str {
+        "mac"
+    }
+
+    fn forward(&self, stream: &mut IoStream, fs: &mut FwdStream) {
+        if fs.rx(stream) == 0 {
+            return;
+        }
+        let (dst, src) = (fs.tx_port.peer_addr.bytes(), fs.tx_port.mac_addr.bytes());
+        for mbuf in fs.pkts.iter_mut() {
+            let eth = mbuf.data_mut();
+            if eth.len() < 12 {
+                continue;
+            }
+            eth[0..6].copy_from_slice(&dst);
+            eth[6..12].copy_from_slice(&src);
+        }
+        fs.tx(stream);
+    }
+}
+
+/// Swaps the source and destination addresses.
+pub struct MacSwapFwd;
+
+impl ForwardingEngine for MacSwapFwd {
+    fn name(&self) -> &'static str {
+        "macswap"
+    }
+
+    fn forward(&self, stream: &mut IoStream, fs: &mut FwdStream) {
+        if fs.rx(stream) == 0 {
+            return;
+        }
+        for mbuf in fs.pkts.iter_mut() {
+            let eth = mbuf.data_mut();
+            if eth.len() < 12 {
+                continue;
+            }
+            for i in 0..6 {
+                eth.swap(i, i + 6);
+            }
+        }
+        fs.tx(stream);
+    }
+}
+
+/// Receives and frees packets.
+pub struct RxOnlyFwd;
+
+impl ForwardingEngine for RxOnlyFwd {
+    fn name(&self) -> &'static str {
+        "rxonly"
+    }
+
+    fn forward(&self, stream: &mut IoStream, fs: &mut FwdStream) {
+        if fs.rx(stream) == 0 {
+            return;
+        }
+        fs.pkts.clear();
+    }
+}
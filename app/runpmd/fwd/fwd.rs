@@ -0,0 +1,354 @@
+#[path = "engines.rs"]
+mod engines;
+#[path = "txonly.rs"]
+mod txonly;
+#[path = "csum.rs"]
+mod csum;
+#[path = "topology.rs"]
+pub mod topology;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use rdpdk::port::ether::EtherAddr;
+use rdpdk::port::io_engine::{self, IoQueue, IoStream, IoWorker};
+use rdpdk::port::mbuf::MbufBatch;
+use rdpdk::port::mempool::Mempool;
+use rdpdk::port::offload::TxOffloads;
+use crate::RunPmd;
+use topology::{FwdPort, PortTopology};
+
+/// Packet forwarding mode, selected with `set fwd <mode>`.
+///
+/// Engines are stateless, per-stream state is kept in [`FwdStream`]. `forward` runs on
+/// the worker lcores and must not block.
+pub trait ForwardingEngine: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Prepares stream state, runs on the main lcore before the workers are launched.
+    fn init_stream(&self, _fs: &mut FwdStream) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Forwards one burst of the stream.
+    fn forward(&self, stream: &mut IoStream, fs: &mut FwdStream);
+}
+
+pub static FWD_ENGINES: &[&dyn ForwardingEngine] = &[
+    &engines::IoFwd,
+    &engines::MacFwd,
+    &engines::MacSwapFwd,
+    &engines::RxOnlyFwd,
+    &txonly::TxOnlyFwd,
+    &txonly::FlowGenFwd,
+    &csum::CsumFwd,
+];
+
+pub fn fwd_engine(name: &str) -> Option<&'static dyn ForwardingEngine> {
+    FWD_ENGINES.iter().find(|engine| engine.name() == name).copied()
+}
+
+pub fn fwd_engine_names() -> Vec<&'static str> {
+    FWD_ENGINES.iter().map(|engine| engine.name()).collect()
+}
+
+/// Stream counters, updated by the worker lcore.
+#[derive(Debug, Default)]
+pub struct StreamStats {
+    pub rx_packets: AtomicU64,
+    pub tx_packets: AtomicU64,
+    pub dropped: AtomicU64,
+    pub bad_ip_cksum: AtomicU64,
+    pub bad_l4_cksum: AtomicU64,
+}
+
+impl StreamStats {
+    pub fn add(counter: &AtomicU64, n: u64) {
+        counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn get(counter: &AtomicU64) -> u64 {
+        counter.load(Ordering::Relaxed)
+    }
+}
+
+/// Tx port properties used by the engines.
+#[derive(Clone)]
+pub struct TxPortInfo {
+    pub mac_addr: EtherAddr,
+    /// Address of the peer connected to the Tx port, `02:00:00:00:00:<port ID>`.
+    pub peer_addr: EtherAddr,
+    pub offloads: TxOffloads,
+    /// Mempool of the port Rx queue 0, used to build packets.
+    pub mempool: Option<Arc<Mempool>>,
+}
+
+/// Per-stream forwarding state, owned by the worker lcore.
+pub struct FwdStream {
+    pub pkts: MbufBatch,
+    pub burst: u16,
+    pub tx_port: TxPortInfo,
+    pub tx_pkt_len: u16,
+    pub nb_flows: u16,
+    /// Next flow of the flowgen mode.
+    pub flow: u16,
+    /// Packet built by `init_stream` for the Tx only modes.
+    pub template: Vec<u8>,
+    pub stats: Arc<StreamStats>,
+}
+
+impl FwdStream {
+    /// Receives a burst into `pkts`.
+    pub fn rx(&mut self, stream: &mut IoStream) -> u16 {
+        let nb_rx = stream.rxq.rx_burst(&mut self.pkts);
+        StreamStats::add(&self.stats.rx_packets, nb_rx as u64);
+        nb_rx
+    }
+
+    /// Sends `pkts`, packets the Tx queue had no room for are dropped.
+    pub fn tx(&mut self, stream: &mut IoStream) -> u16 {
+        if self.pkts.is_empty() {
+            return 0;
+        }
+        let nb_tx = stream.txq.tx_burst(&mut self.pkts);
+        StreamStats::add(&self.stats.tx_packets, nb_tx as u64);
+        StreamStats::add(&self.stats.dropped, self.pkts.len() as u64);
+        self.pkts.clear();
+        nb_tx
+    }
+}
+
+/// Forwarding configuration and the statistics of the running streams.
+pub struct FwdConfig {
+    pub engine: &'static dyn ForwardingEngine,
+    pub burst: u16,
+    pub tx_pkt_len: u16,
+    pub nb_flows: u16,
+    /// Forwarding lcores, all worker lcores if `None`.
+    pub nb_cores: Option<u16>,
+    pub topology: PortTopology,
+    stats: Vec<(IoQueue, IoQueue, Arc<StreamStats>)>,
+}
+
+impl Default for FwdConfig {
+    fn default() -> Self {
+        FwdConfig {
+            engine: &engines::IoFwd,
+            burst: 32,
+            tx_pkt_len: 64,
+            nb_flows: 1024,
+            nb_cores: None,
+            topology: PortTopology::default(),
+            stats: Vec::new(),
+        }
+    }
+}
+
+impl FwdConfig {
+    /// Worker lcores used for forwarding.
+    pub fn fwd_lcores(&self) -> Vec<u32> {
+        let mut lcores = io_engine::worker_lcores();
+        if let Some(nb_cores) = self.nb_cores {
+            lcores.truncate(nb_cores as usize);
+        }
+        lcores
+    }
+}
+
+fn peer_addr(port_id: u16) -> EtherAddr {
+    EtherAddr([0x02, 0, 0, 0, 0, port_id as u8])
+}
+
+fn tx_port_info(runpmd: &RunPmd, port_id: u16) -> TxPortInfo {
+    let port = runpmd.ports[port_id as usize].lock().unwrap();
+    TxPortInfo {
+        mac_addr: port.mac_addr().unwrap_or_default(),
+        peer_addr: peer_addr(port_id),
+        offloads: port.tx_offloads(),
+        mempool: port.port_conf().rx_queue_mempool(0).cloned(),
+    }
+}
+
+// Started ports, forwarding streams are built over these.
+fn fwd_ports(runpmd: &RunPmd) -> Vec<FwdPort> {
+    runpmd
+        .ports
+        .iter()
+        .filter_map(|p| {
+            let port = p.lock().unwrap();
+            port.is_started().then(|| FwdPort {
+                port_id: port.port_id(),
+                rxq_num: port.port_conf().rxq_num,
+                txq_num: port.port_conf().txq_num,
+            })
+        })
+        .collect()
+}
+
+fn claim_stream(runpmd: &RunPmd, rx: IoQueue, tx: IoQueue) -> Result<IoStream, String> {
+    let port = |port_id: u16| {
+        runpmd
+            .ports
+            .get(port_id as usize)
+            .ok_or_else(|| format!("invalid port {port_id}"))
+    };
+    let rxq = port(rx.port_id)?.lock().unwrap().rx_queue(rx.queue_id).map_err(|e| e.to_string())?;
+    let txq = port(tx.port_id)?.lock().unwrap().tx_queue(tx.queue_id).map_err(|e| e.to_string())?;
+    Ok(IoStream::new(rxq, txq))
+}
+
+/// Launches the selected forwarding engine on the worker lcores.
+///
+/// Must be called on the main lcore.
+pub fn start_forwarding(runpmd: &RunPmd) -> Result<(), String> {
+    let mut engine = runpmd.engine.lock().unwrap();
+    if engine.is_running() {
+        return Err("packet forwarding already started".to_string());
+    }
+    let mut fwd = runpmd.fwd.lock().unwrap();
+    let lcores = fwd.fwd_lcores();
+    if lcores.is_empty() {
+        return Err("no worker lcores, packet forwarding disabled".to_string());
+    }
+
+    let mut streams = Vec::new();
+    for (rx, tx) in fwd.topology.streams(&fwd_ports(runpmd)) {
+        streams.push(claim_stream(runpmd, rx, tx)?);
+    }
+    if streams.is_empty() {
+        return Err("no forwarding streams, start ports or check the port topology".to_string());
+    }
+
+    let fwd_engine = fwd.engine;
+    let mut tx_ports = HashMap::new();
+    let mut fwd_streams = HashMap::new();
+    let mut stats = Vec::with_capacity(streams.len());
+    for stream in streams.iter() {
+        let tx_port = tx_ports
+            .entry(stream.tx().port_id)
+            .or_insert_with(|| tx_port_info(runpmd, stream.tx().port_id))
+            .clone();
+        let mut fs = FwdStream {
+            pkts: MbufBatch::new(fwd.burst as usize),
+            burst: fwd.burst,
+            tx_port,
+            tx_pkt_len: fwd.tx_pkt_len,
+            nb_flows: fwd.nb_flows,
+            flow: 0,
+            template: Vec::new(),
+            stats: Arc::new(StreamStats::default()),
+        };
+        fwd_engine.init_stream(&mut fs)?;
+        stats.push((stream.rx(), stream.tx(), fs.stats.clone()));
+        fwd_streams.insert(stream.rx(), fs);
+    }
+
+    for (lcore_id, streams) in io_engine::distribute(streams, &lcores) {
+        for stream in streams {
+            if let Err(err) = engine.assign(lcore_id, stream) {
+                let _ = engine.clear();
+                return Err(err.to_string());
+            }
+        }
+    }
+    let nb_lcores = engine.lcores().len();
+    let launched = engine.launch(|worker: &IoWorker| {
+        let mut worker_streams = worker
+            .streams()
+            .iter()
+            .map(|stream| fwd_streams.remove(&stream.rx()).unwrap())
+            .collect::<Vec<_>>();
+        Box::new(move |worker: &mut IoWorker| {
+            for (stream, fs) in worker.streams_mut().iter_mut().zip(worker_streams.iter_mut()) {
+                fwd_engine.forward(stream, fs);
+            }
+        })
+    });
+    if let Err(err) = launched {
+        let _ = engine.clear();
+        return Err(err.to_string());
+    }
+
+    println!(
+        "Start packet forwarding: mode {}, {} streams on {} lcores",
+        fwd_engine.name(),
+        stats.len(),
+        nb_lcores
+    );
+    fwd.stats = stats;
+    Ok(())
+}
+
+/// Stops the workers, releases the port queues and prints forwarding statistics.
+pub fn stop_forwarding(runpmd: &RunPmd) -> Result<(), String> {
+    let mut engine = runpmd.engine.lock().unwrap();
+    if !engine.is_running() {
+        return Err("packet forwarding not started".to_string());
+    }
+    engine.stop();
+    let joined = engine.join();
+    // queue handles are released for the control path
+    engine.clear().map_err(|e| e.to_string())?;
+
+    let mut fwd = runpmd.fwd.lock().unwrap();
+    let stats = std::mem::take(&mut fwd.stats);
+    show_fwd_stats(fwd.engine, &stats);
+    joined.map_err(|e| e.to_string())
+}
+
+fn show_fwd_stats(engine: &dyn ForwardingEngine, stats: &[(IoQueue, IoQueue, Arc<StreamStats>)]) {
+    let (mut rx, mut tx, mut dropped) = (0, 0, 0);
+    for (rxq, txq, s) in stats {
+        let (s_rx, s_tx, s_dropped) = (
+            StreamStats::get(&s.rx_packets),
+            StreamStats::get(&s.tx_packets),
+            StreamStats::get(&s.dropped),
+        );
+        println!(
+            "  RX-port {} queue {} -> TX-port {} queue {}: RX {:<12} TX {:<12} dropped {}",
+            rxq.port_id, rxq.queue_id, txq.port_id, txq.queue_id, s_rx, s_tx, s_dropped
+        );
+        let (bad_ip, bad_l4) = (StreamStats::get(&s.bad_ip_cksum), StreamStats::get(&s.bad_l4_cksum));
+        if bad_ip + bad_l4 > 0 {
+            println!("    bad IP checksum {bad_ip} bad L4 checksum {bad_l4}");
+        }
+        (rx, tx, dropped) = (rx + s_rx, tx + s_tx, dropped + s_dropped);
+    }
+    println!("  Total ({}): RX {:<12} TX {:<12} dropped {}", engine.name(), rx, tx, dropped);
+}
+
+/// Prints the forwarding mode and the streams of each lcore, the streams that `start`
+/// would launch when forwarding is stopped.
+pub fn show_fwd_config(runpmd: &RunPmd) {
+    let engine = runpmd.engine.lock().unwrap();
+    let fwd = runpmd.fwd.lock().unwrap();
+    let ports = fwd_ports(runpmd);
+    let layout = if engine.is_running() {
+        engine.lcores().to_vec()
+    } else {
+        io_engine::distribute(fwd.topology.streams(&ports), &fwd.fwd_lcores())
+    };
+
+    println!(
+        "{} packet forwarding - ports={} - cores={} - streams={} - burst={}",
+        fwd.engine.name(),
+        ports.len(),
+        layout.len(),
+        layout.iter().map(|(_, streams)| streams.len()).sum::<usize>(),
+        fwd.burst
+    );
+    println!("port topology: {}", fwd.topology);
+    for (lcore_id, streams) in layout.iter() {
+        println!("Logical Core {lcore_id} forwards packets on {} streams:", streams.len());
+        for (rx, tx) in streams {
+            println!(
+                "  RX P={}/Q={} -> TX P={}/Q={} peer={}",
+                rx.port_id,
+                rx.queue_id,
+                tx.port_id,
+                tx.queue_id,
+                peer_addr(tx.port_id)
+            );
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+use std::fmt;
+use rdpdk::port::io_engine::IoQueue;
+
+/// Port selected for forwarding and its queue numbers.
+#[derive(Debug, Clone, Copy)]
+pub struct FwdPort {
+    pub port_id: u16,
+    pub rxq_num: u16,
+    pub txq_num: u16,
+}
+
+/// How received packets are mapped to Tx ports, `set port-topology`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PortTopology {
+    /// Ports 0 and 1, 2 and 3, ... forward to each other, an odd last port to itself.
+    #[default]
+    Paired,
+    /// Each port forwards to the next one, the last port to the first.
+    Chained,
+    /// Each port forwards to itself.
+    Loop,
+    /// Explicit Rx queue to Tx queue streams.
+    Custom(Vec<(IoQueue, IoQueue)>),
+}
+
+// <port>:<queue>
+fn parse_queue(arg: &str) -> Option<IoQueue> {
+    let (port_id, queue_id) = arg.split_once(':')?;
+    Some(IoQueue { port_id: port_id.parse().ok()?, queue_id: queue_id.parse().ok()? })
+}
+
+impl PortTopology {
+    pub const USAGE: &str =
+        "paired|chained|loop|custom <rx port>:<rx queue>-<tx port>:<tx queue>[,...]";
+
+    /// Parses a topology name, `custom` is followed by its streams separated with
+    /// commas or spaces.
+    pub fn parse(args: &[&str]) -> Result<Self, String> {
+        match args {
+            ["paired"] => Ok(PortTopology::Paired),
+            ["chained"] => Ok(PortTopology::Chained),
+            ["loop"] => Ok(PortTopology::Loop),
+            ["custom", streams @ ..] => Self::parse_streams(streams),
+            _ => Err(format!("invalid port topology, expected {}", Self::USAGE)),
+        }
+    }
+
+    /// Parses `<rx port>:<rx queue>-<tx port>:<tx queue>` streams, each queue can be
+    /// used by one stream only.
+    pub fn parse_streams(args: &[&str]) -> Result<Self, String> {
+        let mut streams = Vec::new();
+        let (mut rx_used, mut tx_used) = (HashSet::new(), HashSet::new());
+        for arg in args.iter().flat_map(|arg| arg.split(',')).filter(|arg| !arg.is_empty()) {
+            let (rx, tx) = arg
+                .split_once('-')
+                .and_then(|(rx, tx)| Some((parse_queue(rx)?, parse_queue(tx)?)))
+                .ok_or_else(|| format!("invalid stream {arg}, expected <rx port>:<rx queue>-<tx port>:<tx queue>"))?;
+            if !rx_used.insert(rx) {
+                return Err(format!("Rx queue {}:{} is used by more than one stream", rx.port_id, rx.queue_id));
+            }
+            if !tx_used.insert(tx) {
+                return Err(format!("Tx queue {}:{} is used by more than one stream", tx.port_id, tx.queue_id));
+            }
+            streams.push((rx, tx));
+        }
+        if streams.is_empty() {
+            return Err("custom topology has no streams".to_string());
+        }
+        Ok(PortTopology::Custom(streams))
+    }
+
+    // Index of the port receiving the packets of `ports[index]`.
+    fn peer(&self, nb_ports: usize, index: usize) -> usize {
+        match self {
+            PortTopology::Paired if index ^ 1 < nb_ports => index ^ 1,
+            PortTopology::Chained => (index + 1) % nb_ports,
+            _ => index,
+        }
+    }
+
+    /// Streams of the forwarding ports, Rx queue N of a port is forwarded to Tx queue N
+    /// of its peer.
+    pub fn streams(&self, ports: &[FwdPort]) -> Vec<(IoQueue, IoQueue)> {
+        if let PortTopology::Custom(streams) = self {
+            return streams.clone();
+        }
+        let mut streams = Vec::new();
+        for (index, rx_port) in ports.iter().enumerate() {
+            let tx_port = &ports[self.peer(ports.len(), index)];
+            for queue_id in 0..rx_port.rxq_num.min(tx_port.txq_num) {
+                streams.push((
+                    IoQueue { port_id: rx_port.port_id, queue_id },
+                    IoQueue { port_id: tx_port.port_id, queue_id },
+                ));
+            }
+        }
+        streams
+    }
+}
+
+impl fmt::Display for PortTopology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortTopology::Paired => write!(f, "paired"),
+            PortTopology::Chained => write!(f, "chained"),
+            PortTopology::Loop => write!(f, "loop"),
+            PortTopology::Custom(streams) => {
+                write!(f, "custom")?;
+                for (i, (rx, tx)) in streams.iter().enumerate() {
+                    let sep = if i == 0 { ' ' } else { ',' };
+                    write!(f, "{sep}{}:{}-{}:{}", rx.port_id, rx.queue_id, tx.port_id, tx.queue_id)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use rdpdk::port::io_engine::IoStream;
+use crate::fwd::csum::ipv4_hdr_cksum;
+use crate::fwd::{ForwardingEngine, FwdStream};
+
+const ETHER_HDR_LEN: usize = 14;
+const IPV4_HDR_LEN: usize = 20;
+const UDP_HDR_LEN: usize = 8;
+/// Shortest packet the Tx only modes build, Ethernet, IPv4 and UDP headers.
+pub const TX_PKT_MIN_LEN: u16 = (ETHER_HDR_LEN + IPV4_HDR_LEN + UDP_HDR_LEN) as u16;
+
+const IP_SRC_ADDR: [u8; 4] = [198, 18, 0, 1];
+const IP_DST_ADDR: [u8; 4] = [198, 19, 0, 1];
+const UDP_PORT: u16 = 9;
+
+// Ethernet / IPv4 / UDP packet with a zero payload, the UDP checksum is not used.
+fn build_template(fs: &mut FwdStream) -> Result<(), String> {
+    let len = fs.tx_pkt_len as usize;
+    if len < TX_PKT_MIN_LEN as usize {
+        return Err(format!("packet length {len} is below {TX_PKT_MIN_LEN}"));
+    }
+    if fs.tx_port.mempool.is_none() {
+        return Err("Tx port has no mempool".to_string());
+    }
+
+    let mut pkt = vec![0u8; len];
+    pkt[0..6].copy_from_slice(&fs.tx_port.peer_addr.bytes());
+    pkt[6..12].copy_from_slice(&fs.tx_port.mac_addr.bytes());
+    pkt[12..14].copy_from_slice(&0x0800u16.to_be_bytes());
+
+    let ip = &mut pkt[ETHER_HDR_LEN..];
+    ip[0] = 0x45;
+    ip[2..4].copy_from_slice(&((len - ETHER_HDR_LEN) as u16).to_be_bytes());
+    ip[8] = 64;
+    ip[9] = 17;
+    ip[12..16].copy_from_slice(&IP_SRC_ADDR);
+    ip[16..20].copy_from_slice(&IP_DST_ADDR);
+    let cksum = ipv4_hdr_cksum(&ip[..IPV4_HDR_LEN]);
+    ip[10..12].copy_from_slice(&cksum.to_be_bytes());
+
+    let udp = &mut ip[IPV4_HDR_LEN..];
+    udp[0..2].copy_from_slice(&UDP_PORT.to_be_bytes());
+    udp[2..4].copy_from_slice(&UDP_PORT.to_be_bytes());
+    udp[4..6].copy_from_slice(&((len - ETHER_HDR_LEN - IPV4_HDR_LEN) as u16).to_be_bytes());
+
+    fs.template = pkt;
+    Ok(())
+}
+
+// Allocates a burst of packets filled with the template, `false` if the pool is empty.
+fn alloc_burst(fs: &mut FwdStream) -> bool {
+    let mempool = fs.tx_port.mempool.as_ref().unwrap();
+    if !fs.pkts.alloc(mempool, fs.burst) {
+        return false;
+    }
+    let mut filled = true;
+    for mbuf in fs.pkts.iter_mut() {
+        match mbuf.append(fs.template.len() as u16) {
+            Some(data) => data.copy_from_slice(&fs.template),
+            None => {
+                // mbuf data room is shorter than the packet
+                filled = false;
+                break;
+            }
+        }
+    }
+    if !filled {
+        fs.pkts.clear();
+    }
+    filled
+}
+
+/// Sends generated UDP packets.
+pub struct TxOnlyFwd;
+
+impl ForwardingEngine for TxOnlyFwd {
+    fn name(&self) -> &'static str {
+        "txonly"
+    }
+
+    fn init_stream(&self, fs: &mut FwdStream) -> Result<(), String> {
+        build_template(fs)
+    }
+
+    fn forward(&self, stream: &mut IoStream, fs: &mut FwdStream) {
+        if alloc_burst(fs) {
+            fs.tx(stream);
+        }
+    }
+}
+
+/// Sends generated UDP packets, the IPv4 source address cycles over `nb_flows` flows.
+pub struct FlowGenFwd;
+
+impl ForwardingEngine for FlowGenFwd {
+    fn name(&self) -> &'static str {
+        "flowgen"
+    }
+
+    fn init_stream(&self, fs: &mut FwdStream) -> Result<(), String> {
+        if fs.nb_flows == 0 {
+            return Err("number of flows must be positive".to_string());
+        }
+        build_template(fs)
+    }
+
+    fn forward(&self, stream: &mut IoStream, fs: &mut FwdStream) {
+        if !alloc_burst(fs) {
+            return;
+        }
+        let base = u32::from_be_bytes(IP_SRC_ADDR);
+        for mbuf in fs.pkts.iter_mut() {
+            let ip = &mut mbuf.data_mut()[ETHER_HDR_LEN..ETHER_HDR_LEN + IPV4_HDR_LEN];
+            ip[12..16].copy_from_slice(&(base + fs.flow as u32).to_be_bytes());
+            ip[10..12].fill(0);
+            let cksum = ipv4_hdr_cksum(ip);
+            ip[10..12].copy_from_slice(&cksum.to_be_bytes());
+            fs.flow = (fs.flow + 1) % fs.nb_flows;
+        }
+        fs.tx(stream);
+    }
+}
@@ -0,0 +1,185 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use rdpdk::dpdk_raw::rte_ethdev::RTE_MAX_QUEUES_PER_PORT;
+use crate::fwd::{self, ForwardingEngine, FwdConfig};
+use crate::fwd::topology::PortTopology;
+
+/// Largest forwarding burst, testpmd `MAX_PKT_BURST`.
+pub const MAX_PKT_BURST: u16 = 512;
+
+/// runpmd options, given after the `--` separator.
+pub struct RunPmdParams {
+    pub rxq: u16,
+    pub txq: u16,
+    pub rxd: u16,
+    pub txd: u16,
+    pub burst: u16,
+    /// Mbufs in each socket pool, computed from the queue setup if not set.
+    pub total_num_mbufs: Option<u32>,
+    pub forward_mode: &'static dyn ForwardingEngine,
+    /// Forwarding lcores, all worker lcores if not set.
+    pub nb_cores: Option<u16>,
+    pub topology: PortTopology,
+    pub interactive: bool,
+    pub auto_start: bool,
+    pub cmdline_file: Option<PathBuf>,
+    /// Stop command files at the first failed command, runpmd exits if the
+    /// `--cmdline-file` commands fail.
+    pub exit_on_error: bool,
+    /// Interactive command history, `$HOME/.runpmd_history` by default.
+    pub history_file: Option<PathBuf>,
+}
+
+impl Default for RunPmdParams {
+    fn default() -> Self {
+        let fwd = FwdConfig::default();
+        RunPmdParams {
+            rxq: 1,
+            txq: 1,
+            rxd: 64,
+            txd: 64,
+            burst: fwd.burst,
+            total_num_mbufs: None,
+            forward_mode: fwd.engine,
+            nb_cores: None,
+            topology: fwd.topology,
+            interactive: false,
+            auto_start: false,
+            cmdline_file: None,
+            exit_on_error: false,
+            history_file: std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".runpmd_history")),
+        }
+    }
+}
+
+fn parse_num<T>(option: &str, value: &str, min: T, max: T) -> Result<T, String>
+where
+    T: FromStr + PartialOrd + std::fmt::Display + Copy,
+{
+    match value.parse::<T>() {
+        Ok(n) if n >= min && n <= max => Ok(n),
+        _ => Err(format!("{option}: invalid value '{value}', expected {min}..{max}")),
+    }
+}
+
+impl RunPmdParams {
+    pub const USAGE: &str = "\
+usage: runpmd [EAL options] -- [options]
+  --rxq N                 Rx queues per port, default 1
+  --txq N                 Tx queues per port, default 1
+  --rxd N                 descriptors per Rx queue, default 64
+  --txd N                 descriptors per Tx queue, default 64
+  --burst N               forwarding burst size, default 32
+  --total-num-mbufs N     mbufs per socket pool
+  --forward-mode MODE     forwarding mode, default io
+  --nb-cores N            forwarding lcores, default all worker lcores
+  --port-topology MODE    paired, chained or loop, default paired
+  --fwd-streams STREAMS   <rx port>:<rx queue>-<tx port>:<tx queue>[,...]
+  -i, --interactive       run the command line
+  -a, --auto-start        start forwarding in interactive mode
+  --cmdline-file FILE     run commands from FILE before forwarding starts
+  --exit-on-error         stop command files at the first failed command
+  --history-file FILE     interactive command history, default ~/.runpmd_history
+  -h, --help              show this help";
+
+    /// Parses runpmd options, values follow the option or are given as `--option=value`.
+    ///
+    /// Returns `Ok(None)` if help was requested.
+    pub fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let mut params = RunPmdParams::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let (option, inline_value) = match arg.split_once('=') {
+                Some((option, value)) if option.starts_with("--") => (option, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next().cloned())
+                    .ok_or_else(|| format!("{option}: missing value"))
+            };
+            let max_queues = RTE_MAX_QUEUES_PER_PORT as u16;
+            match option {
+                "--rxq" => params.rxq = parse_num(option, &value()?, 1, max_queues)?,
+                "--txq" => params.txq = parse_num(option, &value()?, 1, max_queues)?,
+                "--rxd" => params.rxd = parse_num(option, &value()?, 1, u16::MAX)?,
+                "--txd" => params.txd = parse_num(option, &value()?, 1, u16::MAX)?,
+                "--burst" => params.burst = parse_num(option, &value()?, 1, MAX_PKT_BURST)?,
+                "--total-num-mbufs" => {
+                    params.total_num_mbufs = Some(parse_num(option, &value()?, 1, u32::MAX)?)
+                }
+                "--forward-mode" => {
+                    let mode = value()?;
+                    params.forward_mode = fwd::fwd_engine(&mode).ok_or_else(|| {
+                        format!(
+                            "{option}: unknown mode '{mode}', expected {}",
+                            fwd::fwd_engine_names().join("|")
+                        )
+                    })?;
+                }
+                "--nb-cores" => params.nb_cores = Some(parse_num(option, &value()?, 1, u16::MAX)?),
+                "--port-topology" => {
+                    params.topology = PortTopology::parse(&[value()?.as_str()])
+                        .map_err(|err| format!("{option}: {err}"))?;
+                }
+                "--fwd-streams" => {
+                    params.topology = PortTopology::parse_streams(&[value()?.as_str()])
+                        .map_err(|err| format!("{option}: {err}"))?;
+                }
+                "-i" | "--interactive" => params.interactive = true,
+                "-a" | "--auto-start" => params.auto_start = true,
+                "--cmdline-file" => {
+                    let path = PathBuf::from(value()?);
+                    if !path.is_file() {
+                        return Err(format!("{option}: {} is not a readable file", path.display()));
+                    }
+                    params.cmdline_file = Some(path);
+                }
+                "--exit-on-error" => params.exit_on_error = true,
+                "--history-file" => params.history_file = Some(PathBuf::from(value()?)),
+                "-h" | "--help" => return Ok(None),
+                _ => return Err(format!("unknown option '{arg}'")),
+            }
+            if inline_value.is_some() && matches!(option, "--interactive" | "--auto-start" | "--exit-on-error") {
+                return Err(format!("{option}: option takes no value"));
+            }
+        }
+        Ok(Some(params))
+    }
+
+    /// Mbufs needed to fill the Rx and Tx rings of a port and a forwarding burst per
+    /// Rx queue.
+    pub fn port_mbufs(&self) -> u32 {
+        self.rxq as u32 * (self.rxd as u32 + self.burst as u32) + self.txq as u32 * self.txd as u32
+    }
+
+    /// Validates the options against the ports and lcores found by EAL, returns the
+    /// number of mbufs in each pool.
+    pub fn check(&self, nb_ports: u16, nb_worker_lcores: usize) -> Result<u32, String> {
+        if let Some(nb_cores) = self.nb_cores {
+            if nb_cores as usize > nb_worker_lcores {
+                return Err(format!(
+                    "--nb-cores: {nb_cores} forwarding lcores requested, EAL has {nb_worker_lcores} worker lcores"
+                ));
+            }
+        }
+        let needed = (nb_ports.max(1) as u64 * self.port_mbufs() as u64).min(u32::MAX as u64) as u32;
+        match self.total_num_mbufs {
+            Some(n) if n < needed => Err(format!(
+                "--total-num-mbufs: {n} mbufs is too small, {nb_ports} ports need at least {needed}"
+            )),
+            Some(n) => Ok(n),
+            None => Ok(needed.max(1024)),
+        }
+    }
+
+    pub fn fwd_config(&self) -> FwdConfig {
+        let mut fwd = FwdConfig::default();
+        fwd.engine = self.forward_mode;
+        fwd.burst = self.burst;
+        fwd.nb_cores = self.nb_cores;
+        fwd.topology = self.topology.clone();
+        fwd
+    }
+}
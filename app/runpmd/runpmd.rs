@@ -1,22 +1,31 @@
+#[path = "cli/cli.rs"]
+pub mod cli;
 #[path = "cmd_module/cmd_module.rs"]
 pub mod cmd_module;
+#[path = "fwd/fwd.rs"]
+pub mod fwd;
+#[path = "params/params.rs"]
+pub mod params;
 
+use cli::{Cli, CmdModule, CmdStatus};
 use cmd_module::flow::FlowCmd;
+use cmd_module::flow::table::FlowTable;
+use cmd_module::fwd::FwdCmd;
+use params::RunPmdParams;
 use cmd_module::port::PortModule;
 use rdpdk::dpdk_raw::rte_eal::{rte_eal_cleanup, rte_eal_init};
-use std::collections::HashMap;
-use std::{env, slice};
+use std::env;
 use std::ffi::{CStr, CString};
-use std::io::Write;
 use std::os::raw::{c_char, c_int};
-use std::ptr::null_mut;
 use rdpdk::dpdk_raw::rte_ethdev::{rte_eth_dev_count_avail, rte_eth_dev_get_name_by_port, rte_eth_dev_socket_id, rust_get_port_eth_device, RTE_ETH_NAME_MAX_LEN};
-use rdpdk::dpdk_raw::rte_mbuf::{rte_mbuf};
 use rdpdk::dpdk_raw::rte_mbuf_core::RTE_MBUF_DEFAULT_BUF_SIZE;
-use std::thread;
 
-use rdpdk::port::{alloc_mbuf_pool, DpdkPort, DpdkPortConf};
+use rdpdk::port::{DpdkPort, DpdkPortConf};
+use rdpdk::port::mempool::MempoolRegistry;
+use rdpdk::port::error::PortError;
 use rdpdk::port::raw_port::{RawDpdkPort};
+use rdpdk::port::io_engine::{self, IoEngine};
+use rdpdk::port::offload::TxOffloads;
 use std::sync::{Arc, Mutex};
 
 use rdpdk::port::init::{
@@ -24,35 +33,6 @@ use rdpdk::port::init::{
     PciDevice,
     KNOWN_PORTS,
 };
-use crate::cmd_module::CmdModuleOps;
-
-fn read_input() -> Result<String, String> {
-    let mut buffer = String::new();
-
-    print!(">>> ");
-    std::io::stdout().flush().unwrap();
-
-    let len = std::io::stdin().read_line(&mut buffer).unwrap();
-    println!("=== input len: {len}");
-    if len == 0 {
-        return Ok(buffer);
-    }
-
-    if buffer.ends_with("\\\n") {
-        loop {
-            buffer.remove(buffer.len() - 1);
-            buffer.remove(buffer.len() - 1);
-            print!("> ");
-            std::io::stdout().flush().unwrap();
-            std::io::stdin().read_line(&mut buffer).unwrap();
-            if !buffer.ends_with("\\\n") {
-                break;
-            }
-        }
-    }
-
-    Ok(buffer)
-}
 
 fn separate_cmd_line() -> (Vec<String>, Vec<String>) {
     let argv = env::args().collect::<Vec<_>>();
@@ -79,7 +59,7 @@ fn eal_init(args: &Vec<String>) -> Result<u16, String> {
         .map(|arg| CString::new(arg.as_bytes()).unwrap().into_raw())
         .collect();
 
-    let rc = unsafe { rte_eal_init(env::args().len() as c_int, argv.as_mut_ptr()) };
+    let rc = unsafe { rte_eal_init(argv.len() as c_int, argv.as_mut_ptr()) };
     if rc < 0 {
         unsafe {
             rte_eal_cleanup();
@@ -90,126 +70,13 @@ fn eal_init(args: &Vec<String>) -> Result<u16, String> {
     }
 }
 
-fn run_command(modules: &CmdModule, command: &str) {
-    let mut input = command
-        .split_ascii_whitespace()
-        .map(|x| x.to_string())
-        .collect::<Vec<String>>();
-
-    match modules.get(&input[0]) {
-        Some(op) => {
-            op.parse_cmd(&mut input);
-        }
-        None => {
-            println!("Unknown command: {}", &input[0]);
-            ()
-        }
-    }
-}
-
-#[derive(Copy, Clone)]
-struct MbufPtr(*const rte_mbuf);
-
-impl std::fmt::Debug for MbufPtr {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.0.is_null() {
-            return write!(f, "MbufPtr(null)");
-        }
-
-        // Safety: We've checked that the pointer is not null
-        let mbuf = unsafe { &*self.0 };
-
-        let data_off = unsafe { mbuf.__bindgen_anon_1.__bindgen_anon_1.data_off };
-        let pkt_len = unsafe { mbuf.__bindgen_anon_2.__bindgen_anon_1.pkt_len };
-
-        write!(f,
-            "MbufPtr {{ address: {:p}, data_offset: {}, packet_length: {} }}",
-            self.0,
-            data_off,
-            pkt_len
-        )
-    }
-}
-fn show_packet(mbuf: &rte_mbuf) {
-
-    let data_off = unsafe {mbuf.__bindgen_anon_1.__bindgen_anon_1.data_off};
-    let pkt_len = unsafe {mbuf.__bindgen_anon_2.__bindgen_anon_1.pkt_len};
-    let raw_ptr = mbuf.buf_addr.wrapping_add(data_off as usize) as *const u8;
-    let eth:&[u8] = unsafe { slice::from_raw_parts(raw_ptr, 14) };
-
-    println!(
-        "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x} > \
-        {:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x} eth_type {:x} len {}",
-        eth[6],eth[7],eth[8],eth[9],eth[10],eth[11],
-        eth[0],eth[1],eth[2],eth[3],eth[4],eth[5],
-        u16::from_be_bytes([eth[12],eth[13]]) as u16,
-        pkt_len
-    );
-}
-
-fn l2_addr_swap(mbuf: &mut rte_mbuf)
-{
-    let data_off = unsafe {mbuf.__bindgen_anon_1.__bindgen_anon_1.data_off};
-    let raw_ptr = mbuf.buf_addr.wrapping_add(data_off as usize) as *mut u8;
-    let eth: &mut [u8] = unsafe { slice::from_raw_parts_mut(raw_ptr, 14) };
-
-    for i in 0..6 {
-        let aux = eth[i];
-        eth[i] = eth[i + 6];
-        eth[i + 6] = aux;
-    }
-}
-
-fn do_io(runpmd: Arc<RunPmd>) {
-    for pretected in runpmd.ports.iter() {
-        let rx_pkts:[*mut rte_mbuf; 64] = [null_mut(); 64];
-        let mut port = pretected.lock().unwrap();
-        let port_id = port.port_id();
-        let rx_burst_res = port.rx_burst(port_id, &rx_pkts);
-
-            match rx_burst_res {
-                Err(err) => println!("{err}"),
-                Ok(rx_num) => {
-                    let tx_pool:&[*mut rte_mbuf] = &rx_pkts[0..rx_num as usize];
-                    if rx_num > 0 {
-                        for i in 0..rx_num {
-                            show_packet(&unsafe { *(rx_pkts[i as usize] as *const rte_mbuf) });
-                            let mbuf_ptr: *const rte_mbuf = rx_pkts[i as usize] as *const rte_mbuf;
-                            println!("{:?}", MbufPtr(mbuf_ptr));
-
-                            l2_addr_swap(&mut unsafe { *(rx_pkts[i as usize] as *mut rte_mbuf) });
-
-                            let _ = port.tx_burst(port_id, tx_pool);
-                        }
-                        continue;
-                    }
-                }
-            }
-    }
-}
-
-fn run_interactive(modules: &CmdModule) {
-    loop {
-        let input = read_input().unwrap();
-        if input.len() < 2 {
-            continue;
-        }
-
-        let slin = &input[0..input.len() - 1];
-        println!("===\n\'{}\': len:{}\n===", slin, slin.len());
-        match slin {
-            "exit" => break,
-            _ => run_command(modules, slin),
-        }
-    }
-    println!("Live long and prosper");
-}
-
-type CmdModule = HashMap<String, Box<dyn CmdModuleOps>>;
-fn register_cmd_modules() -> CmdModule {
+fn register_cmd_modules(runpmd: Arc<RunPmd>) -> CmdModule {
     let mut modules = CmdModule::new();
-    modules.insert("flow".to_string(), Box::new(FlowCmd::new()));
-    modules.insert("port".to_string(), Box::new(PortModule::new()));
+    modules.insert("flow".to_string(), Box::new(FlowCmd::new(runpmd.clone())));
+    for cmd in ["set", "show", "start", "stop"] {
+        modules.insert(cmd.to_string(), Box::new(FwdCmd::new(runpmd.clone())));
+    }
+    modules.insert("port".to_string(), Box::new(PortModule::new(runpmd)));
     modules
 }
 
@@ -243,7 +110,7 @@ fn query_port_businfo(port_id: u16) -> (PciVendor, PciDevice) {
 
 }
 
-fn start_port(port_id: u16, port_conf: &DpdkPortConf) -> Result<Box<dyn DpdkPort>, String>
+fn start_port(port_id: u16, port_conf: &DpdkPortConf) -> Result<Box<dyn DpdkPort>, PortError>
 {
     let (vendor, device) = query_port_businfo(port_id);
 
@@ -251,52 +118,59 @@ fn start_port(port_id: u16, port_conf: &DpdkPortConf) -> Result<Box<dyn DpdkPort
         if vendor != *v { continue }
             match func(port_id, device, port_conf) {
                 Ok(port) => { return Ok(port); },
-                Err(_e) => { continue; }
+                // the vendor driver does not handle this device
+                Err(PortError::Unsupported { .. }) => continue,
+                Err(e) => {
+                    println!("{e}");
+                    continue;
+                }
             }
     }
     println!("port {port_id}: fallback to raw port");
-    Ok(Box::new(RawDpdkPort::init(port_id, port_conf).unwrap()))
+    Ok(Box::new(RawDpdkPort::init(port_id, port_conf)?))
 }
 
 
 
-fn init_ports_configuration(_args: &Vec<String>, port_num: u16) -> Vec::<DpdkPortConf> {
+fn init_ports_configuration(
+    params: &RunPmdParams,
+    port_num: u16,
+    num_mbufs: u32,
+) -> Result<Vec<DpdkPortConf>, PortError> {
     let mut port_conf =
         Vec::<DpdkPortConf>::with_capacity(port_num as usize);
 
-    let mbuf_pool = alloc_mbuf_pool(
-        "runpmd_default_mbuf_pool",
-        1024,
+    let mbuf_pools = MempoolRegistry::new(
+        "runpmd_mbuf_pool",
+        num_mbufs,
         0,
         0,
         RTE_MBUF_DEFAULT_BUF_SIZE as u16,
-        0,
-    ).unwrap() ;
-    let mbuf_pool = Arc::new(mbuf_pool);
+    );
 
     for port_id in 0..port_num {
         let socket_id = unsafe { rte_eth_dev_socket_id(port_id) } as u32;
-        let pc = DpdkPortConf::new_from(
-            port_id,
-            unsafe { std::mem::zeroed() },
-            unsafe { std::mem::zeroed() },
-            unsafe { std::mem::zeroed() },
-            1,
-            1,
-            64,
-            64,
-            socket_id,
-            socket_id,
-            Some(mbuf_pool.clone())
-        ).unwrap();
+        let mbuf_pool = mbuf_pools.for_port(port_id)?;
+        let pc = DpdkPortConf::builder(port_id)
+            .rx_queues(params.rxq)
+            .tx_queues(params.txq)
+            .rx_descriptors(params.rxd)
+            .tx_descriptors(params.txd)
+            .socket_id(socket_id)
+            .rx_mempool(mbuf_pool)
+            .optional_tx_offloads(TxOffloads::CHECKSUM)
+            .build()?;
         port_conf.insert(port_id as usize, pc);
     }
 
-    port_conf
+    Ok(port_conf)
 }
 
-struct RunPmd {
-    ports: Vec<Mutex<Box<dyn DpdkPort>>>    
+pub struct RunPmd {
+    pub ports: Vec<Mutex<Box<dyn DpdkPort>>>,
+    pub engine: Mutex<IoEngine>,
+    pub fwd: Mutex<fwd::FwdConfig>,
+    pub flows: Mutex<FlowTable>,
 }
 unsafe impl Send for RunPmd {}
 unsafe impl Sync for RunPmd {}
@@ -304,6 +178,18 @@ unsafe impl Sync for RunPmd {}
 fn main() {
     let (eal_params, app_params) = separate_cmd_line();
 
+    let params = match RunPmdParams::parse(&app_params) {
+        Ok(Some(params)) => params,
+        Ok(None) => {
+            println!("{}", RunPmdParams::USAGE);
+            std::process::exit(0);
+        }
+        Err(e) => {
+            println!("{e}\n{}", RunPmdParams::USAGE);
+            std::process::exit(255);
+        }
+    };
+
     let port_num = match eal_init(&eal_params) {
         Ok(n) => n,
         Err(e) => {
@@ -312,7 +198,22 @@ fn main() {
         }
     };
 
-    let port_conf = Arc::new(init_ports_configuration(&app_params, port_num));
+    let num_mbufs = match params.check(port_num, io_engine::worker_lcores().len()) {
+        Ok(n) => n,
+        Err(e) => {
+            println!("{e}");
+            unsafe { rte_eal_cleanup() };
+            std::process::exit(255);
+        }
+    };
+    let port_conf = match init_ports_configuration(&params, port_num, num_mbufs) {
+        Ok(port_conf) => Arc::new(port_conf),
+        Err(e) => {
+            println!("{e}");
+            unsafe { rte_eal_cleanup() };
+            std::process::exit(255);
+        }
+    };
 
     let mut ports: Vec<Mutex<Box<dyn DpdkPort>>> = Vec::with_capacity(port_num as usize);
     for port_id in 0..port_num {
@@ -327,23 +228,73 @@ fn main() {
     show_ports_summary(&ports);
     
     let runpmd = Arc::new(RunPmd {
-        ports: ports
-    });
-    
-
-    let _ = thread::spawn(move || {
-        loop { do_io(runpmd.clone()); }
+        ports: ports,
+        engine: Mutex::new(IoEngine::new()),
+        fwd: Mutex::new(params.fwd_config()),
+        flows: Mutex::new(FlowTable::default()),
     });
 
-    let cli_thread = thread::spawn(move || {
-        let modules = register_cmd_modules();
-        run_interactive(&modules);
-    });
+    // the command line runs on the main lcore, forwarding workers are launched from it
+    let cli = Cli::new(register_cmd_modules(runpmd.clone()), params.exit_on_error);
+    // from here on Ctrl-C must not skip the port and EAL cleanup
+    if let Err(err) = cli::handle_interrupts() {
+        println!("{err}");
+    }
+    let status = match &params.cmdline_file {
+        Some(path) => cli.load(path),
+        None => Ok(CmdStatus::Continue),
+    };
+    let exit_code = match status {
+        Ok(CmdStatus::Continue) => {
+            if params.auto_start || !params.interactive {
+                if let Err(err) = fwd::start_forwarding(&runpmd) {
+                    println!("{err}");
+                }
+            }
+            if params.interactive {
+                cli.run_interactive(params.history_file.as_deref());
+            } else {
+                cli::wait_for_exit();
+            }
+            0
+        }
+        Ok(CmdStatus::Exit) => 0,
+        Err(err) => {
+            println!("{err}");
+            1
+        }
+    };
+    drop(cli);
 
-    cli_thread.join().unwrap();
+    // queue handles must be released before the ports are closed
+    if runpmd.engine.lock().unwrap().is_running() {
+        if let Err(err) = fwd::stop_forwarding(&runpmd) {
+            println!("{err}");
+        }
+    }
+    close_ports(&runpmd);
+    // ports and mbuf pools must be released before EAL cleanup
+    drop(runpmd);
+    drop(port_conf);
+    unsafe { rte_eal_cleanup() };
 
     // without direct reference to the mlx5 port linker does not include it in a build.
     mlx5::mlx5_pol(); // TODO: remove
+    std::process::exit(exit_code);
+}
+
+fn close_ports(runpmd: &RunPmd) {
+    for p in runpmd.ports.iter() {
+        let mut port = p.lock().unwrap();
+        let port_id = port.port_id();
+        // closing the port destroys its flow rules
+        runpmd.flows.lock().unwrap().clear(port_id);
+        print!("Stopping port {port_id}... ");
+        match port.stop().and_then(|_| port.close()) {
+            Ok(()) => println!("closed"),
+            Err(err) => println!("{err}"),
+        }
+    }
 }
 
 pub fn show_ports_summary(ports: &Vec<Mutex<Box<dyn DpdkPort>>>) {
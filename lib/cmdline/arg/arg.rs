@@ -63,9 +63,31 @@ impl ArgData {
         self.size = std::cmp::max(self.size, offset + src.len());
         Ok(())
     }
+
+    // `len` most significant bits set over `size` bytes.
+    pub fn new_prefix_mask(size: usize, len: usize) -> Result<Self, String> {
+        if len > size * 8 {
+            return Err(format!("invalid prefix length: {len}"));
+        }
+        let mut arg = ArgData::new_from_size(size);
+        for i in 0..len {
+            arg.data[i / 8] |= 0x80 >> (i % 8);
+        }
+        Ok(arg)
+    }
 }
 
 pub trait Arg {
     fn serialize(&self, sample: &str) -> Result<ArgData, String>;
+
+    // Bits of the field a serialized value belongs to, used by the `is` modifier.
+    fn mask(&self, arg: &ArgData) -> ArgData {
+        ArgData::new_from_slice(&ArgData::DEFAULT_MASK[0..arg.size])
+    }
+
+    // Mask of the `len` leading bits of the field.
+    fn prefix(&self, _len: usize) -> Result<ArgData, String> {
+        Err("prefix is not supported by the argument".to_string())
+    }
 }
 
@@ -70,6 +70,60 @@ impl<A: FromStr + Num + ToBytes> Arg for ArgInt<A> {
     }
 }
 
+// Field of `width` bits, `shift` bits above the least significant bit of a
+// `size` bytes word. The word is big endian unless built with another order:
+// IPv6 traffic class is `ArgBitField::new(4, 20, 8)`.
+pub struct ArgBitField {
+    size: usize,
+    shift: u32,
+    width: u32,
+    bytes_order: ByteOrder,
+}
+
+impl ArgBitField {
+    pub fn new(size: usize, shift: u32, width: u32) -> Self {
+        ArgBitField::new_with_order(size, shift, width, ByteOrder::BigEndian)
+    }
+
+    pub fn new_with_order(size: usize, shift: u32, width: u32, order: ByteOrder) -> Self {
+        assert!(size <= 8 && shift + width <= size as u32 * 8);
+        ArgBitField {
+            size,
+            shift,
+            width,
+            bytes_order: order,
+        }
+    }
+
+    fn to_data(&self, val: u64) -> ArgData {
+        let word = val << self.shift;
+        let little_endian = match self.bytes_order {
+            ByteOrder::HostOrder => cfg!(target_endian = "little"),
+            ByteOrder::LittleEndian => true,
+            ByteOrder::BigEndian => false,
+        };
+        if little_endian {
+            ArgData::new_from_slice(&word.to_le_bytes()[..self.size])
+        } else {
+            ArgData::new_from_slice(&word.to_be_bytes()[8 - self.size..])
+        }
+    }
+}
+
+impl Arg for ArgBitField {
+    fn serialize(&self, sample: &str) -> Result<ArgData, String> {
+        let val = ArgInt::<u64>::new().strton(sample)?;
+        if val >> self.width != 0 {
+            return Err(format!("value out of range: \"{sample}\""));
+        }
+        Ok(self.to_data(val))
+    }
+
+    fn mask(&self, _arg: &ArgData) -> ArgData {
+        self.to_data((1 << self.width) - 1)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,4 +139,40 @@ mod tests {
         let x64 = ArgInt::<u64>::new();
         x64.serialize("0x1234567812345678").unwrap();
     }
+
+    #[test]
+    fn bit_field() {
+        // IPv6 flow label
+        let flow = ArgBitField::new(4, 0, 20);
+        let arg = flow.serialize("0x12345").unwrap();
+        assert_eq!(arg.data[0..arg.size], [0x00, 0x01, 0x23, 0x45]);
+        let mask = flow.mask(&arg);
+        assert_eq!(mask.data[0..mask.size], [0x00, 0x0f, 0xff, 0xff]);
+        assert!(flow.serialize("0x100000").is_err());
+
+        // IPv6 traffic class
+        let tc = ArgBitField::new(4, 20, 8);
+        let arg = tc.serialize("0xab").unwrap();
+        assert_eq!(arg.data[0..arg.size], [0x0a, 0xb0, 0x00, 0x00]);
+        let mask = tc.mask(&arg);
+        assert_eq!(mask.data[0..mask.size], [0x0f, 0xf0, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn bit_field_order() {
+        // VLAN priority, big endian TCI
+        let pcp = ArgBitField::new(2, 13, 3);
+        let arg = pcp.serialize("5").unwrap();
+        assert_eq!(arg.data[0..arg.size], [0xa0, 0x00]);
+
+        // MPLS bottom of stack, 3 bytes word
+        let s = ArgBitField::new(3, 0, 1);
+        let arg = s.serialize("1").unwrap();
+        assert_eq!(arg.data[0..arg.size], [0x00, 0x00, 0x01]);
+
+        let flag = ArgBitField::new_with_order(4, 1, 1, ByteOrder::LittleEndian);
+        let arg = flag.serialize("1").unwrap();
+        assert_eq!(arg.data[0..arg.size], [0x02, 0x00, 0x00, 0x00]);
+        assert!(flag.serialize("2").is_err());
+    }
 }
@@ -1,4 +1,5 @@
 use crate::cmdline::arg::{Arg, ArgData};
+use std::net::{Ipv4Addr, Ipv6Addr};
 
 pub struct EthAddrArg;
 
@@ -10,12 +11,17 @@ impl EthAddrArg {
 
 impl Arg for EthAddrArg {
     fn serialize(&self, sample: &str) -> Result<ArgData, String> {
-        let mut arg = ArgData::new_from_size(6);
-
-        for (i, val) in sample.split(':').enumerate() {
-            arg.data[i] = u8::from_str_radix(val, 16).unwrap()
+        let octets = sample
+            .split(':')
+            .map(|val| match val.len() {
+                1 | 2 if val.chars().all(|c| c.is_ascii_hexdigit()) => u8::from_str_radix(val, 16).ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        match octets {
+            Some(octets) if octets.len() == 6 => Ok(ArgData::new_from_slice(&octets)),
+            _ => Err(format!("invalid argument: \"{sample}\"")),
         }
-        Ok(arg)
     }
 }
 
@@ -26,13 +32,74 @@ impl Ipv4AddrArg {
 
 impl Arg for Ipv4AddrArg {
     fn serialize(&self, sample: &str) -> Result<ArgData, String> {
-        let mut arg = ArgData::new_from_size(4);
-        for (i, val) in sample.split('.').enumerate() {
-            arg.data[i] = match val.parse::<u8>() {
-                Ok(v) => v,
-                Err(_) => return Err(format!("invalid argument: \"{sample}\"")),
-            }
+        match sample.parse::<Ipv4Addr>() {
+            Ok(addr) => Ok(ArgData::new_from_slice(&addr.octets())),
+            Err(_) => Err(format!("invalid argument: \"{sample}\"")),
+        }
+    }
+
+    fn prefix(&self, len: usize) -> Result<ArgData, String> {
+        ArgData::new_prefix_mask(4, len)
+    }
+}
+
+pub struct Ipv6AddrArg;
+impl Ipv6AddrArg {
+    pub fn new() -> Ipv6AddrArg { Ipv6AddrArg {} }
+}
+
+impl Arg for Ipv6AddrArg {
+    fn serialize(&self, sample: &str) -> Result<ArgData, String> {
+        match sample.parse::<Ipv6Addr>() {
+            Ok(addr) => Ok(ArgData::new_from_slice(&addr.octets())),
+            Err(_) => Err(format!("invalid argument: \"{sample}\"")),
         }
-        Ok(arg)
+    }
+
+    fn prefix(&self, len: usize) -> Result<ArgData, String> {
+        ArgData::new_prefix_mask(16, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eth_addr() {
+        let arg = EthAddrArg::new().serialize("00:1b:21:0a:ff:1").unwrap();
+        assert_eq!(arg.data[0..arg.size], [0x00, 0x1b, 0x21, 0x0a, 0xff, 0x01]);
+        assert!(EthAddrArg::new().serialize("zz:00:00:00:00:01").is_err());
+        assert!(EthAddrArg::new().serialize("00:00:00:00:00").is_err());
+        assert!(EthAddrArg::new().serialize("00:00:00:00:00:01:02").is_err());
+        assert!(EthAddrArg::new().serialize("00:00:00:00:00:+1").is_err());
+    }
+
+    #[test]
+    fn ipv4_addr() {
+        let arg = Ipv4AddrArg::new().serialize("10.1.2.3").unwrap();
+        assert_eq!(arg.data[0..arg.size], [10, 1, 2, 3]);
+        assert!(Ipv4AddrArg::new().serialize("10.1.2").is_err());
+        assert!(Ipv4AddrArg::new().serialize("10.1.2.3.4").is_err());
+    }
+
+    #[test]
+    fn ipv6_addr() {
+        let arg = Ipv6AddrArg::new().serialize("2001:db8::1").unwrap();
+        assert_eq!(arg.size, 16);
+        assert_eq!(arg.data[0..4], [0x20, 0x01, 0x0d, 0xb8]);
+        assert_eq!(arg.data[15], 1);
+        assert!(Ipv6AddrArg::new().serialize("2001:db8::g").is_err());
+    }
+
+    #[test]
+    fn prefix() {
+        let mask = Ipv4AddrArg::new().prefix(20).unwrap();
+        assert_eq!(mask.data[0..mask.size], [0xff, 0xff, 0xf0, 0x00]);
+
+        let mask = Ipv6AddrArg::new().prefix(64).unwrap();
+        assert_eq!(mask.data[0..mask.size], [[0xff; 8], [0; 8]].concat()[..]);
+        assert!(Ipv6AddrArg::new().prefix(129).is_err());
+        assert!(EthAddrArg::new().prefix(24).is_err());
     }
 }
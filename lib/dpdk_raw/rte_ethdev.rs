@@ -25875,6 +25875,16 @@ unsafe extern "C" {
 unsafe extern "C" {
     pub fn rust_get_port_fp_ops(port_id: u16) -> *mut rte_eth_fp_ops;
 }
+unsafe extern "C" {
+    pub fn rust_pktmbuf_alloc_bulk(
+        pool: *mut rte_mempool,
+        mbufs: *mut *mut rte_mbuf,
+        count: ::std::os::raw::c_uint,
+    ) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn rust_get_rte_errno() -> ::std::os::raw::c_int;
+}
 pub type __builtin_va_list = [__va_list_tag; 1usize];
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -0,0 +1,125 @@
+use crate::flow::RuleMemory;
+use crate::port::rss::RssHashFunction;
+use crate::dpdk_raw::rte_ethdev::{
+    rte_flow_action,
+    rte_flow_action_type,
+    rte_flow_action_mark,
+    rte_flow_action_queue,
+    rte_flow_action_jump,
+    rte_flow_action_count,
+    rte_flow_action_rss,
+    rte_flow_action_ethdev,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_VOID,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_PASSTHRU,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_FLAG,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_DROP,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_MARK,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_QUEUE,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_JUMP,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_COUNT,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_RSS,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_REPRESENTED_PORT,
+};
+
+/// RSS action configuration, `RTE_FLOW_ACTION_TYPE_RSS`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FlowRss {
+    pub func: RssHashFunction,
+    /// Encapsulation level the hash applies to, 0 for the device default.
+    pub level: u32,
+    /// Hashed packet fields (`RTE_ETH_RSS_*`), 0 for the device default.
+    pub types: u64,
+    /// Hash key, `None` for the device key.
+    pub key: Option<Vec<u8>>,
+    /// Rx queues the packets are spread over.
+    pub queues: Vec<u16>,
+}
+
+/// Flow rule action.
+///
+/// The terminating `END` action is added when the rule is converted to its raw form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlowAction {
+    /// Ignored action.
+    Void,
+    /// Leaves the packet to the rules of lower priority.
+    Passthru,
+    /// Sets the `RTE_MBUF_F_RX_FDIR` mbuf flag.
+    Flag,
+    Drop,
+    /// Sets the `RTE_MBUF_F_RX_FDIR_ID` flag and the mbuf `hash.fdir.hi` field.
+    Mark(u32),
+    /// Rx queue index.
+    Queue(u16),
+    /// Continues in another flow group.
+    Jump(u32),
+    /// Counts packets and bytes, read with `rte_flow_query`. The counter ID must be
+    /// unique within the rule.
+    Count(u32),
+    Rss(FlowRss),
+    /// Sends the packets to the port represented by an ethdev port ID.
+    RepresentedPort(u16),
+}
+
+impl FlowAction {
+    /// testpmd name of the action.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FlowAction::Void => "void",
+            FlowAction::Passthru => "passthru",
+            FlowAction::Flag => "flag",
+            FlowAction::Drop => "drop",
+            FlowAction::Mark(_) => "mark",
+            FlowAction::Queue(_) => "queue",
+            FlowAction::Jump(_) => "jump",
+            FlowAction::Count(_) => "count",
+            FlowAction::Rss(_) => "rss",
+            FlowAction::RepresentedPort(_) => "represented_port",
+        }
+    }
+
+    /// Action type, `RTE_FLOW_ACTION_TYPE_*`.
+    pub fn action_type(&self) -> rte_flow_action_type {
+        match self {
+            FlowAction::Void => rte_flow_action_type_RTE_FLOW_ACTION_TYPE_VOID,
+            FlowAction::Passthru => rte_flow_action_type_RTE_FLOW_ACTION_TYPE_PASSTHRU,
+            FlowAction::Flag => rte_flow_action_type_RTE_FLOW_ACTION_TYPE_FLAG,
+            FlowAction::Drop => rte_flow_action_type_RTE_FLOW_ACTION_TYPE_DROP,
+            FlowAction::Mark(_) => rte_flow_action_type_RTE_FLOW_ACTION_TYPE_MARK,
+            FlowAction::Queue(_) => rte_flow_action_type_RTE_FLOW_ACTION_TYPE_QUEUE,
+            FlowAction::Jump(_) => rte_flow_action_type_RTE_FLOW_ACTION_TYPE_JUMP,
+            FlowAction::Count(_) => rte_flow_action_type_RTE_FLOW_ACTION_TYPE_COUNT,
+            FlowAction::Rss(_) => rte_flow_action_type_RTE_FLOW_ACTION_TYPE_RSS,
+            FlowAction::RepresentedPort(_) => rte_flow_action_type_RTE_FLOW_ACTION_TYPE_REPRESENTED_PORT,
+        }
+    }
+
+    // Raw action, the configuration is kept in `memory`.
+    pub(crate) fn to_raw(&self, memory: &mut RuleMemory) -> rte_flow_action {
+        let conf = match self {
+            FlowAction::Void | FlowAction::Passthru | FlowAction::Flag | FlowAction::Drop => std::ptr::null(),
+            FlowAction::Mark(id) => memory.keep(rte_flow_action_mark { id: *id }),
+            FlowAction::Queue(index) => memory.keep(rte_flow_action_queue { index: *index }),
+            FlowAction::Jump(group) => memory.keep(rte_flow_action_jump { group: *group }),
+            FlowAction::Count(id) => memory.keep(rte_flow_action_count { id: *id }),
+            FlowAction::RepresentedPort(port_id) => memory.keep(rte_flow_action_ethdev { port_id: *port_id }),
+            FlowAction::Rss(rss) => {
+                let (key, key_len) = match &rss.key {
+                    Some(key) => (memory.keep_slice(key), key.len() as u32),
+                    None => (std::ptr::null(), 0),
+                };
+                let queue = memory.keep_slice(&rss.queues);
+                memory.keep(rte_flow_action_rss {
+                    func: rss.func.to_raw(),
+                    level: rss.level,
+                    types: rss.types,
+                    key_len,
+                    queue_num: rss.queues.len() as u32,
+                    key,
+                    queue,
+                })
+            }
+        };
+        rte_flow_action { type_: self.action_type(), conf }
+    }
+}
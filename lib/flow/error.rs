@@ -0,0 +1,346 @@
+use std::ffi::{c_void, CStr};
+use std::fmt;
+use std::io;
+use crate::dpdk_raw::rte_ethdev::{
+    rte_flow_error,
+    rte_flow_error_type,
+    rte_flow_item,
+    rte_flow_action,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_NONE,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_UNSPECIFIED,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_HANDLE,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR_GROUP,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR_PRIORITY,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR_INGRESS,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR_EGRESS,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR_TRANSFER,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM_NUM,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM_SPEC,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM_LAST,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM_MASK,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ACTION_NUM,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ACTION_CONF,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ACTION,
+    rte_flow_error_type_RTE_FLOW_ERROR_TYPE_STATE,
+};
+
+/// Part of a flow rule a driver rejected, `RTE_FLOW_ERROR_TYPE_*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowErrorType {
+    None,
+    Unspecified,
+    /// Flow rule handle.
+    Handle,
+    AttrGroup,
+    AttrPriority,
+    AttrIngress,
+    AttrEgress,
+    AttrTransfer,
+    /// Attributes structure.
+    Attr,
+    /// Pattern length.
+    ItemNum,
+    ItemSpec,
+    ItemLast,
+    ItemMask,
+    /// Specific pattern item.
+    Item,
+    /// Number of actions.
+    ActionNum,
+    ActionConf,
+    /// Specific action.
+    Action,
+    /// Current device state.
+    State,
+    /// Type unknown to rdpdk.
+    Other(rte_flow_error_type),
+}
+
+impl FlowErrorType {
+    pub fn to_raw(self) -> rte_flow_error_type {
+        match self {
+            FlowErrorType::None => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_NONE,
+            FlowErrorType::Unspecified => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_UNSPECIFIED,
+            FlowErrorType::Handle => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_HANDLE,
+            FlowErrorType::AttrGroup => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR_GROUP,
+            FlowErrorType::AttrPriority => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR_PRIORITY,
+            FlowErrorType::AttrIngress => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR_INGRESS,
+            FlowErrorType::AttrEgress => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR_EGRESS,
+            FlowErrorType::AttrTransfer => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR_TRANSFER,
+            FlowErrorType::Attr => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ATTR,
+            FlowErrorType::ItemNum => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM_NUM,
+            FlowErrorType::ItemSpec => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM_SPEC,
+            FlowErrorType::ItemLast => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM_LAST,
+            FlowErrorType::ItemMask => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM_MASK,
+            FlowErrorType::Item => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM,
+            FlowErrorType::ActionNum => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ACTION_NUM,
+            FlowErrorType::ActionConf => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ACTION_CONF,
+            FlowErrorType::Action => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ACTION,
+            FlowErrorType::State => rte_flow_error_type_RTE_FLOW_ERROR_TYPE_STATE,
+            FlowErrorType::Other(error_type) => error_type,
+        }
+    }
+
+    pub fn from_raw(error_type: rte_flow_error_type) -> Self {
+        [
+            FlowErrorType::None,
+            FlowErrorType::Unspecified,
+            FlowErrorType::Handle,
+            FlowErrorType::AttrGroup,
+            FlowErrorType::AttrPriority,
+            FlowErrorType::AttrIngress,
+            FlowErrorType::AttrEgress,
+            FlowErrorType::AttrTransfer,
+            FlowErrorType::Attr,
+            FlowErrorType::ItemNum,
+            FlowErrorType::ItemSpec,
+            FlowErrorType::ItemLast,
+            FlowErrorType::ItemMask,
+            FlowErrorType::Item,
+            FlowErrorType::ActionNum,
+            FlowErrorType::ActionConf,
+            FlowErrorType::Action,
+            FlowErrorType::State,
+        ]
+        .into_iter()
+        .find(|known| known.to_raw() == error_type)
+        .unwrap_or(FlowErrorType::Other(error_type))
+    }
+
+    /// Error cause points to a pattern item or its spec, last or mask.
+    pub fn is_item(&self) -> bool {
+        matches!(
+            self,
+            FlowErrorType::ItemSpec | FlowErrorType::ItemLast | FlowErrorType::ItemMask | FlowErrorType::Item
+        )
+    }
+
+    /// Error cause points to an action or its configuration.
+    pub fn is_action(&self) -> bool {
+        matches!(self, FlowErrorType::ActionConf | FlowErrorType::Action)
+    }
+}
+
+impl fmt::Display for FlowErrorType {
+    // testpmd `port_flow_complain()` wording
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlowErrorType::None => write!(f, "no error"),
+            FlowErrorType::Unspecified => write!(f, "cause unspecified"),
+            FlowErrorType::Handle => write!(f, "flow rule (handle)"),
+            FlowErrorType::AttrGroup => write!(f, "group field"),
+            FlowErrorType::AttrPriority => write!(f, "priority field"),
+            FlowErrorType::AttrIngress => write!(f, "ingress field"),
+            FlowErrorType::AttrEgress => write!(f, "egress field"),
+            FlowErrorType::AttrTransfer => write!(f, "transfer field"),
+            FlowErrorType::Attr => write!(f, "attributes structure"),
+            FlowErrorType::ItemNum => write!(f, "pattern length"),
+            FlowErrorType::ItemSpec => write!(f, "item specification"),
+            FlowErrorType::ItemLast => write!(f, "item specification range"),
+            FlowErrorType::ItemMask => write!(f, "item specification mask"),
+            FlowErrorType::Item => write!(f, "specific pattern item"),
+            FlowErrorType::ActionNum => write!(f, "number of actions"),
+            FlowErrorType::ActionConf => write!(f, "action configuration"),
+            FlowErrorType::Action => write!(f, "specific action"),
+            FlowErrorType::State => write!(f, "current device state"),
+            FlowErrorType::Other(error_type) => write!(f, "error type {error_type}"),
+        }
+    }
+}
+
+// Index of the array element at `cause`.
+fn element_index<T>(cause: *const c_void, array: &[T]) -> Option<usize> {
+    let size = std::mem::size_of::<T>();
+    let offset = (cause as usize).checked_sub(array.as_ptr() as usize)?;
+    match offset % size == 0 && offset / size < array.len() {
+        true => Some(offset / size),
+        false => None,
+    }
+}
+
+/// Errors reported by the flow API.
+///
+/// # Overview
+///
+/// `rte_flow` functions fill an `rte_flow_error` with the rejected part of the rule,
+/// the object responsible for the error and a driver message. `FlowError::Dpdk` keeps
+/// them decoded, together with the failing call and port. The cause is reported as the
+/// index of the pattern item or action it points to, drivers may point either to the
+/// item / action or to its spec, mask or configuration.
+///
+/// `rte_flow_create` reports its error code through `rte_errno` only, `errno` is then
+/// read from it so that create and validate failures carry the same detail.
+///
+/// # Example
+///
+/// ```
+/// match rule.validate(port_id) {
+///     Err(FlowError::Dpdk { error_type, cause: Some(index), .. }) if error_type.is_item() => {
+///         eprintln!("pattern item {index} is not supported");
+///     }
+///     Err(err) => eprintln!("{err}"),
+///     Ok(()) => (),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlowError {
+    /// A DPDK flow call failed.
+    Dpdk {
+        /// Name of the failing DPDK function.
+        call: &'static str,
+        /// Port the call was applied to.
+        port_id: u16,
+        /// Negative errno returned by DPDK, or `-rte_errno` for calls returning a handle.
+        errno: Option<i32>,
+        /// Rejected part of the rule, `rte_flow_error.type`.
+        error_type: FlowErrorType,
+        /// Index of the pattern item or action responsible for the error.
+        cause: Option<usize>,
+        /// Driver message from `rte_flow_error`.
+        message: Option<String>,
+    },
+}
+
+impl FlowError {
+    /// Decodes the `rte_flow_error` filled by a failed call, the cause is looked up in
+    /// the END terminated `pattern` and `actions` the call was given.
+    pub fn from_raw(
+        call: &'static str,
+        port_id: u16,
+        errno: Option<i32>,
+        error: &rte_flow_error,
+        pattern: &[rte_flow_item],
+        actions: &[rte_flow_action],
+    ) -> Self {
+        let message = match error.message.is_null() {
+            true => None,
+            // the message is a static driver string, valid while the port is configured
+            false => Some(unsafe { CStr::from_ptr(error.message) }.to_string_lossy().into_owned()),
+        };
+        let error_type = FlowErrorType::from_raw(error.type_);
+        let cause = match error.cause.is_null() {
+            true => None,
+            false if error_type.is_item() => element_index(error.cause, pattern).or_else(|| {
+                pattern.iter().position(|item| [item.spec, item.last, item.mask].contains(&error.cause))
+            }),
+            false if error_type.is_action() => element_index(error.cause, actions)
+                .or_else(|| actions.iter().position(|action| action.conf == error.cause)),
+            false => None,
+        };
+        FlowError::Dpdk { call, port_id, errno, error_type, cause, message }
+    }
+
+    /// Converts a DPDK return code of a call without a rule into a `Result`.
+    ///
+    /// Non-negative codes are passed through, negative codes become [`FlowError::Dpdk`].
+    pub fn check(rc: i32, call: &'static str, port_id: u16, error: &rte_flow_error) -> Result<i32, FlowError> {
+        Self::check_rule(rc, call, port_id, error, &[], &[])
+    }
+
+    /// Converts a DPDK return code of a call given a rule into a `Result`.
+    pub fn check_rule(
+        rc: i32,
+        call: &'static str,
+        port_id: u16,
+        error: &rte_flow_error,
+        pattern: &[rte_flow_item],
+        actions: &[rte_flow_action],
+    ) -> Result<i32, FlowError> {
+        if rc < 0 {
+            Err(FlowError::from_raw(call, port_id, Some(rc), error, pattern, actions))
+        } else {
+            Ok(rc)
+        }
+    }
+
+    /// Returns the negative errno reported by DPDK, if any.
+    pub fn errno(&self) -> Option<i32> {
+        match self {
+            FlowError::Dpdk { errno, .. } => *errno,
+        }
+    }
+
+    /// Maps the error to `std::io::ErrorKind`, errors without an errno map to `InvalidInput`.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self.errno() {
+            Some(errno) => io::Error::from_raw_os_error(-errno).kind(),
+            None => io::ErrorKind::InvalidInput,
+        }
+    }
+}
+
+impl fmt::Display for FlowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlowError::Dpdk { call, port_id, errno, error_type, cause, message } => {
+                write!(f, "port {port_id}: {call} failed")?;
+                if let Some(errno) = errno {
+                    write!(f, ": {}", io::Error::from_raw_os_error(-errno))?;
+                }
+                write!(f, ": {error_type}")?;
+                match cause {
+                    Some(index) if error_type.is_item() => write!(f, " of item {index}")?,
+                    Some(index) => write!(f, " of action {index}")?,
+                    None => (),
+                }
+                match message {
+                    Some(message) => write!(f, ": {message}"),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for FlowError {}
+
+impl From<FlowError> for io::Error {
+    fn from(err: FlowError) -> Self {
+        io::Error::new(err.kind(), err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use crate::dpdk_raw::rte_ethdev::rte_flow_item_type_RTE_FLOW_ITEM_TYPE_END;
+
+    #[test]
+    fn from_raw_decodes_type_cause_and_message() {
+        let spec = 7u32;
+        let item = |spec: *const c_void| rte_flow_item {
+            type_: rte_flow_item_type_RTE_FLOW_ITEM_TYPE_END,
+            spec,
+            last: std::ptr::null(),
+            mask: std::ptr::null(),
+        };
+        let pattern = [item(std::ptr::null()), item(&spec as *const u32 as *const c_void), item(std::ptr::null())];
+        let message = CString::new("mask enables unsupported bits").unwrap();
+        let mut error = rte_flow_error {
+            type_: rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM,
+            cause: &pattern[2] as *const rte_flow_item as *const c_void,
+            message: message.as_ptr(),
+        };
+
+        let err = FlowError::from_raw("rte_flow_validate", 0, Some(-95), &error, &pattern, &[]);
+        let FlowError::Dpdk { error_type, cause, .. } = &err;
+        assert_eq!((*error_type, *cause), (FlowErrorType::Item, Some(2)));
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "port 0: rte_flow_validate failed: {}: specific pattern item of item 2: mask enables unsupported bits",
+                io::Error::from_raw_os_error(95)
+            )
+        );
+
+        error.type_ = rte_flow_error_type_RTE_FLOW_ERROR_TYPE_ITEM_SPEC;
+        error.cause = &spec as *const u32 as *const c_void;
+        error.message = std::ptr::null();
+        let err = FlowError::from_raw("rte_flow_create", 1, None, &error, &pattern, &[]);
+        assert_eq!(err.to_string(), "port 1: rte_flow_create failed: item specification of item 1");
+    }
+}
@@ -0,0 +1,338 @@
+pub mod error;
+pub mod item;
+pub mod action;
+
+use std::any::Any;
+use std::ffi::c_void;
+use std::mem::ManuallyDrop;
+use std::ptr::NonNull;
+use crate::flow::error::FlowError;
+use crate::flow::item::{raw_end_item, FlowItem};
+use crate::flow::action::FlowAction;
+use crate::dpdk_raw::rte_ethdev::{
+    rte_flow,
+    rte_flow_attr,
+    rte_flow_item,
+    rte_flow_action,
+    rte_flow_error,
+    rte_flow_validate,
+    rte_flow_create,
+    rte_flow_destroy,
+    rte_flow_flush,
+    rust_get_rte_errno,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_END,
+    rte_flow_action_type_RTE_FLOW_ACTION_TYPE_END,
+};
+
+/// Flow rule attributes.
+///
+/// At least one of `ingress`, `egress` and `transfer` must be set. Rules in groups
+/// other than 0 are reached with a [`FlowAction::Jump`], a lower `priority` value
+/// takes precedence within a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FlowAttr {
+    pub group: u32,
+    pub priority: u32,
+    pub ingress: bool,
+    pub egress: bool,
+    /// Rule applies to the embedded switch the port belongs to.
+    pub transfer: bool,
+}
+
+impl FlowAttr {
+    pub fn ingress() -> Self {
+        FlowAttr { ingress: true, ..Default::default() }
+    }
+
+    pub fn egress() -> Self {
+        FlowAttr { egress: true, ..Default::default() }
+    }
+
+    pub fn transfer() -> Self {
+        FlowAttr { transfer: true, ..Default::default() }
+    }
+
+    pub fn group(mut self, group: u32) -> Self {
+        self.group = group;
+        self
+    }
+
+    pub fn priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn to_raw(&self) -> rte_flow_attr {
+        // plain C structure, all-zero is a valid value
+        let mut raw: rte_flow_attr = unsafe { std::mem::zeroed() };
+        raw.group = self.group;
+        raw.priority = self.priority;
+        raw.set_ingress(self.ingress as u32);
+        raw.set_egress(self.egress as u32);
+        raw.set_transfer(self.transfer as u32);
+        raw
+    }
+}
+
+// Heap copies of the item specs / masks and action configurations a raw rule points to.
+// Boxed values do not move when the box is, so the pointers stay valid while the
+// memory is alive.
+#[derive(Default)]
+pub(crate) struct RuleMemory(Vec<Box<dyn Any>>);
+
+impl RuleMemory {
+    pub(crate) fn keep<T: 'static>(&mut self, value: T) -> *const c_void {
+        let value = Box::new(value);
+        let ptr = &*value as *const T as *const c_void;
+        self.0.push(value);
+        ptr
+    }
+
+    pub(crate) fn keep_slice<T: Clone + 'static>(&mut self, values: &[T]) -> *const T {
+        let values: Box<[T]> = values.into();
+        let ptr = values.as_ptr();
+        self.0.push(Box::new(values));
+        ptr
+    }
+}
+
+// Raw form of a flow rule, END terminated item and action arrays together with the
+// memory they point to.
+pub(crate) struct RawFlowRule {
+    pub(crate) attr: rte_flow_attr,
+    pub(crate) pattern: Vec<rte_flow_item>,
+    pub(crate) actions: Vec<rte_flow_action>,
+    _memory: RuleMemory,
+}
+
+impl RawFlowRule {
+    pub(crate) fn new(rule: &FlowRule) -> Self {
+        let mut memory = RuleMemory::default();
+        let mut pattern = rule.pattern.iter().map(|item| item.to_raw(&mut memory)).collect::<Vec<_>>();
+        pattern.push(raw_end_item(rte_flow_item_type_RTE_FLOW_ITEM_TYPE_END));
+        let mut actions = rule.actions.iter().map(|action| action.to_raw(&mut memory)).collect::<Vec<_>>();
+        actions.push(rte_flow_action { type_: rte_flow_action_type_RTE_FLOW_ACTION_TYPE_END, conf: std::ptr::null() });
+        RawFlowRule { attr: rule.attr.to_raw(), pattern, actions, _memory: memory }
+    }
+}
+
+/// Flow rule, attributes, pattern and actions.
+///
+/// # Overview
+///
+/// `FlowRule` is plain Rust data. [`FlowRule::validate`] and [`FlowRule::create`] convert
+/// it to the raw `rte_flow_attr`, `rte_flow_item` and `rte_flow_action` arrays, END
+/// terminated, and keep every spec, mask and action configuration alive until the
+/// DPDK call returns.
+///
+/// # Example
+///
+/// ```
+/// let rule = FlowRule::new(FlowAttr::ingress())
+///     .item(FlowItem::Eth(Masked::default()))
+///     .item(FlowItem::Ipv4(Masked::<Ipv4>::default().dst("10.0.0.0".parse()?, 8)))
+///     .item(FlowItem::Udp(Masked::<Udp>::default().dst_port(4789)))
+///     .action(FlowAction::Count(0))
+///     .action(FlowAction::Queue(3));
+/// rule.validate(port_id)?;
+/// let flow = rule.create(port_id)?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FlowRule {
+    pub attr: FlowAttr,
+    pub pattern: Vec<FlowItem>,
+    pub actions: Vec<FlowAction>,
+}
+
+impl FlowRule {
+    pub fn new(attr: FlowAttr) -> Self {
+        FlowRule { attr, pattern: Vec::new(), actions: Vec::new() }
+    }
+
+    /// Appends a pattern item.
+    pub fn item(mut self, item: FlowItem) -> Self {
+        self.pattern.push(item);
+        self
+    }
+
+    /// Appends an action.
+    pub fn action(mut self, action: FlowAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Checks the rule against the port with `rte_flow_validate`, the rule is not created.
+    pub fn validate(&self, port_id: u16) -> Result<(), FlowError> {
+        let raw = RawFlowRule::new(self);
+        let mut error: rte_flow_error = unsafe { std::mem::zeroed() };
+        let rc = unsafe {
+            rte_flow_validate(port_id, &raw.attr, raw.pattern.as_ptr(), raw.actions.as_ptr(), &mut error)
+        };
+        FlowError::check_rule(rc, "rte_flow_validate", port_id, &error, &raw.pattern, &raw.actions).map(|_| ())
+    }
+
+    /// Creates the rule on the port with `rte_flow_create`.
+    pub fn create(&self, port_id: u16) -> Result<Flow, FlowError> {
+        let raw = RawFlowRule::new(self);
+        let mut error: rte_flow_error = unsafe { std::mem::zeroed() };
+        let flow = unsafe {
+            rte_flow_create(port_id, &raw.attr, raw.pattern.as_ptr(), raw.actions.as_ptr(), &mut error)
+        };
+        match NonNull::new(flow) {
+            Some(raw) => Ok(Flow { port_id, raw, rule: Some(self.clone()) }),
+            None => {
+                let errno = -unsafe { rust_get_rte_errno() };
+                Err(FlowError::from_raw("rte_flow_create", port_id, Some(errno), &error, &raw.pattern, &raw.actions))
+            }
+        }
+    }
+}
+
+/// Flow rule created on a port.
+///
+/// The rule is destroyed with `rte_flow_destroy` when the handle is dropped or
+/// [`Flow::destroy`] is called. Rules removed with [`flush`] or by closing the port
+/// must be released with [`Flow::into_raw`].
+pub struct Flow {
+    port_id: u16,
+    raw: NonNull<rte_flow>,
+    rule: Option<FlowRule>,
+}
+
+// rte_flow handles are not tied to the lcore that created them.
+unsafe impl Send for Flow {}
+
+impl Flow {
+    /// Takes ownership of a rule created without [`FlowRule::create`], the handle carries
+    /// no [`FlowRule`].
+    ///
+    /// # Safety
+    ///
+    /// `raw` must be a live rule of `port_id` that no other handle owns.
+    pub unsafe fn from_raw(port_id: u16, raw: NonNull<rte_flow>) -> Self {
+        Flow { port_id, raw, rule: None }
+    }
+
+    pub fn port_id(&self) -> u16 {
+        self.port_id
+    }
+
+    /// Rule the flow was created from, `None` for handles made with [`Flow::from_raw`].
+    pub fn rule(&self) -> Option<&FlowRule> {
+        self.rule.as_ref()
+    }
+
+    pub fn as_raw(&self) -> *mut rte_flow {
+        self.raw.as_ptr()
+    }
+
+    /// Releases the handle without destroying the rule.
+    pub fn into_raw(self) -> NonNull<rte_flow> {
+        let flow = ManuallyDrop::new(self);
+        drop(unsafe { std::ptr::read(&flow.rule) });
+        flow.raw
+    }
+
+    /// Destroys the rule, the handle is released even if `rte_flow_destroy` fails.
+    pub fn destroy(self) -> Result<(), FlowError> {
+        let result = self.destroy_raw();
+        self.into_raw();
+        result
+    }
+
+    fn destroy_raw(&self) -> Result<(), FlowError> {
+        let mut error: rte_flow_error = unsafe { std::mem::zeroed() };
+        let rc = unsafe { rte_flow_destroy(self.port_id, self.raw.as_ptr(), &mut error) };
+        FlowError::check(rc, "rte_flow_destroy", self.port_id, &error).map(|_| ())
+    }
+}
+
+impl Drop for Flow {
+    fn drop(&mut self) {
+        let _ = self.destroy_raw();
+    }
+}
+
+/// Destroys all flow rules of a port with `rte_flow_flush`.
+pub fn flush(port_id: u16) -> Result<(), FlowError> {
+    let mut error: rte_flow_error = unsafe { std::mem::zeroed() };
+    let rc = unsafe { rte_flow_flush(port_id, &mut error) };
+    FlowError::check(rc, "rte_flow_flush", port_id, &error).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flow::action::FlowRss;
+    use crate::flow::item::{Ipv4, Ipv6, Masked, Udp, Vlan};
+    use crate::port::rss::RssHashFunction;
+    use crate::dpdk_raw::rte_ethdev::{
+        rte_flow_item_ipv4,
+        rte_flow_item_ipv6,
+        rte_flow_item_udp,
+        rte_flow_item_vlan,
+        rte_flow_action_queue,
+        rte_flow_action_rss,
+        rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ETH,
+        rte_flow_action_type_RTE_FLOW_ACTION_TYPE_QUEUE,
+    };
+
+    unsafe fn conf<T>(ptr: *const c_void) -> &'static T {
+        unsafe { &*(ptr as *const T) }
+    }
+
+    #[test]
+    fn raw_rule_is_end_terminated_in_network_order() {
+        let rule = FlowRule::new(FlowAttr::ingress().group(2).priority(1))
+            .item(FlowItem::Eth(Masked::default()))
+            .item(FlowItem::Ipv4(Masked::<Ipv4>::default().dst("10.1.0.0".parse().unwrap(), 16)))
+            .item(FlowItem::Udp(Masked::<Udp>::default().dst_port(4789)))
+            .action(FlowAction::Queue(3));
+        let raw = RawFlowRule::new(&rule);
+
+        assert_eq!((raw.attr.group, raw.attr.priority), (2, 1));
+        assert_eq!((raw.attr.ingress(), raw.attr.egress(), raw.attr.transfer()), (1, 0, 0));
+
+        let types = raw.pattern.iter().map(|item| item.type_).collect::<Vec<_>>();
+        assert_eq!(types[0], rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ETH);
+        assert_eq!(types[3], rte_flow_item_type_RTE_FLOW_ITEM_TYPE_END);
+        assert!(raw.pattern[3].spec.is_null() && raw.pattern[3].mask.is_null());
+
+        let (spec, mask) = unsafe {
+            (conf::<rte_flow_item_ipv4>(raw.pattern[1].spec), conf::<rte_flow_item_ipv4>(raw.pattern[1].mask))
+        };
+        assert_eq!(spec.hdr.dst_addr.to_ne_bytes(), [10, 1, 0, 0]);
+        assert_eq!(mask.hdr.dst_addr.to_ne_bytes(), [255, 255, 0, 0]);
+        assert_eq!({ mask.hdr.src_addr }, 0);
+        let udp = unsafe { conf::<rte_flow_item_udp>(raw.pattern[2].spec) };
+        assert_eq!(udp.hdr.dst_port.to_ne_bytes(), 4789u16.to_be_bytes());
+
+        assert_eq!(raw.actions.len(), 2);
+        assert_eq!(raw.actions[0].type_, rte_flow_action_type_RTE_FLOW_ACTION_TYPE_QUEUE);
+        assert_eq!(unsafe { conf::<rte_flow_action_queue>(raw.actions[0].conf) }.index, 3);
+        assert_eq!(raw.actions[1].type_, rte_flow_action_type_RTE_FLOW_ACTION_TYPE_END);
+    }
+
+    #[test]
+    fn raw_rule_packs_header_bitfields() {
+        let vlan = Masked::<Vlan>::default().vid(0x123).pcp(5);
+        assert_eq!((vlan.spec.tci, vlan.mask.tci), (0xa123, 0xefff));
+        let ipv6 = Masked::<Ipv6>::default().traffic_class(0x12).flow_label(0x3_4567);
+        let rss = FlowRss { func: RssHashFunction::Toeplitz, queues: vec![0, 1, 2], ..Default::default() };
+        let rule = FlowRule::new(FlowAttr::ingress())
+            .item(FlowItem::Vlan(vlan))
+            .item(FlowItem::Ipv6(ipv6))
+            .action(FlowAction::Rss(rss));
+        let raw = RawFlowRule::new(&rule);
+
+        let vlan = unsafe { conf::<rte_flow_item_vlan>(raw.pattern[0].spec) };
+        assert_eq!(unsafe { vlan.__bindgen_anon_1.hdr.vlan_tci }.to_ne_bytes(), [0xa1, 0x23]);
+        let ipv6 = unsafe { conf::<rte_flow_item_ipv6>(raw.pattern[1].spec) };
+        assert_eq!(unsafe { ipv6.hdr.__bindgen_anon_1.vtc_flow }.to_ne_bytes(), [0x01, 0x23, 0x45, 0x67]);
+
+        let rss = unsafe { conf::<rte_flow_action_rss>(raw.actions[0].conf) };
+        assert_eq!((rss.queue_num, rss.key_len), (3, 0));
+        assert!(rss.key.is_null());
+        assert_eq!(unsafe { std::slice::from_raw_parts(rss.queue, 3) }, [0, 1, 2]);
+    }
+}
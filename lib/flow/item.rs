@@ -0,0 +1,546 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+use crate::flow::RuleMemory;
+use crate::port::ether::EtherAddr;
+use crate::dpdk_raw::rte_ethdev::{
+    rte_flow_item,
+    rte_flow_item_type,
+    rte_flow_item_eth,
+    rte_flow_item_vlan,
+    rte_flow_item_ipv4,
+    rte_flow_item_ipv6,
+    rte_flow_item_icmp,
+    rte_flow_item_icmp6,
+    rte_flow_item_udp,
+    rte_flow_item_tcp,
+    rte_ether_hdr,
+    rte_vlan_hdr,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_VOID,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ETH,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_VLAN,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_IPV4,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_IPV6,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ICMP,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ICMP6,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_UDP,
+    rte_flow_item_type_RTE_FLOW_ITEM_TYPE_TCP,
+};
+
+/// Pattern item header and its mask, only the bits set in `mask` are matched.
+///
+/// Builder methods set a field in `spec` and all of its bits in `mask`, so an item
+/// built with no method calls matches any header of its type. Change `mask` directly
+/// to match a part of a field.
+///
+/// # Example
+///
+/// ```
+/// let ipv4 = Masked::<Ipv4>::default().dst("192.168.0.0".parse()?, 16).proto(17);
+/// let udp = Masked::<Udp>::default().dst_port(4789);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Masked<T> {
+    pub spec: T,
+    pub mask: T,
+}
+
+/// Ethernet header, `RTE_FLOW_ITEM_TYPE_ETH`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Eth {
+    pub dst: EtherAddr,
+    pub src: EtherAddr,
+    /// EtherType, or the TPID if the item is followed by a VLAN item.
+    pub ether_type: u16,
+    /// Packet has at least one VLAN tag.
+    pub has_vlan: bool,
+}
+
+impl Masked<Eth> {
+    pub fn dst(mut self, addr: EtherAddr) -> Self {
+        self.spec.dst = addr;
+        self.mask.dst = EtherAddr::BROADCAST;
+        self
+    }
+
+    pub fn src(mut self, addr: EtherAddr) -> Self {
+        self.spec.src = addr;
+        self.mask.src = EtherAddr::BROADCAST;
+        self
+    }
+
+    pub fn ether_type(mut self, ether_type: u16) -> Self {
+        self.spec.ether_type = ether_type;
+        self.mask.ether_type = u16::MAX;
+        self
+    }
+
+    pub fn has_vlan(mut self, has_vlan: bool) -> Self {
+        self.spec.has_vlan = has_vlan;
+        self.mask.has_vlan = true;
+        self
+    }
+}
+
+/// 802.1Q / 802.1ad VLAN tag, `RTE_FLOW_ITEM_TYPE_VLAN`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Vlan {
+    /// Tag control information, PCP (3 bits), DEI (1 bit) and VID (12 bits).
+    pub tci: u16,
+    /// EtherType, or the TPID of the inner tag.
+    pub inner_type: u16,
+    /// Another VLAN tag follows.
+    pub has_more_vlan: bool,
+}
+
+const VLAN_VID_MASK: u16 = 0x0fff;
+const VLAN_DEI_MASK: u16 = 0x1000;
+const VLAN_PCP_MASK: u16 = 0xe000;
+
+impl Masked<Vlan> {
+    // Sets the `field` bits of the TCI.
+    fn tci_bits(mut self, field: u16, value: u16) -> Self {
+        let shift = field.trailing_zeros();
+        self.spec.tci = (self.spec.tci & !field) | ((value << shift) & field);
+        self.mask.tci |= field;
+        self
+    }
+
+    pub fn tci(mut self, tci: u16) -> Self {
+        self.spec.tci = tci;
+        self.mask.tci = u16::MAX;
+        self
+    }
+
+    /// VLAN ID, the lower 12 bits of `vid` are used.
+    pub fn vid(self, vid: u16) -> Self {
+        self.tci_bits(VLAN_VID_MASK, vid)
+    }
+
+    /// Priority code point, the lower 3 bits of `pcp` are used.
+    pub fn pcp(self, pcp: u8) -> Self {
+        self.tci_bits(VLAN_PCP_MASK, pcp as u16)
+    }
+
+    pub fn dei(self, dei: bool) -> Self {
+        self.tci_bits(VLAN_DEI_MASK, dei as u16)
+    }
+
+    pub fn inner_type(mut self, inner_type: u16) -> Self {
+        self.spec.inner_type = inner_type;
+        self.mask.inner_type = u16::MAX;
+        self
+    }
+
+    pub fn has_more_vlan(mut self, has_more_vlan: bool) -> Self {
+        self.spec.has_more_vlan = has_more_vlan;
+        self.mask.has_more_vlan = true;
+        self
+    }
+}
+
+/// IPv4 header, `RTE_FLOW_ITEM_TYPE_IPV4`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ipv4 {
+    pub src: Ipv4Addr,
+    pub dst: Ipv4Addr,
+    pub tos: u8,
+    pub ttl: u8,
+    pub proto: u8,
+}
+
+impl Default for Ipv4 {
+    fn default() -> Self {
+        Ipv4 { src: Ipv4Addr::UNSPECIFIED, dst: Ipv4Addr::UNSPECIFIED, tos: 0, ttl: 0, proto: 0 }
+    }
+}
+
+/// Address mask of the first `prefix` bits, longer prefixes are clamped to 32.
+pub fn ipv4_prefix_mask(prefix: u8) -> Ipv4Addr {
+    Ipv4Addr::from(u32::MAX.checked_shl(32 - prefix.min(32) as u32).unwrap_or(0))
+}
+
+impl Masked<Ipv4> {
+    /// Source address, the first `prefix` bits are matched.
+    pub fn src(mut self, addr: Ipv4Addr, prefix: u8) -> Self {
+        self.spec.src = addr;
+        self.mask.src = ipv4_prefix_mask(prefix);
+        self
+    }
+
+    /// Destination address, the first `prefix` bits are matched.
+    pub fn dst(mut self, addr: Ipv4Addr, prefix: u8) -> Self {
+        self.spec.dst = addr;
+        self.mask.dst = ipv4_prefix_mask(prefix);
+        self
+    }
+
+    pub fn tos(mut self, tos: u8) -> Self {
+        self.spec.tos = tos;
+        self.mask.tos = u8::MAX;
+        self
+    }
+
+    pub fn ttl(mut self, ttl: u8) -> Self {
+        self.spec.ttl = ttl;
+        self.mask.ttl = u8::MAX;
+        self
+    }
+
+    pub fn proto(mut self, proto: u8) -> Self {
+        self.spec.proto = proto;
+        self.mask.proto = u8::MAX;
+        self
+    }
+}
+
+/// IPv6 header, `RTE_FLOW_ITEM_TYPE_IPV6`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ipv6 {
+    pub src: Ipv6Addr,
+    pub dst: Ipv6Addr,
+    pub traffic_class: u8,
+    /// Flow label, 20 bits.
+    pub flow_label: u32,
+    /// Next header.
+    pub proto: u8,
+    pub hop_limit: u8,
+}
+
+impl Default for Ipv6 {
+    fn default() -> Self {
+        Ipv6 {
+            src: Ipv6Addr::UNSPECIFIED,
+            dst: Ipv6Addr::UNSPECIFIED,
+            traffic_class: 0,
+            flow_label: 0,
+            proto: 0,
+            hop_limit: 0,
+        }
+    }
+}
+
+const IPV6_FLOW_LABEL_MASK: u32 = 0x000f_ffff;
+
+/// Address mask of the first `prefix` bits, longer prefixes are clamped to 128.
+pub fn ipv6_prefix_mask(prefix: u8) -> Ipv6Addr {
+    Ipv6Addr::from(u128::MAX.checked_shl(128 - prefix.min(128) as u32).unwrap_or(0))
+}
+
+impl Masked<Ipv6> {
+    /// Source address, the first `prefix` bits are matched.
+    pub fn src(mut self, addr: Ipv6Addr, prefix: u8) -> Self {
+        self.spec.src = addr;
+        self.mask.src = ipv6_prefix_mask(prefix);
+        self
+    }
+
+    /// Destination address, the first `prefix` bits are matched.
+    pub fn dst(mut self, addr: Ipv6Addr, prefix: u8) -> Self {
+        self.spec.dst = addr;
+        self.mask.dst = ipv6_prefix_mask(prefix);
+        self
+    }
+
+    pub fn traffic_class(mut self, traffic_class: u8) -> Self {
+        self.spec.traffic_class = traffic_class;
+        self.mask.traffic_class = u8::MAX;
+        self
+    }
+
+    /// Flow label, the lower 20 bits of `flow_label` are used.
+    pub fn flow_label(mut self, flow_label: u32) -> Self {
+        self.spec.flow_label = flow_label & IPV6_FLOW_LABEL_MASK;
+        self.mask.flow_label = IPV6_FLOW_LABEL_MASK;
+        self
+    }
+
+    pub fn proto(mut self, proto: u8) -> Self {
+        self.spec.proto = proto;
+        self.mask.proto = u8::MAX;
+        self
+    }
+
+    pub fn hop_limit(mut self, hop_limit: u8) -> Self {
+        self.spec.hop_limit = hop_limit;
+        self.mask.hop_limit = u8::MAX;
+        self
+    }
+}
+
+/// ICMP or ICMPv6 header, `RTE_FLOW_ITEM_TYPE_ICMP` / `RTE_FLOW_ITEM_TYPE_ICMP6`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Icmp {
+    pub icmp_type: u8,
+    pub code: u8,
+}
+
+impl Masked<Icmp> {
+    pub fn icmp_type(mut self, icmp_type: u8) -> Self {
+        self.spec.icmp_type = icmp_type;
+        self.mask.icmp_type = u8::MAX;
+        self
+    }
+
+    pub fn code(mut self, code: u8) -> Self {
+        self.spec.code = code;
+        self.mask.code = u8::MAX;
+        self
+    }
+}
+
+/// UDP header, `RTE_FLOW_ITEM_TYPE_UDP`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Udp {
+    pub src_port: u16,
+    pub dst_port: u16,
+}
+
+impl Masked<Udp> {
+    pub fn src_port(mut self, port: u16) -> Self {
+        self.spec.src_port = port;
+        self.mask.src_port = u16::MAX;
+        self
+    }
+
+    pub fn dst_port(mut self, port: u16) -> Self {
+        self.spec.dst_port = port;
+        self.mask.dst_port = u16::MAX;
+        self
+    }
+}
+
+/// TCP header, `RTE_FLOW_ITEM_TYPE_TCP`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tcp {
+    pub src_port: u16,
+    pub dst_port: u16,
+    /// TCP flags, `RTE_TCP_*_FLAG`.
+    pub flags: u8,
+}
+
+impl Masked<Tcp> {
+    pub fn src_port(mut self, port: u16) -> Self {
+        self.spec.src_port = port;
+        self.mask.src_port = u16::MAX;
+        self
+    }
+
+    pub fn dst_port(mut self, port: u16) -> Self {
+        self.spec.dst_port = port;
+        self.mask.dst_port = u16::MAX;
+        self
+    }
+
+    /// Matches the `mask` bits of the TCP flags against `flags`.
+    pub fn flags(mut self, flags: u8, mask: u8) -> Self {
+        self.spec.flags = flags & mask;
+        self.mask.flags = mask;
+        self
+    }
+}
+
+/// Flow rule pattern item.
+///
+/// Items are matched in packet order, from the outermost header. The terminating
+/// `END` item is added when the rule is converted to its raw form.
+///
+/// # Example
+///
+/// ```
+/// let pattern = [
+///     FlowItem::Eth(Masked::default()),
+///     FlowItem::Ipv4(Masked::<Ipv4>::default().proto(6)),
+///     FlowItem::Tcp(Masked::<Tcp>::default().dst_port(80)),
+/// ];
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowItem {
+    /// Ignored item.
+    Void,
+    Eth(Masked<Eth>),
+    Vlan(Masked<Vlan>),
+    Ipv4(Masked<Ipv4>),
+    Ipv6(Masked<Ipv6>),
+    Icmp(Masked<Icmp>),
+    Icmp6(Masked<Icmp>),
+    Udp(Masked<Udp>),
+    Tcp(Masked<Tcp>),
+}
+
+// Item header converted to its `rte_flow_item_*` structure, in network byte order.
+trait RawHeader {
+    type Raw: 'static;
+    fn to_raw(&self) -> Self::Raw;
+}
+
+// Bindgen structures below are plain C data, all-zero is a valid value.
+fn zeroed<T>() -> T {
+    unsafe { std::mem::zeroed() }
+}
+
+impl RawHeader for Eth {
+    type Raw = rte_flow_item_eth;
+
+    fn to_raw(&self) -> rte_flow_item_eth {
+        let mut raw: rte_flow_item_eth = zeroed();
+        raw.__bindgen_anon_1.hdr = rte_ether_hdr {
+            dst_addr: self.dst.to_raw(),
+            src_addr: self.src.to_raw(),
+            ether_type: self.ether_type.to_be(),
+        };
+        raw.set_has_vlan(self.has_vlan as u32);
+        raw
+    }
+}
+
+impl RawHeader for Vlan {
+    type Raw = rte_flow_item_vlan;
+
+    fn to_raw(&self) -> rte_flow_item_vlan {
+        let mut raw: rte_flow_item_vlan = zeroed();
+        raw.__bindgen_anon_1.hdr =
+            rte_vlan_hdr { vlan_tci: self.tci.to_be(), eth_proto: self.inner_type.to_be() };
+        raw.set_has_more_vlan(self.has_more_vlan as u32);
+        raw
+    }
+}
+
+impl RawHeader for Ipv4 {
+    type Raw = rte_flow_item_ipv4;
+
+    fn to_raw(&self) -> rte_flow_item_ipv4 {
+        let mut raw: rte_flow_item_ipv4 = zeroed();
+        raw.hdr.src_addr = u32::from(self.src).to_be();
+        raw.hdr.dst_addr = u32::from(self.dst).to_be();
+        raw.hdr.type_of_service = self.tos;
+        raw.hdr.time_to_live = self.ttl;
+        raw.hdr.next_proto_id = self.proto;
+        raw
+    }
+}
+
+impl RawHeader for Ipv6 {
+    type Raw = rte_flow_item_ipv6;
+
+    fn to_raw(&self) -> rte_flow_item_ipv6 {
+        let mut raw: rte_flow_item_ipv6 = zeroed();
+        let vtc_flow = (self.traffic_class as u32) << 20 | (self.flow_label & IPV6_FLOW_LABEL_MASK);
+        raw.hdr.__bindgen_anon_1.vtc_flow = vtc_flow.to_be();
+        raw.hdr.src_addr.a = self.src.octets();
+        raw.hdr.dst_addr.a = self.dst.octets();
+        raw.hdr.proto = self.proto;
+        raw.hdr.hop_limits = self.hop_limit;
+        raw
+    }
+}
+
+impl RawHeader for Icmp {
+    type Raw = rte_flow_item_icmp;
+
+    fn to_raw(&self) -> rte_flow_item_icmp {
+        let mut raw: rte_flow_item_icmp = zeroed();
+        raw.hdr.icmp_type = self.icmp_type;
+        raw.hdr.icmp_code = self.code;
+        raw
+    }
+}
+
+// ICMPv6 has its own item structure with the same fields.
+struct Icmp6(Icmp);
+
+impl RawHeader for Icmp6 {
+    type Raw = rte_flow_item_icmp6;
+
+    fn to_raw(&self) -> rte_flow_item_icmp6 {
+        rte_flow_item_icmp6 { type_: self.0.icmp_type, code: self.0.code, checksum: 0 }
+    }
+}
+
+impl RawHeader for Udp {
+    type Raw = rte_flow_item_udp;
+
+    fn to_raw(&self) -> rte_flow_item_udp {
+        let mut raw: rte_flow_item_udp = zeroed();
+        raw.hdr.src_port = self.src_port.to_be();
+        raw.hdr.dst_port = self.dst_port.to_be();
+        raw
+    }
+}
+
+impl RawHeader for Tcp {
+    type Raw = rte_flow_item_tcp;
+
+    fn to_raw(&self) -> rte_flow_item_tcp {
+        let mut raw: rte_flow_item_tcp = zeroed();
+        raw.hdr.src_port = self.src_port.to_be();
+        raw.hdr.dst_port = self.dst_port.to_be();
+        raw.hdr.tcp_flags = self.flags;
+        raw
+    }
+}
+
+fn raw_item<H: RawHeader>(
+    type_: rte_flow_item_type,
+    spec: &H,
+    mask: &H,
+    memory: &mut RuleMemory,
+) -> rte_flow_item {
+    rte_flow_item {
+        type_,
+        spec: memory.keep(spec.to_raw()),
+        last: std::ptr::null(),
+        mask: memory.keep(mask.to_raw()),
+    }
+}
+
+impl FlowItem {
+    /// testpmd name of the item.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FlowItem::Void => "void",
+            FlowItem::Eth(_) => "eth",
+            FlowItem::Vlan(_) => "vlan",
+            FlowItem::Ipv4(_) => "ipv4",
+            FlowItem::Ipv6(_) => "ipv6",
+            FlowItem::Icmp(_) => "icmp",
+            FlowItem::Icmp6(_) => "icmp6",
+            FlowItem::Udp(_) => "udp",
+            FlowItem::Tcp(_) => "tcp",
+        }
+    }
+
+    /// Item type, `RTE_FLOW_ITEM_TYPE_*`.
+    pub fn item_type(&self) -> rte_flow_item_type {
+        match self {
+            FlowItem::Void => rte_flow_item_type_RTE_FLOW_ITEM_TYPE_VOID,
+            FlowItem::Eth(_) => rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ETH,
+            FlowItem::Vlan(_) => rte_flow_item_type_RTE_FLOW_ITEM_TYPE_VLAN,
+            FlowItem::Ipv4(_) => rte_flow_item_type_RTE_FLOW_ITEM_TYPE_IPV4,
+            FlowItem::Ipv6(_) => rte_flow_item_type_RTE_FLOW_ITEM_TYPE_IPV6,
+            FlowItem::Icmp(_) => rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ICMP,
+            FlowItem::Icmp6(_) => rte_flow_item_type_RTE_FLOW_ITEM_TYPE_ICMP6,
+            FlowItem::Udp(_) => rte_flow_item_type_RTE_FLOW_ITEM_TYPE_UDP,
+            FlowItem::Tcp(_) => rte_flow_item_type_RTE_FLOW_ITEM_TYPE_TCP,
+        }
+    }
+
+    // Raw item, spec and mask are kept in `memory`.
+    pub(crate) fn to_raw(&self, memory: &mut RuleMemory) -> rte_flow_item {
+        let type_ = self.item_type();
+        match self {
+            FlowItem::Void => raw_end_item(type_),
+            FlowItem::Eth(item) => raw_item(type_, &item.spec, &item.mask, memory),
+            FlowItem::Vlan(item) => raw_item(type_, &item.spec, &item.mask, memory),
+            FlowItem::Ipv4(item) => raw_item(type_, &item.spec, &item.mask, memory),
+            FlowItem::Ipv6(item) => raw_item(type_, &item.spec, &item.mask, memory),
+            FlowItem::Icmp(item) => raw_item(type_, &item.spec, &item.mask, memory),
+            FlowItem::Icmp6(item) => raw_item(type_, &Icmp6(item.spec), &Icmp6(item.mask), memory),
+            FlowItem::Udp(item) => raw_item(type_, &item.spec, &item.mask, memory),
+            FlowItem::Tcp(item) => raw_item(type_, &item.spec, &item.mask, memory),
+        }
+    }
+}
+
+// Item without spec, mask and last, `END` terminates the pattern.
+pub(crate) fn raw_end_item(type_: rte_flow_item_type) -> rte_flow_item {
+    rte_flow_item { type_, spec: std::ptr::null(), last: std::ptr::null(), mask: std::ptr::null() }
+}
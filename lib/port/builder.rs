@@ -0,0 +1,489 @@
+use std::sync::Arc;
+use crate::port::DpdkPortConf;
+use crate::port::mempool::Mempool;
+use crate::port::error::PortError;
+use crate::port::rss::RssConfig;
+use crate::port::vlan::{self, VlanConfig};
+use crate::port::offload::{RxOffloads, TxOffloads};
+use crate::dpdk_raw::rte_ethdev::{
+    rte_eth_conf,
+    rte_eth_txconf,
+    rte_eth_rxconf,
+    rte_eth_dev_info,
+    rte_eth_desc_lim,
+    rte_eth_dev_info_get,
+    rte_eth_dev_adjust_nb_rx_tx_desc,
+};
+
+/// Builder for [`DpdkPortConf`].
+///
+/// # Overview
+///
+/// Collects port settings with named setters and validates them against the device
+/// capabilities in `rte_eth_dev_info` when [`DpdkPortConfBuilder::build`] is called:
+/// - Rx / Tx descriptor numbers are clamped to the device descriptor limits.
+/// - Rx / Tx offloads must be included in `rx_offload_capa` / `tx_offload_capa`, optional
+///   offloads are negotiated when the port is configured.
+/// - Queue numbers must not exceed `max_rx_queues` / `max_tx_queues`.
+/// - MTU must be in the `min_mtu` / `max_mtu` range.
+/// - RSS hash functions must be included in `flow_type_rss_offloads`, RSS key, algorithm
+///   and redirection table must match the device.
+/// - VLAN offloads must be supported and filter IDs valid.
+/// - Every Rx queue must have a memory pool.
+///
+/// # Example
+///
+/// ```
+/// let conf = DpdkPortConf::builder(port_id)
+///     .rx_queues(4)
+///     .tx_queues(4)
+///     .rx_descriptors(1024)
+///     .tx_descriptors(1024)
+///     .socket_id(socket_id)
+///     .rx_mempool(mempool)
+///     .build()?;
+/// ```
+pub struct DpdkPortConfBuilder {
+    port_id: u16,
+    dev_conf: rte_eth_conf,
+    tx_conf: rte_eth_txconf,
+    rx_conf: rte_eth_rxconf,
+    rxq_num: u16,
+    txq_num: u16,
+    rx_desc_num: u16,
+    tx_desc_num: u16,
+    rxq_socket_id: u32,
+    txq_socket_id: u32,
+    rxq_mempool: Option<Arc<Mempool>>,
+    rxq_mempools: Vec<Option<Arc<Mempool>>>,
+    rss: Option<RssConfig>,
+    optional_rx_offloads: RxOffloads,
+    optional_tx_offloads: TxOffloads,
+    vlan: Option<VlanConfig>,
+}
+
+impl DpdkPortConfBuilder {
+    pub const DEFAULT_QUEUES: u16 = 1;
+    pub const DEFAULT_DESCRIPTORS: u16 = 512;
+
+    pub fn new(port_id: u16) -> Self {
+        DpdkPortConfBuilder {
+            port_id,
+            dev_conf: unsafe { std::mem::zeroed() },
+            tx_conf: unsafe { std::mem::zeroed() },
+            rx_conf: unsafe { std::mem::zeroed() },
+            rxq_num: Self::DEFAULT_QUEUES,
+            txq_num: Self::DEFAULT_QUEUES,
+            rx_desc_num: Self::DEFAULT_DESCRIPTORS,
+            tx_desc_num: Self::DEFAULT_DESCRIPTORS,
+            rxq_socket_id: 0,
+            txq_socket_id: 0,
+            rxq_mempool: None,
+            rxq_mempools: Vec::new(),
+            rss: None,
+            optional_rx_offloads: RxOffloads::empty(),
+            optional_tx_offloads: TxOffloads::empty(),
+            vlan: None,
+        }
+    }
+
+    /// Sets the base device configuration. Setters called later override its fields.
+    pub fn dev_conf(mut self, dev_conf: rte_eth_conf) -> Self {
+        self.dev_conf = dev_conf;
+        self
+    }
+
+    /// Sets the configuration applied to every Rx queue.
+    pub fn rx_conf(mut self, rx_conf: rte_eth_rxconf) -> Self {
+        self.rx_conf = rx_conf;
+        self
+    }
+
+    /// Sets the configuration applied to every Tx queue.
+    pub fn tx_conf(mut self, tx_conf: rte_eth_txconf) -> Self {
+        self.tx_conf = tx_conf;
+        self
+    }
+
+    pub fn rx_queues(mut self, rxq_num: u16) -> Self {
+        self.rxq_num = rxq_num;
+        self
+    }
+
+    pub fn tx_queues(mut self, txq_num: u16) -> Self {
+        self.txq_num = txq_num;
+        self
+    }
+
+    pub fn rx_descriptors(mut self, rx_desc_num: u16) -> Self {
+        self.rx_desc_num = rx_desc_num;
+        self
+    }
+
+    pub fn tx_descriptors(mut self, tx_desc_num: u16) -> Self {
+        self.tx_desc_num = tx_desc_num;
+        self
+    }
+
+    /// Sets port-level Rx offloads, the device must support all of them.
+    pub fn rx_offloads(mut self, offloads: RxOffloads) -> Self {
+        self.dev_conf.rxmode.offloads = offloads.bits();
+        self
+    }
+
+    /// Sets port-level Tx offloads, the device must support all of them.
+    pub fn tx_offloads(mut self, offloads: TxOffloads) -> Self {
+        self.dev_conf.txmode.offloads = offloads.bits();
+        self
+    }
+
+    /// Sets Rx offloads enabled only if the device supports them.
+    pub fn optional_rx_offloads(mut self, offloads: RxOffloads) -> Self {
+        self.optional_rx_offloads = offloads;
+        self
+    }
+
+    /// Sets Tx offloads enabled only if the device supports them.
+    pub fn optional_tx_offloads(mut self, offloads: TxOffloads) -> Self {
+        self.optional_tx_offloads = offloads;
+        self
+    }
+
+    pub fn mtu(mut self, mtu: u16) -> Self {
+        self.dev_conf.rxmode.mtu = mtu as u32;
+        self
+    }
+
+    /// Enables link status change interrupts, see [`crate::port::link::PortEventType`].
+    pub fn lsc_interrupt(mut self, enable: bool) -> Self {
+        self.dev_conf.intr_conf.set_lsc(enable as u32);
+        self
+    }
+
+    /// Enables device removal interrupts, see [`crate::port::link::PortEventType`].
+    pub fn rmv_interrupt(mut self, enable: bool) -> Self {
+        self.dev_conf.intr_conf.set_rmv(enable as u32);
+        self
+    }
+
+    /// Sets RSS hash functions (`RTE_ETH_RSS_*`), other RSS settings are kept.
+    pub fn rss_hf(mut self, rss_hf: u64) -> Self {
+        self.rss.get_or_insert_with(RssConfig::default).hf = rss_hf;
+        self
+    }
+
+    /// Sets the RSS configuration.
+    pub fn rss(mut self, rss: RssConfig) -> Self {
+        self.rss = Some(rss);
+        self
+    }
+
+    /// Sets VLAN offloads and filter IDs, VLAN Rx offloads set with [`Self::rx_offloads`]
+    /// are replaced.
+    pub fn vlan(mut self, vlan: VlanConfig) -> Self {
+        self.vlan = Some(vlan);
+        self
+    }
+
+    /// Sets the memory pool used by Rx queues without a dedicated pool.
+    pub fn rx_mempool(mut self, mempool: Arc<Mempool>) -> Self {
+        self.rxq_mempool = Some(mempool);
+        self
+    }
+
+    /// Sets a dedicated memory pool for the Rx queue.
+    pub fn rx_queue_mempool(mut self, queue_id: u16, mempool: Arc<Mempool>) -> Self {
+        let index = queue_id as usize;
+        if self.rxq_mempools.len() <= index {
+            self.rxq_mempools.resize(index + 1, None);
+        }
+        self.rxq_mempools[index] = Some(mempool);
+        self
+    }
+
+    /// Sets NUMA socket for both Rx and Tx queues.
+    pub fn socket_id(mut self, socket_id: u32) -> Self {
+        self.rxq_socket_id = socket_id;
+        self.txq_socket_id = socket_id;
+        self
+    }
+
+    pub fn rx_socket_id(mut self, socket_id: u32) -> Self {
+        self.rxq_socket_id = socket_id;
+        self
+    }
+
+    pub fn tx_socket_id(mut self, socket_id: u32) -> Self {
+        self.txq_socket_id = socket_id;
+        self
+    }
+
+    /// Queries the device information and builds a validated [`DpdkPortConf`].
+    ///
+    /// Descriptor numbers are adjusted with `rte_eth_dev_adjust_nb_rx_tx_desc`.
+    pub fn build(mut self) -> Result<DpdkPortConf, PortError> {
+        let mut dev_info: rte_eth_dev_info = unsafe { std::mem::zeroed() };
+        PortError::check(
+            unsafe { rte_eth_dev_info_get(self.port_id, &mut dev_info as *mut rte_eth_dev_info) },
+            "rte_eth_dev_info_get",
+            self.port_id,
+            None,
+        )?;
+
+        PortError::check(
+            unsafe {
+                rte_eth_dev_adjust_nb_rx_tx_desc(
+                    self.port_id,
+                    &mut self.rx_desc_num as *mut u16,
+                    &mut self.tx_desc_num as *mut u16,
+                )
+            },
+            "rte_eth_dev_adjust_nb_rx_tx_desc",
+            self.port_id,
+            None,
+        )?;
+
+        self.build_with_dev_info(dev_info)
+    }
+
+    /// Builds a validated [`DpdkPortConf`] against the provided device information.
+    ///
+    /// Descriptor numbers are clamped to `rx_desc_lim` / `tx_desc_lim` the same way
+    /// `rte_eth_dev_adjust_nb_rx_tx_desc` does.
+    pub fn build_with_dev_info(mut self, dev_info: rte_eth_dev_info) -> Result<DpdkPortConf, PortError> {
+        let port_id = self.port_id;
+        let invalid = |reason: String| Err(PortError::InvalidConfig { port_id, reason });
+
+        if self.rxq_num == 0 && self.txq_num == 0 {
+            return invalid("no Rx or Tx queues".to_string());
+        }
+        if self.rxq_num > dev_info.max_rx_queues {
+            return invalid(format!(
+                "{} Rx queues requested, device supports {}",
+                self.rxq_num, dev_info.max_rx_queues
+            ));
+        }
+        if self.txq_num > dev_info.max_tx_queues {
+            return invalid(format!(
+                "{} Tx queues requested, device supports {}",
+                self.txq_num, dev_info.max_tx_queues
+            ));
+        }
+
+        if let Some(vlan) = &self.vlan {
+            if let Err(reason) = vlan.validate(&dev_info) {
+                return invalid(reason);
+            }
+            self.dev_conf.rxmode.offloads =
+                self.dev_conf.rxmode.offloads & !vlan::VLAN_RX_OFFLOADS | vlan.rx_offloads();
+        }
+
+        let rx_offloads = self.dev_conf.rxmode.offloads;
+        if rx_offloads & !dev_info.rx_offload_capa != 0 {
+            return invalid(format!(
+                "unsupported Rx offloads {:#x}",
+                rx_offloads & !dev_info.rx_offload_capa
+            ));
+        }
+        let tx_offloads = self.dev_conf.txmode.offloads;
+        if tx_offloads & !dev_info.tx_offload_capa != 0 {
+            return invalid(format!(
+                "unsupported Tx offloads {:#x}",
+                tx_offloads & !dev_info.tx_offload_capa
+            ));
+        }
+
+        let mtu = self.dev_conf.rxmode.mtu;
+        if mtu != 0 && (mtu < dev_info.min_mtu as u32 || mtu > dev_info.max_mtu as u32) {
+            return invalid(format!(
+                "MTU {} out of range [{}, {}]",
+                mtu, dev_info.min_mtu, dev_info.max_mtu
+            ));
+        }
+
+        // RSS hash functions set directly in the base device configuration
+        let rss = match self.rss {
+            None if self.dev_conf.rx_adv_conf.rss_conf.rss_hf != 0 => {
+                Some(RssConfig::new(self.dev_conf.rx_adv_conf.rss_conf.rss_hf))
+            }
+            rss => rss,
+        };
+        if let Some(Err(reason)) = rss.as_ref().map(|rss| rss.validate(&dev_info, self.rxq_num)) {
+            return invalid(reason);
+        }
+
+        let conf = DpdkPortConf {
+            dev_info,
+            dev_conf: self.dev_conf,
+            tx_conf: self.tx_conf,
+            rx_conf: self.rx_conf,
+            rxq_num: self.rxq_num,
+            txq_num: self.txq_num,
+            tx_desc_num: adjust_desc(self.tx_desc_num, &dev_info.tx_desc_lim),
+            rx_desc_num: adjust_desc(self.rx_desc_num, &dev_info.rx_desc_lim),
+            rxq_socket_id: self.rxq_socket_id,
+            txq_socket_id: self.txq_socket_id,
+            rxq_mempool: self.rxq_mempool,
+            rxq_mempools: self.rxq_mempools,
+            rss,
+            optional_rx_offloads: self.optional_rx_offloads,
+            optional_tx_offloads: self.optional_tx_offloads,
+            vlan: self.vlan,
+        };
+
+        if let Some(queue_id) = (0..conf.rxq_num).find(|q| conf.rx_queue_mempool(*q).is_none()) {
+            return invalid(format!("Rx queue {queue_id} has no mempool"));
+        }
+
+        Ok(conf)
+    }
+}
+
+fn adjust_desc(nb_desc: u16, desc_lim: &rte_eth_desc_lim) -> u16 {
+    let mut nb_desc = nb_desc;
+    if desc_lim.nb_max != 0 {
+        nb_desc = nb_desc.min(desc_lim.nb_max);
+    }
+    nb_desc = nb_desc.max(desc_lim.nb_min);
+    if desc_lim.nb_align != 0 {
+        nb_desc = nb_desc.div_ceil(desc_lim.nb_align) * desc_lim.nb_align;
+    }
+    nb_desc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dev_info() -> rte_eth_dev_info {
+        let mut dev_info: rte_eth_dev_info = unsafe { std::mem::zeroed() };
+        dev_info.max_rx_queues = 4;
+        dev_info.max_tx_queues = 2;
+        dev_info.rx_offload_capa = 0b0110;
+        dev_info.tx_offload_capa = 0b0001;
+        dev_info.flow_type_rss_offloads = 0xf0;
+        dev_info.min_mtu = 68;
+        dev_info.max_mtu = 9000;
+        dev_info.rx_desc_lim.nb_min = 64;
+        dev_info.rx_desc_lim.nb_max = 4096;
+        dev_info.rx_desc_lim.nb_align = 32;
+        dev_info.tx_desc_lim.nb_min = 32;
+        dev_info.tx_desc_lim.nb_max = 1024;
+        dev_info.tx_desc_lim.nb_align = 1;
+        dev_info
+    }
+
+    fn mempool() -> Arc<Mempool> {
+        Arc::new(Mempool::dangling("test_pool"))
+    }
+
+    fn reason(res: Result<DpdkPortConf, PortError>) -> String {
+        match res {
+            Err(PortError::InvalidConfig { reason, .. }) => reason,
+            Err(other) => panic!("unexpected error: {other}"),
+            Ok(_) => panic!("configuration was not rejected"),
+        }
+    }
+
+    #[test]
+    fn build_clamps_descriptors() {
+        let conf = DpdkPortConfBuilder::new(0)
+            .rx_descriptors(100)
+            .tx_descriptors(8192)
+            .rx_mempool(mempool())
+            .build_with_dev_info(dev_info())
+            .unwrap();
+        assert_eq!(conf.rx_desc_num, 128);
+        assert_eq!(conf.tx_desc_num, 1024);
+
+        let conf = DpdkPortConfBuilder::new(0)
+            .rx_descriptors(1)
+            .tx_descriptors(1)
+            .rx_mempool(mempool())
+            .build_with_dev_info(dev_info())
+            .unwrap();
+        assert_eq!(conf.rx_desc_num, 64);
+        assert_eq!(conf.tx_desc_num, 32);
+    }
+
+    #[test]
+    fn build_rejects_queue_numbers() {
+        let res = DpdkPortConfBuilder::new(0)
+            .rx_queues(5)
+            .rx_mempool(mempool())
+            .build_with_dev_info(dev_info());
+        assert!(reason(res).contains("5 Rx queues"));
+
+        let res = DpdkPortConfBuilder::new(0)
+            .tx_queues(3)
+            .rx_mempool(mempool())
+            .build_with_dev_info(dev_info());
+        assert!(reason(res).contains("3 Tx queues"));
+    }
+
+    #[test]
+    fn build_rejects_offloads() {
+        let conf = DpdkPortConfBuilder::new(0)
+            .rx_offloads(RxOffloads::from_bits_retain(0b0100))
+            .tx_offloads(TxOffloads::from_bits_retain(0b0001))
+            .rss_hf(0x30)
+            .mtu(1500)
+            .rx_mempool(mempool())
+            .build_with_dev_info(dev_info())
+            .unwrap();
+        assert_eq!(conf.dev_conf.rxmode.offloads, 0b0100);
+        assert_eq!(conf.dev_conf.txmode.offloads, 0b0001);
+        assert_eq!(conf.rss, Some(RssConfig::new(0x30)));
+
+        let res = DpdkPortConfBuilder::new(0)
+            .rx_offloads(RxOffloads::from_bits_retain(0b1100))
+            .rx_mempool(mempool())
+            .build_with_dev_info(dev_info());
+        assert_eq!(reason(res), "unsupported Rx offloads 0x8");
+
+        let res = DpdkPortConfBuilder::new(0)
+            .tx_offloads(TxOffloads::from_bits_retain(0b0010))
+            .rx_mempool(mempool())
+            .build_with_dev_info(dev_info());
+        assert_eq!(reason(res), "unsupported Tx offloads 0x2");
+
+        let res = DpdkPortConfBuilder::new(0)
+            .rss_hf(0x1f0)
+            .rx_mempool(mempool())
+            .build_with_dev_info(dev_info());
+        assert_eq!(reason(res), "unsupported RSS hash functions 0x100");
+
+        let res = DpdkPortConfBuilder::new(0)
+            .mtu(9600)
+            .rx_mempool(mempool())
+            .build_with_dev_info(dev_info());
+        assert!(reason(res).starts_with("MTU 9600"));
+
+        let res = DpdkPortConfBuilder::new(0)
+            .vlan(VlanConfig::new().strip(true))
+            .rx_mempool(mempool())
+            .build_with_dev_info(dev_info());
+        assert_eq!(reason(res), "unsupported VLAN Rx offloads 0x1");
+    }
+
+    #[test]
+    fn build_resolves_queue_mempools() {
+        let res = DpdkPortConfBuilder::new(0)
+            .rx_queues(2)
+            .rx_queue_mempool(0, mempool())
+            .build_with_dev_info(dev_info());
+        assert_eq!(reason(res), "Rx queue 1 has no mempool");
+
+        let default_pool = mempool();
+        let queue_pool = mempool();
+        let conf = DpdkPortConfBuilder::new(0)
+            .rx_queues(3)
+            .rx_mempool(default_pool.clone())
+            .rx_queue_mempool(1, queue_pool.clone())
+            .build_with_dev_info(dev_info())
+            .unwrap();
+        assert!(Arc::ptr_eq(conf.rx_queue_mempool(0).unwrap(), &default_pool));
+        assert!(Arc::ptr_eq(conf.rx_queue_mempool(1).unwrap(), &queue_pool));
+        assert!(Arc::ptr_eq(conf.rx_queue_mempool(2).unwrap(), &default_pool));
+    }
+}
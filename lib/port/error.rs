@@ -0,0 +1,177 @@
+use std::fmt;
+use std::io;
+use crate::port::PortState;
+
+/// Errors reported by the port layer.
+///
+/// # Overview
+///
+/// DPDK control functions return a negative `errno` on failure. `PortError::Dpdk` keeps
+/// that value together with the name of the failing DPDK call and the port / queue it was
+/// applied to, so callers can tell, for example, a queue setup `ENOMEM` from a
+/// device that refuses to start.
+///
+/// # Example
+///
+/// ```
+/// match dpdk_port.configure() {
+///     Err(err) if err.kind() == std::io::ErrorKind::OutOfMemory => {
+///         eprintln!("not enough memory: {err}");
+///     }
+///     Err(err) => eprintln!("configure failed: {err}"),
+///     Ok(()) => (),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortError {
+    /// A DPDK call returned an error code.
+    Dpdk {
+        /// Name of the failing DPDK function.
+        call: &'static str,
+        /// Port the call was applied to.
+        port_id: u16,
+        /// Queue the call was applied to, if the call is queue specific.
+        queue_id: Option<u16>,
+        /// Negative errno returned by DPDK.
+        errno: i32,
+    },
+
+    /// A queue ID outside of the configured range was used.
+    InvalidQueue { port_id: u16, queue_id: u16 },
+
+    /// Operation is not allowed in the current port state.
+    InvalidState { port_id: u16, call: &'static str, state: PortState },
+
+    /// Operation is not allowed in the current queue state, for example Rx burst on a
+    /// stopped queue or setup of a started queue.
+    QueueState { port_id: u16, queue_id: u16, call: &'static str },
+
+    /// Port configuration is not valid for the device.
+    InvalidConfig { port_id: u16, reason: String },
+
+    /// Port driver cannot handle the device.
+    Unsupported { port_id: u16, reason: String },
+
+    /// Mbuf pool could not be created.
+    Mempool { call: &'static str, name: String },
+
+    /// Mbuf pool name cannot be used.
+    MempoolName { name: String, reason: &'static str },
+
+    /// EAL lcore cannot run or did not complete an IO worker.
+    Lcore { lcore_id: u32, reason: String },
+}
+
+impl PortError {
+    /// Converts a DPDK return code into a `Result`.
+    ///
+    /// Non-negative codes are passed through, negative codes become [`PortError::Dpdk`].
+    pub fn check(
+        rc: i32,
+        call: &'static str,
+        port_id: u16,
+        queue_id: Option<u16>,
+    ) -> Result<i32, PortError> {
+        if rc < 0 {
+            Err(PortError::Dpdk { call, port_id, queue_id, errno: rc })
+        } else {
+            Ok(rc)
+        }
+    }
+
+    /// Returns the negative errno reported by DPDK, if any.
+    pub fn errno(&self) -> Option<i32> {
+        match self {
+            PortError::Dpdk { errno, .. } => Some(*errno),
+            _ => None,
+        }
+    }
+
+    /// Maps the error to `std::io::ErrorKind`.
+    ///
+    /// DPDK errors are mapped through the OS errno table; errors that do not carry
+    /// an errno map to `InvalidInput`, `Unsupported` or `OutOfMemory`.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            PortError::Dpdk { errno, .. } => io::Error::from_raw_os_error(-errno).kind(),
+            PortError::InvalidQueue { .. }
+            | PortError::InvalidState { .. }
+            | PortError::QueueState { .. }
+            | PortError::InvalidConfig { .. }
+            | PortError::MempoolName { .. }
+            | PortError::Lcore { .. } => io::ErrorKind::InvalidInput,
+            PortError::Unsupported { .. } => io::ErrorKind::Unsupported,
+            PortError::Mempool { .. } => io::ErrorKind::OutOfMemory,
+        }
+    }
+}
+
+impl fmt::Display for PortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortError::Dpdk { call, port_id, queue_id: Some(queue_id), errno } => write!(
+                f,
+                "port {port_id} queue {queue_id}: {call} failed: {}",
+                io::Error::from_raw_os_error(-errno)
+            ),
+            PortError::Dpdk { call, port_id, queue_id: None, errno } => write!(
+                f,
+                "port {port_id}: {call} failed: {}",
+                io::Error::from_raw_os_error(-errno)
+            ),
+            PortError::InvalidQueue { port_id, queue_id } => {
+                write!(f, "port {port_id}: invalid queue {queue_id}")
+            }
+            PortError::InvalidState { port_id, call, state } => {
+                write!(f, "port {port_id}: {call} not allowed, port is {state}")
+            }
+            PortError::QueueState { port_id, queue_id, call } => {
+                write!(f, "port {port_id} queue {queue_id}: {call} not allowed in current queue state")
+            }
+            PortError::InvalidConfig { port_id, reason } => {
+                write!(f, "port {port_id}: invalid configuration: {reason}")
+            }
+            PortError::Unsupported { port_id, reason } => {
+                write!(f, "port {port_id}: unsupported: {reason}")
+            }
+            PortError::Mempool { call, name } => {
+                write!(f, "mempool \"{name}\": {call} failed")
+            }
+            PortError::MempoolName { name, reason } => {
+                write!(f, "mempool \"{name}\": {reason}")
+            }
+            PortError::Lcore { lcore_id, reason } => {
+                write!(f, "lcore {lcore_id}: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PortError {}
+
+impl From<PortError> for io::Error {
+    fn from(err: PortError) -> Self {
+        io::Error::new(err.kind(), err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_maps_negative_errno() {
+        assert_eq!(PortError::check(3, "rte_eth_dev_start", 0, None), Ok(3));
+
+        let err = PortError::check(-12, "rte_eth_rx_queue_setup", 1, Some(2)).unwrap_err();
+        assert_eq!(err.errno(), Some(-12));
+        assert_eq!(err.kind(), io::ErrorKind::OutOfMemory);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "port 1 queue 2: rte_eth_rx_queue_setup failed: {}",
+                io::Error::from_raw_os_error(12)
+            )
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::port::error::PortError;
+use crate::dpdk_raw::rte_ethdev::{
+    rte_ether_addr,
+    rte_eth_macaddr_get,
+    rte_eth_macaddrs_get,
+    rte_eth_dev_default_mac_addr_set,
+    rte_eth_dev_mac_addr_add,
+    rte_eth_dev_mac_addr_remove,
+    rte_eth_dev_set_mc_addr_list,
+    rte_eth_dev_get_mtu,
+    rte_eth_dev_set_mtu,
+    rte_eth_promiscuous_enable,
+    rte_eth_promiscuous_disable,
+    rte_eth_promiscuous_get,
+    rte_eth_allmulticast_enable,
+    rte_eth_allmulticast_disable,
+    rte_eth_allmulticast_get,
+};
+
+/// Ethernet MAC address.
+///
+/// Parsed from and formatted as `xx:xx:xx:xx:xx:xx`.
+///
+/// # Example
+///
+/// ```
+/// let mac: EtherAddr = "02:00:00:00:00:01".parse()?;
+/// assert!(mac.is_unicast() && mac.is_locally_administered());
+/// dpdk_port.set_default_mac_addr(mac)?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct EtherAddr(pub [u8; 6]);
+
+impl EtherAddr {
+    pub const BROADCAST: EtherAddr = EtherAddr([0xff; 6]);
+
+    pub const fn new(bytes: [u8; 6]) -> Self {
+        EtherAddr(bytes)
+    }
+
+    pub fn bytes(&self) -> [u8; 6] {
+        self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 6]
+    }
+
+    pub fn is_broadcast(&self) -> bool {
+        *self == EtherAddr::BROADCAST
+    }
+
+    /// Group address bit is set, broadcast included.
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    pub fn is_unicast(&self) -> bool {
+        !self.is_multicast()
+    }
+
+    pub fn is_locally_administered(&self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+
+    /// Unicast and not all zeroes, the `rte_is_valid_assigned_ether_addr` rule.
+    pub fn is_valid_assigned(&self) -> bool {
+        self.is_unicast() && !self.is_zero()
+    }
+
+    pub fn to_raw(self) -> rte_ether_addr {
+        rte_ether_addr { addr_bytes: self.0 }
+    }
+
+    pub fn from_raw(addr: &rte_ether_addr) -> Self {
+        EtherAddr(addr.addr_bytes)
+    }
+}
+
+impl From<[u8; 6]> for EtherAddr {
+    fn from(bytes: [u8; 6]) -> Self {
+        EtherAddr(bytes)
+    }
+}
+
+impl fmt::Display for EtherAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let b = &self.0;
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            b[0], b[1], b[2], b[3], b[4], b[5]
+        )
+    }
+}
+
+impl FromStr for EtherAddr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid MAC address: \"{s}\"");
+        let mut bytes = [0u8; 6];
+        let mut parts = s.split(':');
+        for byte in bytes.iter_mut() {
+            let part = parts.next().ok_or_else(invalid)?;
+            if part.is_empty() || part.len() > 2 {
+                return Err(invalid());
+            }
+            *byte = u8::from_str_radix(part, 16).map_err(|_| invalid())?;
+        }
+        match parts.next() {
+            Some(_) => Err(invalid()),
+            None => Ok(EtherAddr(bytes)),
+        }
+    }
+}
+
+fn invalid_addr(port_id: u16, addr: EtherAddr, reason: &str) -> PortError {
+    PortError::InvalidConfig { port_id, reason: format!("{reason} address {addr}") }
+}
+
+/// Returns the port default MAC address.
+pub fn mac_addr(port_id: u16) -> Result<EtherAddr, PortError> {
+    let mut addr = EtherAddr::default().to_raw();
+    PortError::check(
+        unsafe { rte_eth_macaddr_get(port_id, &mut addr) },
+        "rte_eth_macaddr_get",
+        port_id,
+        None,
+    )?;
+    Ok(EtherAddr::from_raw(&addr))
+}
+
+/// Returns all port MAC addresses, the default address first.
+///
+/// `max_mac_addrs` is the `rte_eth_dev_info` value.
+pub fn mac_addrs(port_id: u16, max_mac_addrs: u32) -> Result<Vec<EtherAddr>, PortError> {
+    let mut addrs = vec![EtherAddr::default().to_raw(); max_mac_addrs as usize];
+    let count = PortError::check(
+        unsafe { rte_eth_macaddrs_get(port_id, addrs.as_mut_ptr(), max_mac_addrs) },
+        "rte_eth_macaddrs_get",
+        port_id,
+        None,
+    )? as usize;
+    Ok(addrs[..count.min(addrs.len())]
+        .iter()
+        .map(EtherAddr::from_raw)
+        .filter(|addr| !addr.is_zero())
+        .collect())
+}
+
+pub fn set_default_mac_addr(port_id: u16, addr: EtherAddr) -> Result<(), PortError> {
+    if !addr.is_valid_assigned() {
+        return Err(invalid_addr(port_id, addr, "invalid default MAC"));
+    }
+    let mut raw = addr.to_raw();
+    PortError::check(
+        unsafe { rte_eth_dev_default_mac_addr_set(port_id, &mut raw) },
+        "rte_eth_dev_default_mac_addr_set",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+/// Adds a secondary MAC address to the VMDq `pool`, use pool 0 without VMDq.
+pub fn add_mac_addr(port_id: u16, addr: EtherAddr, pool: u32) -> Result<(), PortError> {
+    if addr.is_zero() {
+        return Err(invalid_addr(port_id, addr, "invalid MAC"));
+    }
+    let mut raw = addr.to_raw();
+    PortError::check(
+        unsafe { rte_eth_dev_mac_addr_add(port_id, &mut raw, pool) },
+        "rte_eth_dev_mac_addr_add",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+/// Removes a secondary MAC address, the default address cannot be removed.
+pub fn remove_mac_addr(port_id: u16, addr: EtherAddr) -> Result<(), PortError> {
+    let mut raw = addr.to_raw();
+    PortError::check(
+        unsafe { rte_eth_dev_mac_addr_remove(port_id, &mut raw) },
+        "rte_eth_dev_mac_addr_remove",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+/// Replaces the multicast addresses the port accepts, an empty list flushes them.
+pub fn set_mc_addr_list(port_id: u16, addrs: &[EtherAddr]) -> Result<(), PortError> {
+    if let Some(addr) = addrs.iter().find(|addr| !addr.is_multicast()) {
+        return Err(invalid_addr(port_id, *addr, "not a multicast"));
+    }
+    let mut raw: Vec<rte_ether_addr> = addrs.iter().map(|addr| addr.to_raw()).collect();
+    let mc_addr_set = if raw.is_empty() { std::ptr::null_mut() } else { raw.as_mut_ptr() };
+    PortError::check(
+        unsafe { rte_eth_dev_set_mc_addr_list(port_id, mc_addr_set, raw.len() as u32) },
+        "rte_eth_dev_set_mc_addr_list",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+pub fn mtu(port_id: u16) -> Result<u16, PortError> {
+    let mut mtu: u16 = 0;
+    PortError::check(
+        unsafe { rte_eth_dev_get_mtu(port_id, &mut mtu) },
+        "rte_eth_dev_get_mtu",
+        port_id,
+        None,
+    )?;
+    Ok(mtu)
+}
+
+pub fn set_mtu(port_id: u16, mtu: u16) -> Result<(), PortError> {
+    PortError::check(
+        unsafe { rte_eth_dev_set_mtu(port_id, mtu) },
+        "rte_eth_dev_set_mtu",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+pub fn set_promiscuous(port_id: u16, enable: bool) -> Result<(), PortError> {
+    let (rc, call) = if enable {
+        (unsafe { rte_eth_promiscuous_enable(port_id) }, "rte_eth_promiscuous_enable")
+    } else {
+        (unsafe { rte_eth_promiscuous_disable(port_id) }, "rte_eth_promiscuous_disable")
+    };
+    PortError::check(rc, call, port_id, None)?;
+    Ok(())
+}
+
+pub fn promiscuous(port_id: u16) -> Result<bool, PortError> {
+    let rc = PortError::check(
+        unsafe { rte_eth_promiscuous_get(port_id) },
+        "rte_eth_promiscuous_get",
+        port_id,
+        None,
+    )?;
+    Ok(rc == 1)
+}
+
+pub fn set_allmulticast(port_id: u16, enable: bool) -> Result<(), PortError> {
+    let (rc, call) = if enable {
+        (unsafe { rte_eth_allmulticast_enable(port_id) }, "rte_eth_allmulticast_enable")
+    } else {
+        (unsafe { rte_eth_allmulticast_disable(port_id) }, "rte_eth_allmulticast_disable")
+    };
+    PortError::check(rc, call, port_id, None)?;
+    Ok(())
+}
+
+pub fn allmulticast(port_id: u16) -> Result<bool, PortError> {
+    let rc = PortError::check(
+        unsafe { rte_eth_allmulticast_get(port_id) },
+        "rte_eth_allmulticast_get",
+        port_id,
+        None,
+    )?;
+    Ok(rc == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ether_addr_parse_format() {
+        let addr: EtherAddr = "02:1b:21:A0:0f:9".parse().unwrap();
+        assert_eq!(addr, EtherAddr([0x02, 0x1b, 0x21, 0xa0, 0x0f, 0x09]));
+        assert_eq!(addr.to_string(), "02:1b:21:a0:0f:09");
+        assert_eq!(EtherAddr::from_raw(&addr.to_raw()), addr);
+
+        for bad in ["", "02:1b:21:a0:0f", "02:1b:21:a0:0f:09:00", "02:1b:21:a0:0f:", "02:1b:21:a0:0f:100", "g2:1b:21:a0:0f:09"] {
+            assert!(bad.parse::<EtherAddr>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn ether_addr_kind() {
+        let addr: EtherAddr = "02:00:00:00:00:01".parse().unwrap();
+        assert!(addr.is_unicast() && addr.is_locally_administered() && addr.is_valid_assigned());
+
+        let mcast: EtherAddr = "01:00:5e:00:00:fb".parse().unwrap();
+        assert!(mcast.is_multicast() && !mcast.is_valid_assigned());
+        assert!(EtherAddr::BROADCAST.is_broadcast() && EtherAddr::BROADCAST.is_multicast());
+        assert!(!EtherAddr::default().is_valid_assigned());
+    }
+}
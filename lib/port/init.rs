@@ -3,11 +3,12 @@ use std::collections::HashMap;
 use std::sync::{Mutex};
 use once_cell::sync::Lazy;
 use crate::port::{DpdkPort, DpdkPortConf};
+use crate::port::error::PortError;
 
 pub type PciVendor = u16;
 pub type PciDevice = u16;
 
-pub type PciPortInitFn = fn(port_id: u16, device: PciDevice, port_conf: &DpdkPortConf) -> Result<Box<dyn DpdkPort>, String>;
+pub type PciPortInitFn = fn(port_id: u16, device: PciDevice, port_conf: &DpdkPortConf) -> Result<Box<dyn DpdkPort>, PortError>;
 
 pub type PortHwMap = HashMap<PciVendor, PciPortInitFn>;
 
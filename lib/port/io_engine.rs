@@ -0,0 +1,359 @@
+use std::io;
+use std::os::raw::{c_int, c_uint, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::port::DpdkPort;
+use crate::port::error::PortError;
+use crate::port::queue::{RxQueue, TxQueue};
+use crate::dpdk_raw::rte_ethdev::{
+    rte_eal_remote_launch,
+    rte_eal_wait_lcore,
+    rte_get_main_lcore,
+    rte_get_next_lcore,
+    rte_lcore_is_enabled,
+    RTE_MAX_LCORE,
+};
+
+/// Port queue polled by an [`IoWorker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IoQueue {
+    pub port_id: u16,
+    pub queue_id: u16,
+}
+
+/// Packets received on `rxq` are sent on `txq`, the queues may belong to different ports.
+pub struct IoStream {
+    pub rxq: RxQueue,
+    pub txq: TxQueue,
+}
+
+impl IoStream {
+    pub fn new(rxq: RxQueue, txq: TxQueue) -> Self {
+        IoStream { rxq, txq }
+    }
+
+    /// Takes Rx and Tx queue `queue_id` of the port.
+    pub fn from_port(port: &mut dyn DpdkPort, queue_id: u16) -> Result<Self, PortError> {
+        let rxq = port.rx_queue(queue_id)?;
+        Ok(IoStream { rxq, txq: port.tx_queue(queue_id)? })
+    }
+
+    pub fn rx(&self) -> IoQueue {
+        IoQueue { port_id: self.rxq.port_id(), queue_id: self.rxq.queue_id() }
+    }
+
+    pub fn tx(&self) -> IoQueue {
+        IoQueue { port_id: self.txq.port_id(), queue_id: self.txq.queue_id() }
+    }
+}
+
+/// Returns enabled EAL lcores, the main lcore excluded.
+pub fn worker_lcores() -> Vec<u32> {
+    let mut lcores = Vec::new();
+    // -1 starts the search from lcore 0
+    let mut lcore_id = unsafe { rte_get_next_lcore(c_uint::MAX, 1, 0) };
+    while lcore_id < RTE_MAX_LCORE {
+        lcores.push(lcore_id);
+        lcore_id = unsafe { rte_get_next_lcore(lcore_id, 1, 0) };
+    }
+    lcores
+}
+
+/// Spreads items over lcores round-robin, lcores without items are left out.
+pub fn distribute<T>(items: Vec<T>, lcores: &[u32]) -> Vec<(u32, Vec<T>)> {
+    let mut map: Vec<(u32, Vec<T>)> = lcores.iter().map(|lcore| (*lcore, Vec::new())).collect();
+    if map.is_empty() {
+        return map;
+    }
+    let nb_lcores = map.len();
+    for (i, item) in items.into_iter().enumerate() {
+        map[i % nb_lcores].1.push(item);
+    }
+    map.retain(|(_, items)| !items.is_empty());
+    map
+}
+
+fn lcore_entry<T>(map: &mut Vec<(u32, Vec<T>)>, lcore_id: u32) -> &mut Vec<T> {
+    let pos = match map.iter().position(|(id, _)| *id == lcore_id) {
+        Some(pos) => pos,
+        None => {
+            map.push((lcore_id, Vec::new()));
+            map.len() - 1
+        }
+    };
+    &mut map[pos].1
+}
+
+/// Streams owned by a worker lcore.
+///
+/// The worker is the only user of its queue handles, bursts go straight to the PMD
+/// without locking the ports.
+pub struct IoWorker {
+    lcore_id: u32,
+    streams: Vec<IoStream>,
+}
+
+impl IoWorker {
+    pub fn lcore_id(&self) -> u32 {
+        self.lcore_id
+    }
+
+    pub fn streams(&self) -> &[IoStream] {
+        &self.streams
+    }
+
+    pub fn streams_mut(&mut self) -> &mut [IoStream] {
+        &mut self.streams
+    }
+}
+
+/// Per-lcore worker function, called in a loop until the engine is stopped.
+pub type IoWorkerFn = Box<dyn FnMut(&mut IoWorker) + Send>;
+
+struct LcoreJob {
+    worker: IoWorker,
+    run: IoWorkerFn,
+    stop: Arc<AtomicBool>,
+}
+
+unsafe extern "C" fn lcore_main(arg: *mut c_void) -> c_int {
+    let job = unsafe { &mut *(arg as *mut LcoreJob) };
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        while !job.stop.load(Ordering::Acquire) {
+            (job.run)(&mut job.worker);
+        }
+    }));
+    if result.is_ok() { 0 } else { -1 }
+}
+
+unsafe impl Send for IoEngine {}
+
+/// Packet IO on EAL worker lcores.
+///
+/// # Overview
+///
+/// `IoEngine` maps [`IoStream`]s to EAL worker lcores. Streams own their queue handles,
+/// so each queue is polled by exactly one lcore. [`IoEngine::launch`] moves the streams
+/// to the lcores and starts a worker function on every lcore with `rte_eal_remote_launch`.
+///
+/// [`IoEngine::stop`] asks the workers to return, [`IoEngine::join`] waits for them with
+/// `rte_eal_wait_lcore` and takes the streams back, the engine can be launched again.
+/// [`IoEngine::clear`] drops the streams and releases the queues. The engine is stopped
+/// and joined on drop.
+///
+/// Launch and join must be called from the EAL main lcore.
+///
+/// # Example
+///
+/// ```
+/// let streams = vec![IoStream::from_port(&mut port, 0)?, IoStream::from_port(&mut port, 1)?];
+/// let mut engine = IoEngine::new();
+/// for (lcore_id, streams) in distribute(streams, &worker_lcores()) {
+///     for stream in streams {
+///         engine.assign(lcore_id, stream)?;
+///     }
+/// }
+/// engine.launch(|_worker| {
+///     let mut pkts = MbufBatch::new(32);
+///     Box::new(move |worker: &mut IoWorker| {
+///         for stream in worker.streams_mut() {
+///             stream.rxq.rx_burst(&mut pkts);
+///             stream.txq.tx_burst(&mut pkts);
+///             pkts.clear();
+///         }
+///     })
+/// })?;
+/// ...
+/// engine.stop();
+/// engine.join()?;
+/// engine.clear()?;
+/// ```
+pub struct IoEngine {
+    // Streams of idle lcores.
+    streams: Vec<(u32, Vec<IoStream>)>,
+    // Rx and Tx queues of every stream, kept while the streams run on the lcores.
+    layout: Vec<(u32, Vec<(IoQueue, IoQueue)>)>,
+    stop: Arc<AtomicBool>,
+    // Jobs are owned by the worker lcores until joined.
+    jobs: Vec<(u32, *mut LcoreJob)>,
+}
+
+impl Default for IoEngine {
+    fn default() -> Self {
+        IoEngine::new()
+    }
+}
+
+impl IoEngine {
+    pub fn new() -> Self {
+        IoEngine {
+            streams: Vec::new(),
+            layout: Vec::new(),
+            stop: Arc::new(AtomicBool::new(false)),
+            jobs: Vec::new(),
+        }
+    }
+
+    fn check_idle(&self) -> Result<(), PortError> {
+        match self.jobs.first() {
+            Some((lcore_id, _)) => Err(PortError::Lcore {
+                lcore_id: *lcore_id,
+                reason: "IO engine is running".to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Assigns a stream to a worker lcore.
+    pub fn assign(&mut self, lcore_id: u32, stream: IoStream) -> Result<(), PortError> {
+        self.check_idle()?;
+        if lcore_id >= RTE_MAX_LCORE || unsafe { rte_lcore_is_enabled(lcore_id) } == 0 {
+            return Err(PortError::Lcore { lcore_id, reason: "lcore is not enabled".to_string() });
+        }
+        if lcore_id == unsafe { rte_get_main_lcore() } {
+            return Err(PortError::Lcore { lcore_id, reason: "main lcore cannot run IO".to_string() });
+        }
+        lcore_entry(&mut self.layout, lcore_id).push((stream.rx(), stream.tx()));
+        lcore_entry(&mut self.streams, lcore_id).push(stream);
+        Ok(())
+    }
+
+    /// Drops all streams, releasing their queues.
+    pub fn clear(&mut self) -> Result<(), PortError> {
+        self.check_idle()?;
+        self.streams.clear();
+        self.layout.clear();
+        Ok(())
+    }
+
+    /// Lcores with the Rx and Tx queues of their streams.
+    pub fn lcores(&self) -> &[(u32, Vec<(IoQueue, IoQueue)>)] {
+        &self.layout
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.jobs.is_empty()
+    }
+
+    /// Launches workers on all lcores with assigned streams.
+    ///
+    /// `new_worker` runs on the calling lcore once per worker and returns the function the
+    /// worker lcore calls in a loop, per-lcore state is captured by that function.
+    pub fn launch<F>(&mut self, mut new_worker: F) -> Result<(), PortError>
+    where
+        F: FnMut(&IoWorker) -> IoWorkerFn,
+    {
+        self.check_idle()?;
+        self.stop.store(false, Ordering::Release);
+
+        while !self.streams.is_empty() {
+            let (lcore_id, streams) = self.streams.remove(0);
+            let worker = IoWorker { lcore_id, streams };
+            let run = new_worker(&worker);
+            let job = Box::into_raw(Box::new(LcoreJob { worker, run, stop: self.stop.clone() }));
+            let rc = unsafe { rte_eal_remote_launch(Some(lcore_main), job as *mut c_void, lcore_id) };
+            if rc < 0 {
+                let job = unsafe { Box::from_raw(job) };
+                self.streams.insert(0, (lcore_id, job.worker.streams));
+                self.stop();
+                let _ = self.join();
+                return Err(PortError::Lcore {
+                    lcore_id,
+                    reason: format!(
+                        "rte_eal_remote_launch failed: {}",
+                        io::Error::from_raw_os_error(-rc)
+                    ),
+                });
+            }
+            self.jobs.push((lcore_id, job));
+        }
+        Ok(())
+    }
+
+    /// Asks the workers to return, see [`IoEngine::join`].
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Release);
+    }
+
+    /// Waits for all workers to return and takes their streams back, reports the first
+    /// worker that panicked.
+    pub fn join(&mut self) -> Result<(), PortError> {
+        let mut result = Ok(());
+        for (lcore_id, job) in std::mem::take(&mut self.jobs) {
+            let rc = unsafe { rte_eal_wait_lcore(lcore_id) };
+            let job = unsafe { Box::from_raw(job) };
+            self.streams.push((lcore_id, job.worker.streams));
+            if rc < 0 && result.is_ok() {
+                result = Err(PortError::Lcore { lcore_id, reason: "IO worker panicked".to_string() });
+            }
+        }
+        result
+    }
+}
+
+impl Drop for IoEngine {
+    fn drop(&mut self) {
+        if self.is_running() {
+            self.stop();
+            if let Err(err) = self.join() {
+                eprintln!("{err}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::port::queue::QueueOwners;
+    use crate::dpdk_raw::rte_mbuf::rte_mbuf;
+
+    unsafe extern "C" fn no_burst(_q: *mut c_void, _pkts: *mut *mut rte_mbuf, _nb_pkts: u16) -> u16 {
+        0
+    }
+
+    fn stream(rx: &mut QueueOwners, tx: &mut QueueOwners, port_id: u16, queue_id: u16) -> IoStream {
+        let null = std::ptr::null_mut();
+        unsafe {
+            IoStream::new(
+                RxQueue::new(rx.claim(port_id, queue_id).unwrap(), no_burst, null),
+                TxQueue::new(tx.claim(port_id ^ 1, queue_id).unwrap(), no_burst, null),
+            )
+        }
+    }
+
+    #[test]
+    fn distribute_round_robin() {
+        assert_eq!(
+            distribute(vec![1, 2, 3, 4, 5], &[2, 5]),
+            [(2, vec![1, 3, 5]), (5, vec![2, 4])]
+        );
+        assert_eq!(distribute(vec![1], &[2, 5, 7]), [(2, vec![1])]);
+        assert!(distribute(vec![1, 2], &[]).is_empty());
+    }
+
+    #[test]
+    fn distribute_streams() {
+        let (mut rx, mut tx) = (QueueOwners::new(), QueueOwners::new());
+        let streams: Vec<IoStream> = (0..3).map(|q| stream(&mut rx, &mut tx, 0, q)).collect();
+
+        let mut layout = Vec::new();
+        for (lcore_id, streams) in distribute(streams, &[1, 2]) {
+            for stream in streams {
+                lcore_entry(&mut layout, lcore_id).push((stream.rx(), stream.tx()));
+            }
+        }
+        let queue = |port_id, queue_id| IoQueue { port_id, queue_id };
+        assert_eq!(
+            layout,
+            [
+                (1, vec![(queue(0, 0), queue(1, 0)), (queue(0, 2), queue(1, 2))]),
+                (2, vec![(queue(0, 1), queue(1, 1))]),
+            ]
+        );
+        // streams were dropped with the distribution
+        assert_eq!(rx.first_owned(), None);
+        assert_eq!(tx.first_owned(), None);
+    }
+}
@@ -0,0 +1,336 @@
+use std::fmt;
+use std::os::raw::{c_int, c_void};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use crate::port::error::PortError;
+use crate::dpdk_raw::rte_ethdev::{
+    rte_eth_link,
+    rte_eth_event_type,
+    rte_eth_link_get,
+    rte_eth_link_get_nowait,
+    rte_eth_dev_set_link_up,
+    rte_eth_dev_set_link_down,
+    rte_eth_dev_callback_register,
+    rte_eth_dev_callback_unregister,
+    rte_eth_event_type_RTE_ETH_EVENT_INTR_LSC,
+    rte_eth_event_type_RTE_ETH_EVENT_QUEUE_STATE,
+    rte_eth_event_type_RTE_ETH_EVENT_INTR_RESET,
+    rte_eth_event_type_RTE_ETH_EVENT_INTR_RMV,
+    RTE_ETH_ALL,
+    RTE_ETH_LINK_UP,
+    RTE_ETH_LINK_FULL_DUPLEX,
+    RTE_ETH_LINK_AUTONEG,
+    RTE_ETH_SPEED_NUM_NONE,
+    RTE_ETH_SPEED_NUM_UNKNOWN,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkDuplex {
+    Half,
+    Full,
+}
+
+/// Port link status reported by `rte_eth_link_get_nowait`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkStatus {
+    /// Link speed in Mbps, `None` if the driver does not report it.
+    pub speed: Option<u32>,
+    pub duplex: LinkDuplex,
+    pub autoneg: bool,
+    pub up: bool,
+}
+
+impl LinkStatus {
+    pub fn from_raw(link: &rte_eth_link) -> Self {
+        let link = unsafe { link.__bindgen_anon_1.__bindgen_anon_1 };
+        LinkStatus {
+            speed: match link.link_speed {
+                RTE_ETH_SPEED_NUM_NONE | RTE_ETH_SPEED_NUM_UNKNOWN => None,
+                speed => Some(speed),
+            },
+            duplex: if link.link_duplex() as u32 == RTE_ETH_LINK_FULL_DUPLEX {
+                LinkDuplex::Full
+            } else {
+                LinkDuplex::Half
+            },
+            autoneg: link.link_autoneg() as u32 == RTE_ETH_LINK_AUTONEG,
+            up: link.link_status() as u32 == RTE_ETH_LINK_UP,
+        }
+    }
+}
+
+/// Formats the status the way `rte_eth_link_to_str` does, for example
+/// `Link up at 100 Gbps FDX Autoneg`.
+impl fmt::Display for LinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.up {
+            return f.write_str("Link down");
+        }
+        match self.speed {
+            Some(speed) if speed >= 1000 && speed % 1000 == 0 => {
+                write!(f, "Link up at {} Gbps", speed / 1000)?
+            }
+            Some(speed) => write!(f, "Link up at {speed} Mbps")?,
+            None => f.write_str("Link up at Unknown")?,
+        }
+        let duplex = match self.duplex {
+            LinkDuplex::Full => "FDX",
+            LinkDuplex::Half => "HDX",
+        };
+        let autoneg = if self.autoneg { "Autoneg" } else { "Fixed" };
+        write!(f, " {duplex} {autoneg}")
+    }
+}
+
+/// Reads the link status without waiting for the link to settle.
+pub fn link_status(port_id: u16) -> Result<LinkStatus, PortError> {
+    let mut link: rte_eth_link = unsafe { std::mem::zeroed() };
+    PortError::check(
+        unsafe { rte_eth_link_get_nowait(port_id, &mut link) },
+        "rte_eth_link_get_nowait",
+        port_id,
+        None,
+    )?;
+    Ok(LinkStatus::from_raw(&link))
+}
+
+/// Reads the link status, waiting up to 9 seconds for the link to come up.
+pub fn link_status_wait(port_id: u16) -> Result<LinkStatus, PortError> {
+    let mut link: rte_eth_link = unsafe { std::mem::zeroed() };
+    PortError::check(
+        unsafe { rte_eth_link_get(port_id, &mut link) },
+        "rte_eth_link_get",
+        port_id,
+        None,
+    )?;
+    Ok(LinkStatus::from_raw(&link))
+}
+
+pub fn set_link_up(port_id: u16) -> Result<(), PortError> {
+    PortError::check(
+        unsafe { rte_eth_dev_set_link_up(port_id) },
+        "rte_eth_dev_set_link_up",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+pub fn set_link_down(port_id: u16) -> Result<(), PortError> {
+    PortError::check(
+        unsafe { rte_eth_dev_set_link_down(port_id) },
+        "rte_eth_dev_set_link_down",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+/// Port events delivered by [`EventSubscription`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PortEventType {
+    /// Link status change, `RTE_ETH_EVENT_INTR_LSC`.
+    /// The port must be configured with `intr_conf.lsc`.
+    LinkStatusChange,
+
+    /// Device removal, `RTE_ETH_EVENT_INTR_RMV`.
+    /// The port must be configured with `intr_conf.rmv`.
+    Removed,
+
+    /// Device reset is required, `RTE_ETH_EVENT_INTR_RESET`, see [`crate::port::DpdkPort::reset`].
+    ResetRequired,
+
+    /// Queue enabled or disabled, `RTE_ETH_EVENT_QUEUE_STATE`.
+    QueueState,
+}
+
+impl PortEventType {
+    pub const ALL: [PortEventType; 4] = [
+        PortEventType::LinkStatusChange,
+        PortEventType::Removed,
+        PortEventType::ResetRequired,
+        PortEventType::QueueState,
+    ];
+
+    pub fn to_raw(self) -> rte_eth_event_type {
+        match self {
+            PortEventType::LinkStatusChange => rte_eth_event_type_RTE_ETH_EVENT_INTR_LSC,
+            PortEventType::Removed => rte_eth_event_type_RTE_ETH_EVENT_INTR_RMV,
+            PortEventType::ResetRequired => rte_eth_event_type_RTE_ETH_EVENT_INTR_RESET,
+            PortEventType::QueueState => rte_eth_event_type_RTE_ETH_EVENT_QUEUE_STATE,
+        }
+    }
+
+    pub fn from_raw(event: rte_eth_event_type) -> Option<Self> {
+        PortEventType::ALL.into_iter().find(|ty| ty.to_raw() == event)
+    }
+}
+
+/// Port event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortEvent {
+    pub port_id: u16,
+    pub event: PortEventType,
+    /// Link status read when a [`PortEventType::LinkStatusChange`] event was received.
+    pub link: Option<LinkStatus>,
+}
+
+/// Registration of DPDK port event callbacks.
+///
+/// # Overview
+///
+/// `EventSubscription` registers a callback with `rte_eth_dev_callback_register` for each
+/// requested [`PortEventType`]. The callback runs in the DPDK interrupt thread and only
+/// forwards the event to a channel, the application handles events from the
+/// `Receiver` returned by [`EventSubscription::new`].
+///
+/// Callbacks are unregistered when the subscription is dropped.
+///
+/// # Example
+///
+/// ```
+/// let (_events, rx) = EventSubscription::new(None, &[PortEventType::LinkStatusChange])?;
+/// for event in rx {
+///     match event.link {
+///         Some(link) => println!("port {}: {link}", event.port_id),
+///         None => println!("port {}: {:?}", event.port_id, event.event),
+///     }
+/// }
+/// ```
+pub struct EventSubscription {
+    port_id: u16,
+    events: Vec<PortEventType>,
+    // Callback argument, boxed to keep its address stable.
+    sender: Box<Sender<PortEvent>>,
+}
+
+unsafe extern "C" fn event_callback(
+    port_id: u16,
+    event: rte_eth_event_type,
+    cb_arg: *mut c_void,
+    _ret_param: *mut c_void,
+) -> c_int {
+    let sender = unsafe { &*(cb_arg as *const Sender<PortEvent>) };
+    if let Some(event) = PortEventType::from_raw(event) {
+        let link = match event {
+            PortEventType::LinkStatusChange => link_status(port_id).ok(),
+            _ => None,
+        };
+        // The receiver may be gone, the subscription unregisters on drop.
+        let _ = sender.send(PortEvent { port_id, event, link });
+    }
+    0
+}
+
+impl EventSubscription {
+    /// Subscribes to `events` of a port, or of all ports if `port_id` is `None`.
+    pub fn new(
+        port_id: Option<u16>,
+        events: &[PortEventType],
+    ) -> Result<(Self, Receiver<PortEvent>), PortError> {
+        let (sender, receiver) = channel();
+        let mut subscription = EventSubscription {
+            port_id: port_id.unwrap_or(RTE_ETH_ALL as u16),
+            events: Vec::with_capacity(events.len()),
+            sender: Box::new(sender),
+        };
+
+        for &event in events {
+            if subscription.events.contains(&event) {
+                continue;
+            }
+            PortError::check(
+                unsafe {
+                    rte_eth_dev_callback_register(
+                        subscription.port_id,
+                        event.to_raw(),
+                        Some(event_callback),
+                        subscription.cb_arg(),
+                    )
+                },
+                "rte_eth_dev_callback_register",
+                subscription.port_id,
+                None,
+            )?;
+            subscription.events.push(event);
+        }
+        Ok((subscription, receiver))
+    }
+
+    /// Subscribed port, `None` for all ports.
+    pub fn port_id(&self) -> Option<u16> {
+        (self.port_id != RTE_ETH_ALL as u16).then_some(self.port_id)
+    }
+
+    pub fn events(&self) -> &[PortEventType] {
+        &self.events
+    }
+
+    fn cb_arg(&self) -> *mut c_void {
+        &*self.sender as *const Sender<PortEvent> as *mut c_void
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        for event in &self.events {
+            loop {
+                let rc = unsafe {
+                    rte_eth_dev_callback_unregister(
+                        self.port_id,
+                        event.to_raw(),
+                        Some(event_callback),
+                        self.cb_arg(),
+                    )
+                };
+                // -EAGAIN while the callback is running
+                match PortError::check(rc, "rte_eth_dev_callback_unregister", self.port_id, None) {
+                    Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                        std::thread::yield_now()
+                    }
+                    _ => break,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_link(speed: u32, up: bool, duplex: u32, autoneg: u32) -> rte_eth_link {
+        let mut link: rte_eth_link = unsafe { std::mem::zeroed() };
+        let mut fields = unsafe { link.__bindgen_anon_1.__bindgen_anon_1 };
+        fields.link_speed = speed;
+        fields.set_link_status(up as u16);
+        fields.set_link_duplex(duplex as u16);
+        fields.set_link_autoneg(autoneg as u16);
+        link.__bindgen_anon_1.__bindgen_anon_1 = fields;
+        link
+    }
+
+    #[test]
+    fn link_status_from_raw() {
+        let link = LinkStatus::from_raw(&raw_link(100_000, true, RTE_ETH_LINK_FULL_DUPLEX, 1));
+        assert_eq!(
+            link,
+            LinkStatus { speed: Some(100_000), duplex: LinkDuplex::Full, autoneg: true, up: true }
+        );
+        assert_eq!(link.to_string(), "Link up at 100 Gbps FDX Autoneg");
+
+        let link = LinkStatus::from_raw(&raw_link(RTE_ETH_SPEED_NUM_UNKNOWN, true, 0, 0));
+        assert_eq!(link.speed, None);
+        assert_eq!(link.to_string(), "Link up at Unknown HDX Fixed");
+        assert_eq!(LinkStatus::from_raw(&raw_link(2_500, true, 1, 0)).to_string(), "Link up at 2500 Mbps FDX Fixed");
+        assert_eq!(LinkStatus::from_raw(&raw_link(0, false, 0, 0)).to_string(), "Link down");
+    }
+
+    #[test]
+    fn event_type_raw() {
+        for ty in PortEventType::ALL {
+            assert_eq!(PortEventType::from_raw(ty.to_raw()), Some(ty));
+        }
+        assert_eq!(PortEventType::from_raw(rte_eth_event_type_RTE_ETH_EVENT_INTR_RMV), Some(PortEventType::Removed));
+        assert_eq!(PortEventType::from_raw(0), None);
+    }
+}
@@ -0,0 +1,806 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr::{null_mut, NonNull};
+use std::slice;
+use crate::port::mempool::Mempool;
+use crate::dpdk_raw::rte_ethdev::rust_pktmbuf_alloc_bulk;
+use crate::dpdk_raw::rte_mbuf::{
+    rte_mbuf,
+    rte_pktmbuf_free_bulk,
+    RTE_MBUF_F_RX_VLAN,
+    RTE_MBUF_F_RX_RSS_HASH,
+    RTE_MBUF_F_RX_QINQ,
+    RTE_MBUF_F_TX_IPV4,
+    RTE_MBUF_F_TX_IPV6,
+    RTE_MBUF_F_TX_IP_CKSUM,
+    RTE_MBUF_F_TX_TCP_CKSUM,
+    RTE_MBUF_F_TX_UDP_CKSUM,
+    RTE_MBUF_F_TX_L4_MASK,
+    RTE_MBUF_F_TX_TCP_SEG,
+    RTE_MBUF_F_TX_UDP_SEG,
+};
+
+const IPPROTO_TCP: u8 = 6;
+const IPPROTO_UDP: u8 = 17;
+
+/// L4 checksum requested with [`Mbuf::offload_cksum`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum L4Cksum {
+    None,
+    Tcp,
+    Udp,
+}
+
+// IP header fields used by the Tx offload helpers.
+struct IpHeader {
+    ipv4: bool,
+    len: u16,
+    proto: u8,
+}
+
+/// One's complement sum of big endian 16 bit words added to `sum`, `rte_raw_cksum`.
+pub fn raw_cksum(sum: u32, data: &[u8]) -> u32 {
+    data.chunks(2).fold(sum, |sum, word| {
+        sum + u16::from_be_bytes([word[0], *word.get(1).unwrap_or(&0)]) as u32
+    })
+}
+
+/// Folds a [`raw_cksum`] sum to 16 bits.
+pub fn fold_cksum(mut sum: u32) -> u16 {
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    sum as u16
+}
+
+// Pseudo header checksum for L4 checksum offload, `rte_ipv4_phdr_cksum` and
+// `rte_ipv6_phdr_cksum`. TSO packets exclude the length.
+fn phdr_cksum(ip: &[u8], hdr: &IpHeader, tso: bool) -> u16 {
+    let mut sum = if hdr.ipv4 { raw_cksum(0, &ip[12..20]) } else { raw_cksum(0, &ip[8..40]) };
+    sum += hdr.proto as u32;
+    if !tso {
+        sum += if hdr.ipv4 {
+            u16::from_be_bytes([ip[2], ip[3]]).saturating_sub(hdr.len) as u32
+        } else {
+            u16::from_be_bytes([ip[4], ip[5]]) as u32
+        };
+    }
+    fold_cksum(sum)
+}
+
+/// Owned packet buffer.
+///
+/// # Overview
+///
+/// `Mbuf` owns a single `rte_mbuf` chain and returns it to its mempool when dropped.
+/// The packet is handed over to a driver with [`Mbuf::into_raw`].
+///
+/// Data accessors operate on the first segment, length modifiers follow the semantics
+/// of the DPDK `rte_pktmbuf_*` inline helpers.
+///
+/// # Example
+///
+/// ```
+/// for mbuf in batch.drain() {
+///     let eth = &mbuf.data()[0..14];
+///     println!("{} bytes, ether type {:02x}{:02x}", mbuf.pkt_len(), eth[12], eth[13]);
+/// }
+/// ```
+#[repr(transparent)]
+pub struct Mbuf {
+    m: NonNull<rte_mbuf>,
+}
+
+// An mbuf is owned by a single `Mbuf` object.
+unsafe impl Send for Mbuf {}
+
+impl Mbuf {
+    /// Takes ownership of a raw mbuf.
+    ///
+    /// # Safety
+    ///
+    /// `m` must point to a valid mbuf that is not owned by anything else.
+    pub unsafe fn from_raw(m: *mut rte_mbuf) -> Option<Self> {
+        NonNull::new(m).map(|m| Mbuf { m })
+    }
+
+    /// Releases ownership of the mbuf without freeing it.
+    pub fn into_raw(self) -> *mut rte_mbuf {
+        let m = self.m.as_ptr();
+        std::mem::forget(self);
+        m
+    }
+
+    pub fn as_ptr(&self) -> *mut rte_mbuf {
+        self.m.as_ptr()
+    }
+
+    fn raw(&self) -> &rte_mbuf {
+        unsafe { self.m.as_ref() }
+    }
+
+    fn raw_mut(&mut self) -> &mut rte_mbuf {
+        unsafe { self.m.as_mut() }
+    }
+
+    fn last_seg_mut(&mut self) -> &mut rte_mbuf {
+        let mut seg = self.m.as_ptr();
+        unsafe {
+            while !(*seg).next.is_null() {
+                seg = (*seg).next;
+            }
+            &mut *seg
+        }
+    }
+
+    fn data_off(&self) -> u16 {
+        unsafe { self.raw().__bindgen_anon_1.__bindgen_anon_1.data_off }
+    }
+
+    /// Total packet length, sum of all segments.
+    pub fn pkt_len(&self) -> u32 {
+        unsafe { self.raw().__bindgen_anon_2.__bindgen_anon_1.pkt_len }
+    }
+
+    /// Amount of data in the first segment.
+    pub fn data_len(&self) -> u16 {
+        unsafe { self.raw().__bindgen_anon_2.__bindgen_anon_1.data_len }
+    }
+
+    pub fn nb_segs(&self) -> u16 {
+        unsafe { self.raw().__bindgen_anon_1.__bindgen_anon_1.nb_segs }
+    }
+
+    /// Input port.
+    pub fn port(&self) -> u16 {
+        unsafe { self.raw().__bindgen_anon_1.__bindgen_anon_1.port }
+    }
+
+    /// Free space before the packet data in the first segment.
+    pub fn headroom(&self) -> u16 {
+        self.data_off()
+    }
+
+    /// Free space after the packet data in the last segment.
+    pub fn tailroom(&self) -> u16 {
+        let mut seg = self.m.as_ptr();
+        unsafe {
+            while !(*seg).next.is_null() {
+                seg = (*seg).next;
+            }
+            let seg = &*seg;
+            seg.__bindgen_anon_2.__bindgen_anon_1.buf_len
+                - seg.__bindgen_anon_1.__bindgen_anon_1.data_off
+                - seg.__bindgen_anon_2.__bindgen_anon_1.data_len
+        }
+    }
+
+    /// Packet data of the first segment.
+    pub fn data(&self) -> &[u8] {
+        unsafe {
+            let data = (self.raw().buf_addr as *const u8).add(self.data_off() as usize);
+            slice::from_raw_parts(data, self.data_len() as usize)
+        }
+    }
+
+    /// Mutable packet data of the first segment.
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            let data = (self.raw().buf_addr as *mut u8).add(self.data_off() as usize);
+            slice::from_raw_parts_mut(data, self.data_len() as usize)
+        }
+    }
+
+    /// Prepends `len` bytes to the packet data.
+    ///
+    /// Returns the new bytes, or `None` if there is not enough headroom.
+    pub fn prepend(&mut self, len: u16) -> Option<&mut [u8]> {
+        if len > self.headroom() {
+            return None;
+        }
+        let m = self.raw_mut();
+        unsafe {
+            m.__bindgen_anon_1.__bindgen_anon_1.data_off -= len;
+            m.__bindgen_anon_2.__bindgen_anon_1.data_len += len;
+            m.__bindgen_anon_2.__bindgen_anon_1.pkt_len += len as u32;
+        }
+        Some(&mut self.data_mut()[0..len as usize])
+    }
+
+    /// Appends `len` bytes to the last segment.
+    ///
+    /// Returns the new bytes, or `None` if there is not enough tailroom.
+    pub fn append(&mut self, len: u16) -> Option<&mut [u8]> {
+        if len > self.tailroom() {
+            return None;
+        }
+        let tail = unsafe {
+            let last = self.last_seg_mut();
+            let tail = (last.buf_addr as *mut u8).add(
+                (last.__bindgen_anon_1.__bindgen_anon_1.data_off
+                    + last.__bindgen_anon_2.__bindgen_anon_1.data_len) as usize,
+            );
+            last.__bindgen_anon_2.__bindgen_anon_1.data_len += len;
+            tail
+        };
+        unsafe { self.raw_mut().__bindgen_anon_2.__bindgen_anon_1.pkt_len += len as u32 };
+        Some(unsafe { slice::from_raw_parts_mut(tail, len as usize) })
+    }
+
+    /// Removes `len` bytes from the beginning of the packet.
+    ///
+    /// Returns the new packet data, or `None` if the first segment is shorter than `len`.
+    pub fn adj(&mut self, len: u16) -> Option<&mut [u8]> {
+        if len > self.data_len() {
+            return None;
+        }
+        let m = self.raw_mut();
+        unsafe {
+            m.__bindgen_anon_1.__bindgen_anon_1.data_off += len;
+            m.__bindgen_anon_2.__bindgen_anon_1.data_len -= len;
+            m.__bindgen_anon_2.__bindgen_anon_1.pkt_len -= len as u32;
+        }
+        Some(self.data_mut())
+    }
+
+    /// Removes `len` bytes from the end of the packet.
+    ///
+    /// Returns `None` if the last segment is shorter than `len`.
+    pub fn trim(&mut self, len: u16) -> Option<()> {
+        let last = self.last_seg_mut();
+        unsafe {
+            if len > last.__bindgen_anon_2.__bindgen_anon_1.data_len {
+                return None;
+            }
+            last.__bindgen_anon_2.__bindgen_anon_1.data_len -= len;
+            self.raw_mut().__bindgen_anon_2.__bindgen_anon_1.pkt_len -= len as u32;
+        }
+        Some(())
+    }
+
+    /// Offload flags (`RTE_MBUF_F_*`).
+    pub fn ol_flags(&self) -> u64 {
+        self.raw().ol_flags
+    }
+
+    pub fn set_ol_flags(&mut self, ol_flags: u64) {
+        self.raw_mut().ol_flags = ol_flags;
+    }
+
+    /// Packet type (`RTE_PTYPE_*`).
+    pub fn packet_type(&self) -> u32 {
+        unsafe { self.raw().__bindgen_anon_2.__bindgen_anon_1.__bindgen_anon_1.packet_type }
+    }
+
+    pub fn set_packet_type(&mut self, packet_type: u32) {
+        self.raw_mut().__bindgen_anon_2.__bindgen_anon_1.__bindgen_anon_1.packet_type = packet_type;
+    }
+
+    /// RSS hash computed by the NIC.
+    pub fn rss_hash(&self) -> Option<u32> {
+        if self.ol_flags() & RTE_MBUF_F_RX_RSS_HASH as u64 != 0 {
+            Some(unsafe { self.raw().__bindgen_anon_2.__bindgen_anon_1.__bindgen_anon_2.hash.rss })
+        } else {
+            None
+        }
+    }
+
+    /// VLAN TCI stripped by the NIC.
+    pub fn vlan_tci(&self) -> Option<u16> {
+        if self.ol_flags() & RTE_MBUF_F_RX_VLAN as u64 != 0 {
+            Some(unsafe { self.raw().__bindgen_anon_2.__bindgen_anon_1.vlan_tci })
+        } else {
+            None
+        }
+    }
+
+    /// Outer VLAN TCI stripped by the NIC.
+    pub fn vlan_tci_outer(&self) -> Option<u16> {
+        if self.ol_flags() & RTE_MBUF_F_RX_QINQ as u64 != 0 {
+            Some(unsafe { self.raw().__bindgen_anon_2.__bindgen_anon_1.vlan_tci_outer })
+        } else {
+            None
+        }
+    }
+
+    /// Sets VLAN TCI for Tx VLAN insertion. Requires `RTE_MBUF_F_TX_VLAN` in [`Mbuf::ol_flags`].
+    pub fn set_vlan_tci(&mut self, vlan_tci: u16) {
+        self.raw_mut().__bindgen_anon_2.__bindgen_anon_1.vlan_tci = vlan_tci;
+    }
+
+    /// Sets outer VLAN TCI for Tx QinQ insertion. Requires `RTE_MBUF_F_TX_QINQ` in [`Mbuf::ol_flags`].
+    pub fn set_vlan_tci_outer(&mut self, vlan_tci: u16) {
+        self.raw_mut().__bindgen_anon_2.__bindgen_anon_1.vlan_tci_outer = vlan_tci;
+    }
+}
+
+impl Mbuf {
+    /// Sets L2, L3 and L4 header lengths used by Tx offloads.
+    pub fn set_tx_offload_len(&mut self, l2_len: u16, l3_len: u16, l4_len: u16) {
+        let tx_offload = unsafe { &mut self.raw_mut().__bindgen_anon_3.__bindgen_anon_1 };
+        tx_offload.set_l2_len(l2_len as u64);
+        tx_offload.set_l3_len(l3_len as u64);
+        tx_offload.set_l4_len(l4_len as u64);
+    }
+
+    /// Maximal TCP segment size requested with [`Mbuf::offload_tso`].
+    pub fn tso_segsz(&self) -> u16 {
+        unsafe { self.raw().__bindgen_anon_3.__bindgen_anon_1.tso_segsz() as u16 }
+    }
+
+    // Parses the IP header that follows `l2_len` bytes of L2 headers.
+    fn ip_header(&self, l2_len: u16) -> Option<IpHeader> {
+        let ip = self.data().get(l2_len as usize..)?;
+        match ip.first()? >> 4 {
+            4 if ip.len() >= 20 => {
+                let len = ((ip[0] & 0x0f) as u16) * 4;
+                (len >= 20 && ip.len() >= len as usize)
+                    .then_some(IpHeader { ipv4: true, len, proto: ip[9] })
+            }
+            6 if ip.len() >= 40 => Some(IpHeader { ipv4: false, len: 40, proto: ip[6] }),
+            _ => None,
+        }
+    }
+
+    /// Requests Tx checksum offloads for an IPv4 or IPv6 packet.
+    ///
+    /// `l2_len` is the length of L2 headers in the first segment. IPv6 extension headers
+    /// are not supported. The IPv4 header checksum is cleared if `ip_cksum` is set, the
+    /// L4 checksum field is set to the pseudo header checksum as drivers expect.
+    /// The port must be configured with matching [`crate::port::offload::TxOffloads`].
+    ///
+    /// Returns `None` if the packet does not match the requested offloads.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// mbuf.offload_cksum(14, true, L4Cksum::Udp).expect("not an IP / UDP packet");
+    /// ```
+    pub fn offload_cksum(&mut self, l2_len: u16, ip_cksum: bool, l4: L4Cksum) -> Option<()> {
+        let hdr = self.ip_header(l2_len)?;
+        let (l4_flag, min_l4_len, cksum_offset) = match l4 {
+            L4Cksum::None => (0, 0, 0),
+            L4Cksum::Tcp if hdr.proto == IPPROTO_TCP => (RTE_MBUF_F_TX_TCP_CKSUM, 20, 16),
+            L4Cksum::Udp if hdr.proto == IPPROTO_UDP => (RTE_MBUF_F_TX_UDP_CKSUM, 8, 6),
+            _ => return None,
+        };
+        let l3_offset = l2_len as usize;
+        let l4_offset = l3_offset + hdr.len as usize;
+        if self.data().len() < l4_offset + min_l4_len as usize {
+            return None;
+        }
+        let l4_len = match l4 {
+            L4Cksum::Tcp => ((self.data()[l4_offset + 12] >> 4) as u16 * 4).max(min_l4_len),
+            _ => min_l4_len,
+        };
+
+        let data = self.data_mut();
+        let mut ol_flags = if hdr.ipv4 { RTE_MBUF_F_TX_IPV4 } else { RTE_MBUF_F_TX_IPV6 };
+        if hdr.ipv4 && ip_cksum {
+            ol_flags |= RTE_MBUF_F_TX_IP_CKSUM;
+            data[l3_offset + 10..l3_offset + 12].fill(0);
+        }
+        if l4 != L4Cksum::None {
+            ol_flags |= l4_flag;
+            let cksum = phdr_cksum(&data[l3_offset..], &hdr, false);
+            data[l4_offset + cksum_offset..l4_offset + cksum_offset + 2]
+                .copy_from_slice(&cksum.to_be_bytes());
+        }
+
+        let tx_mask = RTE_MBUF_F_TX_IPV4 | RTE_MBUF_F_TX_IPV6 | RTE_MBUF_F_TX_IP_CKSUM
+            | RTE_MBUF_F_TX_L4_MASK | RTE_MBUF_F_TX_TCP_SEG | RTE_MBUF_F_TX_UDP_SEG;
+        self.set_ol_flags(self.ol_flags() & !tx_mask | ol_flags);
+        self.set_tx_offload_len(l2_len, hdr.len, l4_len);
+        Some(())
+    }
+
+    /// Requests TCP segmentation of a TCP packet into `mss` byte segments.
+    ///
+    /// IPv4 header and TCP checksums are offloaded as well. The port must be configured
+    /// with `TxOffloads::TCP_TSO`.
+    ///
+    /// Returns `None` if the packet is not TCP.
+    pub fn offload_tso(&mut self, l2_len: u16, mss: u16) -> Option<()> {
+        let hdr = self.ip_header(l2_len)?;
+        if hdr.proto != IPPROTO_TCP {
+            return None;
+        }
+        let l3_offset = l2_len as usize;
+        let l4_offset = l3_offset + hdr.len as usize;
+        let l4_len = ((*self.data().get(l4_offset + 12)? >> 4) as u16) * 4;
+        if l4_len < 20 || self.data().len() < l4_offset + l4_len as usize {
+            return None;
+        }
+
+        let data = self.data_mut();
+        let mut ol_flags = RTE_MBUF_F_TX_TCP_SEG | RTE_MBUF_F_TX_TCP_CKSUM;
+        if hdr.ipv4 {
+            ol_flags |= RTE_MBUF_F_TX_IPV4 | RTE_MBUF_F_TX_IP_CKSUM;
+            data[l3_offset + 10..l3_offset + 12].fill(0);
+        } else {
+            ol_flags |= RTE_MBUF_F_TX_IPV6;
+        }
+        let cksum = phdr_cksum(&data[l3_offset..], &hdr, true);
+        data[l4_offset + 16..l4_offset + 18].copy_from_slice(&cksum.to_be_bytes());
+
+        let tx_mask = RTE_MBUF_F_TX_IPV4 | RTE_MBUF_F_TX_IPV6 | RTE_MBUF_F_TX_IP_CKSUM
+            | RTE_MBUF_F_TX_L4_MASK | RTE_MBUF_F_TX_UDP_SEG;
+        self.set_ol_flags(self.ol_flags() & !tx_mask | ol_flags);
+        self.set_tx_offload_len(l2_len, hdr.len, l4_len);
+        unsafe { self.raw_mut().__bindgen_anon_3.__bindgen_anon_1.set_tso_segsz(mss as u64) };
+        Some(())
+    }
+}
+
+impl Drop for Mbuf {
+    fn drop(&mut self) {
+        let mut m = self.m.as_ptr();
+        unsafe { rte_pktmbuf_free_bulk(&mut m as *mut *mut rte_mbuf, 1) };
+    }
+}
+
+impl fmt::Debug for Mbuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mbuf")
+            .field("address", &self.m)
+            .field("data_off", &self.data_off())
+            .field("pkt_len", &self.pkt_len())
+            .field("data_len", &self.data_len())
+            .field("nb_segs", &self.nb_segs())
+            .field("ol_flags", &format_args!("{:#x}", self.ol_flags()))
+            .finish()
+    }
+}
+
+/// Fixed capacity batch of owned mbufs.
+///
+/// # Overview
+///
+/// `MbufBatch` is the unit of port IO: [`crate::port::DpdkPort::rx_burst`] fills it and
+/// [`crate::port::DpdkPort::tx_burst`] takes the packets it sent, leaving the unsent tail
+/// to the caller. The capacity is set at creation and the
+/// batch never reallocates, so it can be reused for every burst.
+///
+/// Mbufs left in the batch are freed when the batch is dropped or cleared.
+///
+/// # Example
+///
+/// ```
+/// let mut batch = MbufBatch::new(32);
+/// port.rx_burst(0, &mut batch)?;
+/// for mbuf in batch.iter_mut() {
+///     mbuf.data_mut()[0..6].copy_from_slice(&dst_mac);
+/// }
+/// port.tx_burst(0, &mut batch)?;
+/// // drop packets the Tx queue had no room for
+/// batch.clear();
+/// ```
+pub struct MbufBatch {
+    pkts: Box<[*mut rte_mbuf]>,
+    len: usize,
+}
+
+// Mbufs in the batch are owned by the batch.
+unsafe impl Send for MbufBatch {}
+
+impl MbufBatch {
+    pub fn new(capacity: usize) -> Self {
+        MbufBatch {
+            pkts: vec![null_mut(); capacity].into_boxed_slice(),
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.pkts.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.pkts.len()
+    }
+
+    /// Adds an mbuf to the batch. Returns the mbuf back if the batch is full.
+    pub fn push(&mut self, mbuf: Mbuf) -> Result<(), Mbuf> {
+        if self.is_full() {
+            return Err(mbuf);
+        }
+        self.pkts[self.len] = mbuf.into_raw();
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the last mbuf from the batch.
+    pub fn pop(&mut self) -> Option<Mbuf> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        unsafe { Mbuf::from_raw(self.pkts[self.len]) }
+    }
+
+    pub fn as_slice(&self) -> &[Mbuf] {
+        // `Mbuf` is a transparent non-null mbuf pointer, slots below `len` are never null.
+        unsafe { slice::from_raw_parts(self.pkts.as_ptr() as *const Mbuf, self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [Mbuf] {
+        unsafe { slice::from_raw_parts_mut(self.pkts.as_mut_ptr() as *mut Mbuf, self.len) }
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, Mbuf> {
+        self.as_slice().iter()
+    }
+
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Mbuf> {
+        self.as_mut_slice().iter_mut()
+    }
+
+    /// Moves all mbufs out of the batch.
+    pub fn drain(&mut self) -> MbufDrain<'_> {
+        let len = self.len;
+        self.len = 0;
+        MbufDrain { pkts: &self.pkts[0..len], pos: 0, _batch: PhantomData }
+    }
+
+    /// Frees all mbufs in the batch.
+    pub fn clear(&mut self) {
+        if self.len > 0 {
+            unsafe { rte_pktmbuf_free_bulk(self.pkts.as_mut_ptr(), self.len as u32) };
+            self.len = 0;
+        }
+    }
+
+    /// Appends `count` empty mbufs allocated from `mempool`, `rte_pktmbuf_alloc_bulk`.
+    ///
+    /// Returns `false` and allocates nothing if the batch has no room for `count` mbufs
+    /// or the pool has not enough free mbufs.
+    pub fn alloc(&mut self, mempool: &Mempool, count: u16) -> bool {
+        let count = count as usize;
+        if count == 0 || self.len + count > self.pkts.len() {
+            return count == 0;
+        }
+        let rc = unsafe {
+            rust_pktmbuf_alloc_bulk(
+                mempool.as_ptr() as *mut _,
+                self.pkts.as_mut_ptr().add(self.len) as *mut *mut _,
+                count as u32,
+            )
+        };
+        if rc != 0 {
+            return false;
+        }
+        self.len += count;
+        true
+    }
+
+    /// Fills the free part of the batch with received mbufs.
+    ///
+    /// `rx` is called with the first free slot and the number of free slots, and returns the
+    /// number of mbufs it stored.
+    ///
+    /// # Safety
+    ///
+    /// `rx` must store valid, owned mbufs in the slots it reports.
+    pub unsafe fn rx_with<F>(&mut self, rx: F) -> u16
+    where
+        F: FnOnce(*mut *mut rte_mbuf, u16) -> u16,
+    {
+        let room = (self.pkts.len() - self.len).min(u16::MAX as usize) as u16;
+        let nb_rx = rx(unsafe { self.pkts.as_mut_ptr().add(self.len) }, room).min(room);
+        self.len += nb_rx as usize;
+        nb_rx
+    }
+
+    /// Hands the batch mbufs over to a transmit function.
+    ///
+    /// `tx` is called with the batch mbufs and their number, and returns the number of mbufs
+    /// it took ownership of. Mbufs that were not taken stay in the batch, moved to its front
+    /// in their original order.
+    ///
+    /// # Safety
+    ///
+    /// `tx` must take ownership of exactly the reported number of leading mbufs.
+    pub unsafe fn tx_with<F>(&mut self, tx: F) -> u16
+    where
+        F: FnOnce(*mut *mut rte_mbuf, u16) -> u16,
+    {
+        let count = self.len.min(u16::MAX as usize) as u16;
+        let nb_tx = tx(self.pkts.as_mut_ptr(), count).min(count);
+        self.pkts.copy_within(nb_tx as usize..self.len, 0);
+        self.len -= nb_tx as usize;
+        nb_tx
+    }
+}
+
+impl Drop for MbufBatch {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Draining iterator returned by [`MbufBatch::drain`].
+///
+/// Mbufs that were not consumed are freed when the iterator is dropped.
+pub struct MbufDrain<'a> {
+    pkts: &'a [*mut rte_mbuf],
+    pos: usize,
+    _batch: PhantomData<&'a mut MbufBatch>,
+}
+
+impl Iterator for MbufDrain<'_> {
+    type Item = Mbuf;
+
+    fn next(&mut self) -> Option<Mbuf> {
+        let m = *self.pkts.get(self.pos)?;
+        self.pos += 1;
+        unsafe { Mbuf::from_raw(m) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.pkts.len() - self.pos;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for MbufDrain<'_> {}
+
+impl Drop for MbufDrain<'_> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::ManuallyDrop;
+
+    const BUF_LEN: u16 = 256;
+    const HEADROOM: u16 = 128;
+
+    struct TestBuf {
+        m: Box<rte_mbuf>,
+        _buf: Vec<u8>,
+    }
+
+    fn test_mbuf(data_len: u16) -> TestBuf {
+        let mut buf = vec![0u8; BUF_LEN as usize];
+        let mut m: Box<rte_mbuf> = Box::new(unsafe { std::mem::zeroed() });
+        m.buf_addr = buf.as_mut_ptr() as *mut _;
+        m.__bindgen_anon_1.__bindgen_anon_1.data_off = HEADROOM;
+        m.__bindgen_anon_1.__bindgen_anon_1.nb_segs = 1;
+        m.__bindgen_anon_2.__bindgen_anon_1.buf_len = BUF_LEN;
+        m.__bindgen_anon_2.__bindgen_anon_1.data_len = data_len;
+        m.__bindgen_anon_2.__bindgen_anon_1.pkt_len = data_len as u32;
+        TestBuf { m, _buf: buf }
+    }
+
+    #[test]
+    fn length_helpers() {
+        let mut tb = test_mbuf(64);
+        // Test mbufs are not backed by a mempool and must never be freed.
+        let mut mbuf = ManuallyDrop::new(unsafe { Mbuf::from_raw(&mut *tb.m as *mut rte_mbuf) }.unwrap());
+
+        assert_eq!(mbuf.headroom(), HEADROOM);
+        assert_eq!(mbuf.tailroom(), BUF_LEN - HEADROOM - 64);
+
+        mbuf.prepend(14).unwrap().copy_from_slice(&[0xaa; 14]);
+        assert_eq!(mbuf.pkt_len(), 78);
+        assert_eq!(mbuf.headroom(), HEADROOM - 14);
+        assert_eq!(&mbuf.data()[0..14], &[0xaa; 14]);
+        assert!(mbuf.prepend(HEADROOM).is_none());
+
+        mbuf.append(4).unwrap().copy_from_slice(&[0xbb; 4]);
+        assert_eq!(mbuf.pkt_len(), 82);
+        assert_eq!(&mbuf.data()[78..82], &[0xbb; 4]);
+        assert!(mbuf.append(BUF_LEN).is_none());
+
+        assert_eq!(mbuf.adj(14).unwrap().len(), 68);
+        assert_eq!(mbuf.headroom(), HEADROOM);
+        assert!(mbuf.adj(100).is_none());
+
+        mbuf.trim(4).unwrap();
+        assert_eq!(mbuf.pkt_len(), 64);
+        assert_eq!(mbuf.data_len(), 64);
+        assert!(mbuf.trim(65).is_none());
+    }
+
+    // IPv4 10.0.0.1 > 10.0.0.2 with a 20 bytes TCP header, or IPv6 ::1 > ::2 UDP.
+    fn test_packet(tb: &mut TestBuf, ipv6: bool) -> ManuallyDrop<Mbuf> {
+        let mut mbuf = ManuallyDrop::new(unsafe { Mbuf::from_raw(&mut *tb.m as *mut rte_mbuf) }.unwrap());
+        let data = mbuf.data_mut();
+        data.fill(0);
+        if ipv6 {
+            data[14] = 0x60;
+            data[18..20].copy_from_slice(&8u16.to_be_bytes());
+            data[20] = IPPROTO_UDP;
+            data[37] = 1;
+            data[53] = 2;
+        } else {
+            data[14] = 0x45;
+            data[16..18].copy_from_slice(&40u16.to_be_bytes());
+            data[23] = IPPROTO_TCP;
+            data[24..26].copy_from_slice(&[0xde, 0xad]);
+            data[26..30].copy_from_slice(&[10, 0, 0, 1]);
+            data[30..34].copy_from_slice(&[10, 0, 0, 2]);
+            data[46] = 5 << 4;
+        }
+        mbuf
+    }
+
+    #[test]
+    fn tx_cksum_offload() {
+        let mut tb = test_mbuf(64);
+        let mut mbuf = test_packet(&mut tb, false);
+        assert!(mbuf.offload_cksum(14, true, L4Cksum::Udp).is_none());
+        mbuf.offload_cksum(14, true, L4Cksum::Tcp).unwrap();
+        assert_eq!(
+            mbuf.ol_flags(),
+            RTE_MBUF_F_TX_IPV4 | RTE_MBUF_F_TX_IP_CKSUM | RTE_MBUF_F_TX_TCP_CKSUM
+        );
+        let tx_offload = unsafe { tb.m.__bindgen_anon_3.__bindgen_anon_1 };
+        assert_eq!((tx_offload.l2_len(), tx_offload.l3_len(), tx_offload.l4_len()), (14, 20, 20));
+        let data = mbuf.data();
+        assert_eq!(&data[24..26], &[0, 0]);
+        // 0x0a00 + 0x0001 + 0x0a00 + 0x0002 + proto 6 + TCP length 20
+        assert_eq!(&data[50..52], &[0x14, 0x1d]);
+
+        mbuf.offload_tso(14, 1400).unwrap();
+        assert_eq!(mbuf.ol_flags() & RTE_MBUF_F_TX_TCP_SEG, RTE_MBUF_F_TX_TCP_SEG);
+        assert_eq!(mbuf.tso_segsz(), 1400);
+        assert_eq!(&mbuf.data()[50..52], &[0x14, 0x09]);
+
+        let mut tb = test_mbuf(64);
+        let mut mbuf = test_packet(&mut tb, true);
+        assert!(mbuf.offload_tso(14, 1400).is_none());
+        mbuf.offload_cksum(14, true, L4Cksum::Udp).unwrap();
+        assert_eq!(mbuf.ol_flags(), RTE_MBUF_F_TX_IPV6 | RTE_MBUF_F_TX_UDP_CKSUM);
+        // ::1 + ::2 + length 8 + proto 17
+        assert_eq!(&mbuf.data()[60..62], &[0x00, 0x1c]);
+        assert!(mbuf.offload_cksum(20, false, L4Cksum::None).is_none());
+    }
+
+    #[test]
+    fn offload_accessors() {
+        let mut tb = test_mbuf(64);
+        tb.m.__bindgen_anon_2.__bindgen_anon_1.__bindgen_anon_2.hash.rss = 0x1234;
+        let mut mbuf = ManuallyDrop::new(unsafe { Mbuf::from_raw(&mut *tb.m as *mut rte_mbuf) }.unwrap());
+        mbuf.set_vlan_tci(100);
+
+        assert_eq!(mbuf.vlan_tci(), None);
+        assert_eq!(mbuf.rss_hash(), None);
+        mbuf.set_ol_flags((RTE_MBUF_F_RX_VLAN | RTE_MBUF_F_RX_RSS_HASH) as u64);
+        assert_eq!(mbuf.vlan_tci(), Some(100));
+        assert_eq!(mbuf.rss_hash(), Some(0x1234));
+        assert_eq!(mbuf.vlan_tci_outer(), None);
+    }
+
+    #[test]
+    fn tx_with_keeps_unsent_tail() {
+        // Tx callback never dereferences the mbufs.
+        let fake: Vec<*mut rte_mbuf> = (1..=5usize).map(|i| (i * 8) as *mut rte_mbuf).collect();
+        let mut batch = ManuallyDrop::new(MbufBatch::new(8));
+        let nb_rx = unsafe {
+            batch.rx_with(|slots, room| {
+                assert_eq!(room, 8);
+                std::ptr::copy_nonoverlapping(fake.as_ptr(), slots, fake.len());
+                fake.len() as u16
+            })
+        };
+        assert_eq!(nb_rx, 5);
+
+        let nb_tx = unsafe { batch.tx_with(|_, count| count - 3) };
+        assert_eq!(nb_tx, 2);
+        assert_eq!(batch.len(), 3);
+        let tail: Vec<*mut rte_mbuf> = batch.iter().map(Mbuf::as_ptr).collect();
+        assert_eq!(tail, &fake[2..]);
+    }
+}
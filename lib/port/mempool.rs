@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex};
+use crate::port::error::PortError;
+use crate::dpdk_raw::rte_mbuf::rte_pktmbuf_pool_create;
+use crate::dpdk_raw::rte_mempool::{
+    rte_mempool,
+    rte_mempool_free,
+    rte_mempool_lookup,
+    rte_mempool_avail_count,
+    rte_mempool_in_use_count,
+};
+use crate::dpdk_raw::rte_ethdev::{
+    rte_eth_dev_socket_id,
+    SOCKET_ID_ANY,
+};
+
+/// Maximal mempool name length, without the terminating NUL.
+pub const MEMPOOL_NAME_MAX_LEN: usize = 25;
+
+/// DPDK memory pool.
+///
+/// # Overview
+///
+/// `Mempool` owns an `rte_mempool` created with [`Mempool::pktmbuf`] and frees it with
+/// `rte_mempool_free` when dropped. Pools found with [`Mempool::lookup`] are not owned
+/// and are left intact.
+///
+/// A pool must outlive every port queue and every mbuf allocated from it.
+/// Ports keep their pools alive by holding `Arc<Mempool>` in [`crate::port::DpdkPortConf`].
+/// Mbufs keep their pool alive on their own: an owned pool dropped while some of its
+/// mbufs are still in use is retired and freed once all of them are back, on a later
+/// pool creation or drop, or with [`Mempool::release_retired`].
+///
+/// # Example
+///
+/// ```
+/// let pool = Mempool::pktmbuf("rx_pool", 8191, 256, 0, RTE_MBUF_DEFAULT_BUF_SIZE as u16, 0)?;
+/// println!("{}: {} free mbufs", pool.name(), pool.avail_count());
+/// ```
+pub struct Mempool {
+    pool: NonNull<rte_mempool>,
+    name: String,
+    // Releases the pool on drop, `None` for pools that are not owned.
+    release: Option<PoolRelease>,
+}
+
+// Owned pool release functions, kept as pointers so that the drop glue of pools that
+// are not owned does not reference DPDK.
+#[derive(Clone, Copy)]
+struct PoolRelease {
+    free: unsafe extern "C" fn(*mut rte_mempool),
+    in_use_count: unsafe extern "C" fn(*const rte_mempool) -> u32,
+}
+
+const OWNED_POOL: PoolRelease = PoolRelease {
+    free: rte_mempool_free,
+    in_use_count: rte_mempool_in_use_count,
+};
+
+// Owned pool dropped while some of its mbufs were in use.
+struct RetiredPool {
+    pool: NonNull<rte_mempool>,
+    release: PoolRelease,
+}
+
+// Retired pools are not used by anything but their outstanding mbufs.
+unsafe impl Send for RetiredPool {}
+
+impl RetiredPool {
+    // Frees the pool if all its mbufs are back.
+    fn try_release(&self) -> bool {
+        let in_use = unsafe { (self.release.in_use_count)(self.pool.as_ptr()) };
+        if in_use == 0 {
+            unsafe { (self.release.free)(self.pool.as_ptr()) };
+        }
+        in_use == 0
+    }
+}
+
+static RETIRED_POOLS: Mutex<Vec<RetiredPool>> = Mutex::new(Vec::new());
+
+// Mempool get / put operations are multi-producer / multi-consumer safe for the default
+// mempool handlers, and the per-lcore caches are only touched by their own lcore.
+// Creation and destruction go through `&mut self` / `Drop`.
+unsafe impl Send for Mempool {}
+unsafe impl Sync for Mempool {}
+
+impl Mempool {
+    /// Creates a packet mbuf pool with `rte_pktmbuf_pool_create`.
+    ///
+    /// Fails if the name is too long or already used by another pool.
+    pub fn pktmbuf(
+        name: &str,
+        capacity: u32,
+        cache_size: u32,
+        priv_size: u16,
+        data_room_size: u16,
+        socket_id: i32,
+    ) -> Result<Self, PortError> {
+        // a retired pool keeps its name until it is freed
+        Mempool::release_retired();
+        if name.len() > MEMPOOL_NAME_MAX_LEN {
+            return Err(PortError::MempoolName { name: name.to_string(), reason: "name too long" });
+        }
+        let pool_name = CString::new(name).map_err(|_| PortError::MempoolName {
+            name: name.to_string(),
+            reason: "name contains NUL",
+        })?;
+        if !unsafe { rte_mempool_lookup(pool_name.as_ptr()) }.is_null() {
+            return Err(PortError::MempoolName { name: name.to_string(), reason: "name already used" });
+        }
+
+        let pool = unsafe {
+            rte_pktmbuf_pool_create(
+                pool_name.as_ptr(),
+                capacity,
+                cache_size,
+                priv_size,
+                data_room_size,
+                socket_id as _,
+            )
+        };
+
+        match NonNull::new(pool as *mut rte_mempool) {
+            Some(pool) => Ok(Mempool { pool, name: name.to_string(), release: Some(OWNED_POOL) }),
+            None => Err(PortError::Mempool {
+                call: "rte_pktmbuf_pool_create",
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// Finds an existing pool by name. The returned object does not free the pool.
+    pub fn lookup(name: &str) -> Option<Self> {
+        let pool_name = CString::new(name).ok()?;
+        let pool = NonNull::new(unsafe { rte_mempool_lookup(pool_name.as_ptr()) })?;
+        Some(Mempool { pool, name: name.to_string(), release: None })
+    }
+
+    /// Wraps a raw mempool pointer.
+    ///
+    /// # Safety
+    ///
+    /// `pool` must point to a valid `rte_mempool`. If `owned` is true the pool is freed on drop
+    /// and must not be freed elsewhere.
+    pub unsafe fn from_raw(pool: NonNull<rte_mempool>, owned: bool) -> Self {
+        let name = unsafe { CStr::from_ptr(pool.as_ref().name.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+        Mempool { pool, name, release: if owned { Some(OWNED_POOL) } else { None } }
+    }
+
+    /// Releases ownership of the pool without freeing it.
+    pub fn into_raw(mut self) -> *mut rte_mempool {
+        self.release = None;
+        self.pool.as_ptr()
+    }
+
+    /// Frees the retired pools whose mbufs are all back, returns the number of pools
+    /// still waiting for their mbufs.
+    pub fn release_retired() -> usize {
+        let mut retired = RETIRED_POOLS.lock().unwrap();
+        retired.retain(|pool| !pool.try_release());
+        retired.len()
+    }
+
+    pub fn as_ptr(&self) -> *mut rte_mempool {
+        self.pool.as_ptr()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// NUMA socket the pool memory was allocated on.
+    pub fn socket_id(&self) -> i32 {
+        unsafe { self.pool.as_ref().socket_id }
+    }
+
+    /// Total number of elements in the pool.
+    pub fn capacity(&self) -> u32 {
+        unsafe { self.pool.as_ref().size }
+    }
+
+    /// Number of elements available for allocation, including per-lcore caches.
+    ///
+    /// Browses all lcore caches, do not use in the data path.
+    pub fn avail_count(&self) -> u32 {
+        unsafe { rte_mempool_avail_count(self.pool.as_ptr()) }
+    }
+
+    /// Number of elements allocated from the pool.
+    ///
+    /// Browses all lcore caches, do not use in the data path.
+    pub fn in_use_count(&self) -> u32 {
+        unsafe { rte_mempool_in_use_count(self.pool.as_ptr()) }
+    }
+}
+
+impl Drop for Mempool {
+    fn drop(&mut self) {
+        if let Some(release) = self.release {
+            let pool = RetiredPool { pool: self.pool, release };
+            let mut retired = RETIRED_POOLS.lock().unwrap();
+            retired.retain(|pool| !pool.try_release());
+            if !pool.try_release() {
+                retired.push(pool);
+            }
+        }
+    }
+}
+
+/// Per-socket registry of mbuf pools.
+///
+/// # Overview
+///
+/// `MempoolRegistry` creates one packet mbuf pool per NUMA socket on the first request
+/// and hands out shared references afterwards. Pool names are derived from the registry
+/// prefix and the socket ID, so pools of different sockets never collide.
+///
+/// # Example
+///
+/// ```
+/// let registry = MempoolRegistry::new("runpmd", 8191, 256, 0, RTE_MBUF_DEFAULT_BUF_SIZE as u16);
+/// let pool = registry.for_port(port_id)?;
+/// ```
+pub struct MempoolRegistry {
+    prefix: String,
+    capacity: u32,
+    cache_size: u32,
+    priv_size: u16,
+    data_room_size: u16,
+    pools: Mutex<HashMap<i32, Arc<Mempool>>>,
+}
+
+impl MempoolRegistry {
+    pub fn new(
+        prefix: &str,
+        capacity: u32,
+        cache_size: u32,
+        priv_size: u16,
+        data_room_size: u16,
+    ) -> Self {
+        MempoolRegistry {
+            prefix: prefix.to_string(),
+            capacity,
+            cache_size,
+            priv_size,
+            data_room_size,
+            pools: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the pool name used for the socket.
+    pub fn pool_name(&self, socket_id: i32) -> String {
+        if socket_id == SOCKET_ID_ANY {
+            format!("{}_any", self.prefix)
+        } else {
+            format!("{}_{}", self.prefix, socket_id)
+        }
+    }
+
+    /// Returns the socket pool, creating it on the first call.
+    pub fn get(&self, socket_id: i32) -> Result<Arc<Mempool>, PortError> {
+        let mut pools = self.pools.lock().unwrap();
+        if let Some(pool) = pools.get(&socket_id) {
+            return Ok(pool.clone());
+        }
+
+        let pool = Arc::new(Mempool::pktmbuf(
+            &self.pool_name(socket_id),
+            self.capacity,
+            self.cache_size,
+            self.priv_size,
+            self.data_room_size,
+            socket_id,
+        )?);
+        pools.insert(socket_id, pool.clone());
+        Ok(pool)
+    }
+
+    /// Returns the pool of the socket the port is attached to.
+    pub fn for_port(&self, port_id: u16) -> Result<Arc<Mempool>, PortError> {
+        self.get(unsafe { rte_eth_dev_socket_id(port_id) })
+    }
+
+    /// Returns all pools created so far.
+    pub fn pools(&self) -> Vec<Arc<Mempool>> {
+        self.pools.lock().unwrap().values().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+impl Mempool {
+    /// Pool placeholder for tests that never touch the pool memory.
+    pub(crate) fn dangling(name: &str) -> Self {
+        Mempool { pool: NonNull::dangling(), name: name.to_string(), release: None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    static IN_USE: AtomicU32 = AtomicU32::new(0);
+    static FREED: AtomicU32 = AtomicU32::new(0);
+
+    unsafe extern "C" fn test_free(_pool: *mut rte_mempool) {
+        FREED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    unsafe extern "C" fn test_in_use_count(_pool: *const rte_mempool) -> u32 {
+        IN_USE.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn pool_released_after_its_mbufs() {
+        let release = PoolRelease { free: test_free, in_use_count: test_in_use_count };
+        IN_USE.store(2, Ordering::Relaxed);
+        drop(Mempool { pool: NonNull::dangling(), name: "retired".to_string(), release: Some(release) });
+        assert_eq!(FREED.load(Ordering::Relaxed), 0);
+        assert_eq!(Mempool::release_retired(), 1);
+
+        IN_USE.store(0, Ordering::Relaxed);
+        assert_eq!(Mempool::release_retired(), 0);
+        assert_eq!(FREED.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn registry_pool_names() {
+        let registry = MempoolRegistry::new("runpmd", 1024, 0, 0, 2048);
+        assert_eq!(registry.pool_name(0), "runpmd_0");
+        assert_eq!(registry.pool_name(1), "runpmd_1");
+        assert_eq!(registry.pool_name(SOCKET_ID_ANY), "runpmd_any");
+    }
+}
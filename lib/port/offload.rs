@@ -0,0 +1,214 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_char;
+use bitflags::{bitflags, Flags};
+use crate::dpdk_raw::rte_ethdev::{
+    rte_eth_dev_rx_offload_name,
+    rte_eth_dev_tx_offload_name,
+    RTE_ETH_RX_OFFLOAD_VLAN_STRIP,
+    RTE_ETH_RX_OFFLOAD_VLAN,
+    RTE_ETH_RX_OFFLOAD_IPV4_CKSUM,
+    RTE_ETH_RX_OFFLOAD_UDP_CKSUM,
+    RTE_ETH_RX_OFFLOAD_TCP_CKSUM,
+    RTE_ETH_RX_OFFLOAD_TCP_LRO,
+    RTE_ETH_RX_OFFLOAD_QINQ_STRIP,
+    RTE_ETH_RX_OFFLOAD_OUTER_IPV4_CKSUM,
+    RTE_ETH_RX_OFFLOAD_MACSEC_STRIP,
+    RTE_ETH_RX_OFFLOAD_VLAN_FILTER,
+    RTE_ETH_RX_OFFLOAD_VLAN_EXTEND,
+    RTE_ETH_RX_OFFLOAD_SCATTER,
+    RTE_ETH_RX_OFFLOAD_TIMESTAMP,
+    RTE_ETH_RX_OFFLOAD_SECURITY,
+    RTE_ETH_RX_OFFLOAD_KEEP_CRC,
+    RTE_ETH_RX_OFFLOAD_SCTP_CKSUM,
+    RTE_ETH_RX_OFFLOAD_OUTER_UDP_CKSUM,
+    RTE_ETH_RX_OFFLOAD_RSS_HASH,
+    RTE_ETH_RX_OFFLOAD_BUFFER_SPLIT,
+    RTE_ETH_RX_OFFLOAD_CHECKSUM,
+    RTE_ETH_TX_OFFLOAD_VLAN_INSERT,
+    RTE_ETH_TX_OFFLOAD_IPV4_CKSUM,
+    RTE_ETH_TX_OFFLOAD_UDP_CKSUM,
+    RTE_ETH_TX_OFFLOAD_TCP_CKSUM,
+    RTE_ETH_TX_OFFLOAD_SCTP_CKSUM,
+    RTE_ETH_TX_OFFLOAD_TCP_TSO,
+    RTE_ETH_TX_OFFLOAD_UDP_TSO,
+    RTE_ETH_TX_OFFLOAD_OUTER_IPV4_CKSUM,
+    RTE_ETH_TX_OFFLOAD_QINQ_INSERT,
+    RTE_ETH_TX_OFFLOAD_VXLAN_TNL_TSO,
+    RTE_ETH_TX_OFFLOAD_GRE_TNL_TSO,
+    RTE_ETH_TX_OFFLOAD_IPIP_TNL_TSO,
+    RTE_ETH_TX_OFFLOAD_GENEVE_TNL_TSO,
+    RTE_ETH_TX_OFFLOAD_MACSEC_INSERT,
+    RTE_ETH_TX_OFFLOAD_MT_LOCKFREE,
+    RTE_ETH_TX_OFFLOAD_MULTI_SEGS,
+    RTE_ETH_TX_OFFLOAD_MBUF_FAST_FREE,
+    RTE_ETH_TX_OFFLOAD_SECURITY,
+    RTE_ETH_TX_OFFLOAD_UDP_TNL_TSO,
+    RTE_ETH_TX_OFFLOAD_IP_TNL_TSO,
+    RTE_ETH_TX_OFFLOAD_OUTER_UDP_CKSUM,
+    RTE_ETH_TX_OFFLOAD_SEND_ON_TIMESTAMP,
+};
+
+bitflags! {
+    /// Rx offloads (`RTE_ETH_RX_OFFLOAD_*`).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct RxOffloads: u64 {
+        const VLAN_STRIP = RTE_ETH_RX_OFFLOAD_VLAN_STRIP as u64;
+        const IPV4_CKSUM = RTE_ETH_RX_OFFLOAD_IPV4_CKSUM as u64;
+        const UDP_CKSUM = RTE_ETH_RX_OFFLOAD_UDP_CKSUM as u64;
+        const TCP_CKSUM = RTE_ETH_RX_OFFLOAD_TCP_CKSUM as u64;
+        const TCP_LRO = RTE_ETH_RX_OFFLOAD_TCP_LRO as u64;
+        const QINQ_STRIP = RTE_ETH_RX_OFFLOAD_QINQ_STRIP as u64;
+        const OUTER_IPV4_CKSUM = RTE_ETH_RX_OFFLOAD_OUTER_IPV4_CKSUM as u64;
+        const MACSEC_STRIP = RTE_ETH_RX_OFFLOAD_MACSEC_STRIP as u64;
+        const VLAN_FILTER = RTE_ETH_RX_OFFLOAD_VLAN_FILTER as u64;
+        const VLAN_EXTEND = RTE_ETH_RX_OFFLOAD_VLAN_EXTEND as u64;
+        const SCATTER = RTE_ETH_RX_OFFLOAD_SCATTER as u64;
+        const TIMESTAMP = RTE_ETH_RX_OFFLOAD_TIMESTAMP as u64;
+        const SECURITY = RTE_ETH_RX_OFFLOAD_SECURITY as u64;
+        const KEEP_CRC = RTE_ETH_RX_OFFLOAD_KEEP_CRC as u64;
+        const SCTP_CKSUM = RTE_ETH_RX_OFFLOAD_SCTP_CKSUM as u64;
+        const OUTER_UDP_CKSUM = RTE_ETH_RX_OFFLOAD_OUTER_UDP_CKSUM as u64;
+        const RSS_HASH = RTE_ETH_RX_OFFLOAD_RSS_HASH as u64;
+        const BUFFER_SPLIT = RTE_ETH_RX_OFFLOAD_BUFFER_SPLIT as u64;
+
+        /// IPv4, UDP and TCP checksum.
+        const CHECKSUM = RTE_ETH_RX_OFFLOAD_CHECKSUM as u64;
+        /// VLAN strip, filter, extend and QinQ strip.
+        const VLAN = RTE_ETH_RX_OFFLOAD_VLAN as u64;
+    }
+}
+
+bitflags! {
+    /// Tx offloads (`RTE_ETH_TX_OFFLOAD_*`).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct TxOffloads: u64 {
+        const VLAN_INSERT = RTE_ETH_TX_OFFLOAD_VLAN_INSERT as u64;
+        const IPV4_CKSUM = RTE_ETH_TX_OFFLOAD_IPV4_CKSUM as u64;
+        const UDP_CKSUM = RTE_ETH_TX_OFFLOAD_UDP_CKSUM as u64;
+        const TCP_CKSUM = RTE_ETH_TX_OFFLOAD_TCP_CKSUM as u64;
+        const SCTP_CKSUM = RTE_ETH_TX_OFFLOAD_SCTP_CKSUM as u64;
+        const TCP_TSO = RTE_ETH_TX_OFFLOAD_TCP_TSO as u64;
+        const UDP_TSO = RTE_ETH_TX_OFFLOAD_UDP_TSO as u64;
+        const OUTER_IPV4_CKSUM = RTE_ETH_TX_OFFLOAD_OUTER_IPV4_CKSUM as u64;
+        const QINQ_INSERT = RTE_ETH_TX_OFFLOAD_QINQ_INSERT as u64;
+        const VXLAN_TNL_TSO = RTE_ETH_TX_OFFLOAD_VXLAN_TNL_TSO as u64;
+        const GRE_TNL_TSO = RTE_ETH_TX_OFFLOAD_GRE_TNL_TSO as u64;
+        const IPIP_TNL_TSO = RTE_ETH_TX_OFFLOAD_IPIP_TNL_TSO as u64;
+        const GENEVE_TNL_TSO = RTE_ETH_TX_OFFLOAD_GENEVE_TNL_TSO as u64;
+        const MACSEC_INSERT = RTE_ETH_TX_OFFLOAD_MACSEC_INSERT as u64;
+        const MT_LOCKFREE = RTE_ETH_TX_OFFLOAD_MT_LOCKFREE as u64;
+        const MULTI_SEGS = RTE_ETH_TX_OFFLOAD_MULTI_SEGS as u64;
+        const MBUF_FAST_FREE = RTE_ETH_TX_OFFLOAD_MBUF_FAST_FREE as u64;
+        const SECURITY = RTE_ETH_TX_OFFLOAD_SECURITY as u64;
+        const UDP_TNL_TSO = RTE_ETH_TX_OFFLOAD_UDP_TNL_TSO as u64;
+        const IP_TNL_TSO = RTE_ETH_TX_OFFLOAD_IP_TNL_TSO as u64;
+        const OUTER_UDP_CKSUM = RTE_ETH_TX_OFFLOAD_OUTER_UDP_CKSUM as u64;
+        const SEND_ON_TIMESTAMP = RTE_ETH_TX_OFFLOAD_SEND_ON_TIMESTAMP as u64;
+
+        /// IPv4, UDP and TCP checksum.
+        const CHECKSUM = (RTE_ETH_TX_OFFLOAD_IPV4_CKSUM
+            | RTE_ETH_TX_OFFLOAD_UDP_CKSUM
+            | RTE_ETH_TX_OFFLOAD_TCP_CKSUM) as u64;
+    }
+}
+
+fn offload_names(
+    bits: impl Iterator<Item = u64>,
+    name: unsafe extern "C" fn(u64) -> *const c_char,
+) -> Vec<String> {
+    bits.map(|bit| unsafe { CStr::from_ptr(name(bit)) }.to_string_lossy().into_owned())
+        .collect()
+}
+
+impl RxOffloads {
+    /// DPDK offload names, `rte_eth_dev_rx_offload_name`.
+    pub fn names(self) -> Vec<String> {
+        offload_names(self.iter().map(|offload| offload.bits()), rte_eth_dev_rx_offload_name)
+    }
+}
+
+impl TxOffloads {
+    /// DPDK offload names, `rte_eth_dev_tx_offload_name`.
+    pub fn names(self) -> Vec<String> {
+        offload_names(self.iter().map(|offload| offload.bits()), rte_eth_dev_tx_offload_name)
+    }
+}
+
+/// Formats DPDK offload names separated by spaces.
+impl fmt::Display for RxOffloads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.names().join(" "))
+    }
+}
+
+/// Formats DPDK offload names separated by spaces.
+impl fmt::Display for TxOffloads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.names().join(" "))
+    }
+}
+
+/// Resolves the offloads to enable on a device.
+///
+/// `required` offloads must be in the device capabilities `capa`, `optional` offloads
+/// are enabled only if the device supports them.
+/// Returns the offloads to enable, or the unsupported required offloads.
+///
+/// # Example
+///
+/// ```
+/// let tx = offload::negotiate(
+///     TxOffloads::MULTI_SEGS,
+///     TxOffloads::CHECKSUM | TxOffloads::TCP_TSO,
+///     dev_info.tx_offload_capa,
+/// )?;
+/// ```
+pub fn negotiate<F>(required: F, optional: F, capa: u64) -> Result<F, F>
+where
+    F: Flags<Bits = u64> + Copy,
+{
+    let capa = F::from_bits_retain(capa);
+    let unsupported = required.difference(capa);
+    if !unsupported.is_empty() {
+        return Err(unsupported);
+    }
+    Ok(required.union(optional.intersection(capa)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiate_offloads() {
+        let capa = (RxOffloads::CHECKSUM | RxOffloads::RSS_HASH).bits();
+        assert_eq!(
+            negotiate(RxOffloads::RSS_HASH, RxOffloads::CHECKSUM | RxOffloads::TCP_LRO, capa),
+            Ok(RxOffloads::RSS_HASH | RxOffloads::CHECKSUM)
+        );
+        assert_eq!(
+            negotiate(RxOffloads::TCP_LRO | RxOffloads::IPV4_CKSUM, RxOffloads::empty(), capa),
+            Err(RxOffloads::TCP_LRO)
+        );
+
+        let capa = TxOffloads::MULTI_SEGS.bits() | (1 << 63);
+        assert_eq!(
+            negotiate(TxOffloads::empty(), TxOffloads::all(), capa),
+            Ok(TxOffloads::MULTI_SEGS)
+        );
+    }
+
+    #[test]
+    fn offload_flags() {
+        assert_eq!(
+            RxOffloads::CHECKSUM,
+            RxOffloads::IPV4_CKSUM | RxOffloads::UDP_CKSUM | RxOffloads::TCP_CKSUM
+        );
+        assert!(RxOffloads::VLAN.contains(RxOffloads::QINQ_STRIP));
+        assert_eq!(
+            TxOffloads::CHECKSUM.iter_names().map(|(name, _)| name).collect::<Vec<_>>(),
+            ["IPV4_CKSUM", "UDP_CKSUM", "TCP_CKSUM"]
+        );
+    }
+}
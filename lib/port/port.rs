@@ -1,21 +1,42 @@
 pub mod raw_port;
 pub mod init;
+pub mod error;
+pub mod builder;
+pub mod mempool;
+pub mod mbuf;
+pub mod tx_buffer;
+pub mod rss;
+pub mod stats;
+pub mod link;
+pub mod ether;
+pub mod vlan;
+pub mod offload;
+pub mod queue;
+pub mod io_engine;
 
-use std::ffi::{
-    CString
-};
+use std::ptr::NonNull;
 use std::sync::Arc;
-use crate::dpdk_raw::rte_mbuf::{
-    rte_pktmbuf_pool_create,
-    rte_mbuf,
-};
+use std::sync::mpsc::Receiver;
+use crate::port::error::PortError;
+use crate::port::builder::DpdkPortConfBuilder;
+use crate::port::mempool::Mempool;
+use crate::port::mbuf::MbufBatch;
+use crate::port::queue::{RxQueue, TxQueue};
+use crate::port::rss::RssConfig;
+use crate::port::stats::{PortStats, XStat};
+use crate::port::ether::EtherAddr;
+use crate::port::vlan::VlanConfig;
+use crate::port::offload::{RxOffloads, TxOffloads};
+use crate::port::link::{EventSubscription, LinkStatus, PortEvent, PortEventType};
 
+use crate::dpdk_raw::rte_mempool::rte_mempool;
 use crate::dpdk_raw::rte_ethdev::{
     rte_eth_conf,
     rte_eth_txconf,
     rte_eth_rxconf,
+    rte_eth_rxq_info,
+    rte_eth_txq_info,
     rte_eth_dev_info,
-    rte_mempool,
     rte_eth_dev_info_get,
 };
 
@@ -28,26 +49,22 @@ use crate::dpdk_raw::rte_ethdev::{
 /// - Transmit and receive configurations, including queue settings and memory pools.
 /// - Details about the number of queues and descriptors for transmission and reception.
 ///
-/// This struct is typically instantiated using the [`DpdkPortConf::new_from`] method.
+/// This struct is typically instantiated using the [`DpdkPortConf::builder`] method.
 ///
 /// # Example
 ///
 /// ```
-/// let conf = DpdkPortConf::new_from(
-///     port_id,
-///     dev_conf,
-///     tx_conf,
-///     rx_conf,
-///     8,               // Number of RX queues
-///     8,               // Number of TX queues
-///     512,             // RX descriptors
-///     512,             // TX descriptors
-///     0,               // RX queue socket ID
-///     0,               // TX queue socket ID
-///     Some(rx_mempool) // RX queue mempool
-/// ).unwrap();
+/// let conf = DpdkPortConf::builder(port_id)
+///     .rx_queues(8)
+///     .tx_queues(8)
+///     .rx_descriptors(512)
+///     .tx_descriptors(512)
+///     .socket_id(0)
+///     .rx_mempool(rx_mempool)
+///     .build()
+///     .unwrap();
 /// ```
-/// 
+///
 #[derive(Clone)]
 pub struct DpdkPortConf {
     /// Information about the DPDK Ethernet device (e.g., driver, capabilities, etc.).
@@ -83,10 +100,44 @@ pub struct DpdkPortConf {
 
     /// Memory pool associated with receive (Rx) queues.
     /// This manages the buffers used for storing incoming packets.
-    pub rxq_mempool: Option<Arc<DpdkMempool>>,
+    pub rxq_mempool: Option<Arc<Mempool>>,
+
+    /// Per-queue memory pools, indexed by Rx queue ID.
+    /// A `None` entry, or a queue beyond the vector length, uses [`DpdkPortConf::rxq_mempool`].
+    pub rxq_mempools: Vec<Option<Arc<Mempool>>>,
+
+    /// RSS configuration applied when the port is configured.
+    /// `None` enables IP hashing when the port has several Rx queues.
+    pub rss: Option<RssConfig>,
+
+    /// Rx offloads enabled when the port is configured if the device supports them.
+    /// Offloads in `dev_conf.rxmode.offloads` are always enabled.
+    pub optional_rx_offloads: RxOffloads,
+
+    /// Tx offloads enabled when the port is configured if the device supports them.
+    /// Offloads in `dev_conf.txmode.offloads` are always enabled.
+    pub optional_tx_offloads: TxOffloads,
+
+    /// VLAN configuration applied when the port is configured.
+    /// `None` keeps VLAN offloads set in `dev_conf`.
+    pub vlan: Option<VlanConfig>,
 }
 
 impl DpdkPortConf {
+    /// Returns a [`DpdkPortConfBuilder`] for the port.
+    pub fn builder(port_id: u16) -> DpdkPortConfBuilder {
+        DpdkPortConfBuilder::new(port_id)
+    }
+
+    /// Returns the memory pool used by the Rx queue.
+    pub fn rx_queue_mempool(&self, queue_id: u16) -> Option<&Arc<Mempool>> {
+        match self.rxq_mempools.get(queue_id as usize) {
+            Some(Some(mempool)) => Some(mempool),
+            _ => self.rxq_mempool.as_ref(),
+        }
+    }
+
+    #[deprecated(note = "use DpdkPortConf::builder")]
     pub fn new_from(
         port_id: u16,
         dev_conf: rte_eth_conf,
@@ -98,12 +149,15 @@ impl DpdkPortConf {
         rx_desc_num: u16,
         rxq_socket_id: u32,
         txq_socket_id: u32,
-        rxq_mempool: Option<Arc<DpdkMempool>>,
-    ) -> Result<Self, String> {
+        rxq_mempool: Option<Arc<Mempool>>,
+    ) -> Result<Self, PortError> {
         let mut dev_info:rte_eth_dev_info = unsafe { std::mem::zeroed() };
-        let _ = unsafe {
-            rte_eth_dev_info_get(port_id, &mut dev_info as *mut rte_eth_dev_info)
-        };
+        PortError::check(
+            unsafe { rte_eth_dev_info_get(port_id, &mut dev_info as *mut rte_eth_dev_info) },
+            "rte_eth_dev_info_get",
+            port_id,
+            None,
+        )?;
 
         Ok(DpdkPortConf {
             dev_info: dev_info,
@@ -117,13 +171,77 @@ impl DpdkPortConf {
             rxq_socket_id: rxq_socket_id,
             txq_socket_id: txq_socket_id,
             rxq_mempool: rxq_mempool,
+            rxq_mempools: Vec::new(),
+            rss: None,
+            optional_rx_offloads: RxOffloads::empty(),
+            optional_tx_offloads: TxOffloads::empty(),
+            vlan: None,
         })
     }
 }
 
+#[deprecated(note = "use Mempool::pktmbuf")]
+pub struct DpdkMempool {
+    pub pool: *mut rte_mempool,
+}
+
+/// Creates a packet mbuf pool that is never freed.
+#[deprecated(note = "use Mempool::pktmbuf")]
+#[allow(deprecated)]
+pub fn alloc_mbuf_pool(
+    name: &str,
+    capacity: u32,
+    cache_size: u32,
+    priv_size: u16,
+    data_root_size: u16,
+    socket: i32) -> Result<DpdkMempool, PortError> {
+    let pool = Mempool::pktmbuf(name, capacity, cache_size, priv_size, data_root_size, socket)?;
+    Ok(DpdkMempool { pool: pool.into_raw() })
+}
+
+#[allow(deprecated)]
+impl From<DpdkMempool> for Mempool {
+    /// Wraps the pool without taking ownership, as [`alloc_mbuf_pool`] pools are never freed.
+    fn from(mempool: DpdkMempool) -> Self {
+        let pool = NonNull::new(mempool.pool).expect("null DpdkMempool pool");
+        unsafe { Mempool::from_raw(pool, false) }
+    }
+}
+
 unsafe impl Send for DpdkPortConf {}
 unsafe impl Sync for DpdkPortConf {}
 
+/// Port lifecycle state.
+///
+/// ```text
+/// Unconfigured -> configure -> Configured -> start -> Started -> stop -> Stopped
+///      ^                                                 |                  |
+///      +-------------------- reset ----------------------+------------------+
+/// ```
+///
+/// `close` moves any state to `Closed`, a closed port cannot be used again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortState {
+    Unconfigured,
+    Configured,
+    Started,
+    Stopped,
+    Closed,
+}
+
+impl std::fmt::Display for PortState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PortState::Unconfigured => "unconfigured",
+            PortState::Configured => "configured",
+            PortState::Started => "started",
+            PortState::Stopped => "stopped",
+            PortState::Closed => "closed",
+        };
+        f.write_str(name)
+    }
+}
+
 /// A trait defining basic operations for managing and interacting with a DPDK port.
 ///
 /// # Overview
@@ -131,7 +249,10 @@ unsafe impl Sync for DpdkPortConf {}
 /// The `DpdkPort` trait standardizes how to operate on DPDK ports, making it possible to:
 /// - Configure the Ethernet device using [`configure`].
 /// - Start the device using [`start`].
+/// - Stop, close or reset the device using [`stop`], [`close`] and [`reset`].
 /// - Handle Rx and Tx bursts of packets using [`rx_burst`] and [`tx_burst`].
+/// - Hand out per-queue [`RxQueue`] / [`TxQueue`] handles with [`rx_queue`] and [`tx_queue`]
+///   for lock-free IO from worker threads.
 ///
 pub trait DpdkPort: Send + Sync {
     /// Returns the port ID of the DPDK port.
@@ -165,7 +286,7 @@ pub trait DpdkPort: Send + Sync {
     ///
     /// # Return Value
     /// - `Ok(())` if the configuration was applied successfully.
-    /// - `Err(String)` with a descriptive error message if the configuration failed.
+    /// - `Err(PortError)` with the failing DPDK call and queue if the configuration failed.
     ///
     /// # Example
     /// ```
@@ -174,7 +295,7 @@ pub trait DpdkPort: Send + Sync {
     ///     eprintln!("Failed to configure the port: {}", err);
     /// }
     /// ```
-    fn configure(&mut self) -> Result<(), String>;
+    fn configure(&mut self) -> Result<(), PortError>;
 
     /// Starts the DPDK Ethernet device.
     ///
@@ -183,7 +304,7 @@ pub trait DpdkPort: Send + Sync {
     ///
     /// # Return Value
     /// - `Ok(())` if the port was started successfully.
-    /// - `Err(String)` if the startup process failed, with a descriptive error message.
+    /// - `Err(PortError)` if the startup process failed.
     ///
     /// # Example
     /// ```
@@ -192,75 +313,427 @@ pub trait DpdkPort: Send + Sync {
     ///     eprintln!("Failed to start the port: {}", err);
     /// }
     /// ```
-    fn start(&mut self) -> Result<(), String>;
+    fn start(&mut self) -> Result<(), PortError>;
+
+    /// Stops the DPDK Ethernet device.
+    ///
+    /// Rx and Tx bursts are rejected until the port is started again.
+    /// Stopping a port that is not started does nothing.
+    ///
+    /// # Example
+    /// ```
+    /// dpdk_port.stop()?;
+    /// dpdk_port.configure()?;
+    /// dpdk_port.start()?;
+    /// ```
+    fn stop(&mut self) -> Result<(), PortError>;
+
+    /// Stops the port if needed and releases the DPDK Ethernet device.
+    ///
+    /// A closed port cannot be configured or started again.
+    /// Ports are also closed when dropped.
+    ///
+    /// # Example
+    /// ```
+    /// if let Err(err) = dpdk_port.close() {
+    ///     eprintln!("Failed to close the port: {}", err);
+    /// }
+    /// ```
+    fn close(&mut self) -> Result<(), PortError>;
+
+    /// Resets the DPDK Ethernet device, for example after a `RTE_ETH_EVENT_INTR_RESET` event.
+    ///
+    /// The device returns to the unconfigured state with no queues set up.
+    /// Call [`configure`] and [`start`] to restore the port.
+    ///
+    /// # Example
+    /// ```
+    /// dpdk_port.reset()?;
+    /// dpdk_port.configure()?;
+    /// dpdk_port.start()?;
+    /// ```
+    fn reset(&mut self) -> Result<(), PortError>;
+
+    /// Returns `true` if the port is started and accepts Rx and Tx bursts.
+    fn is_started(&self) -> bool;
+
+    /// Sets up an Rx queue with its own configuration and mempool.
+    ///
+    /// The port must be configured and the queue stopped. Setting up a queue on a started
+    /// port requires the `RTE_ETH_DEV_CAPA_RUNTIME_RX_QUEUE_SETUP` device capability.
+    /// The new queue is stopped, start it with [`rx_queue_start`].
+    /// Set `rx_conf.rx_deferred_start` to keep the queue stopped when the port starts.
+    ///
+    /// # Example
+    /// ```
+    /// let mut rx_conf = dpdk_port.port_conf().rx_conf;
+    /// rx_conf.rx_deferred_start = 1;
+    /// dpdk_port.rx_queue_setup(1, 1024, &rx_conf, jumbo_pool)?;
+    /// dpdk_port.start()?;
+    /// dpdk_port.rx_queue_start(1)?;
+    /// ```
+    fn rx_queue_setup(
+        &mut self,
+        queue_id: u16,
+        nb_desc: u16,
+        rx_conf: &rte_eth_rxconf,
+        mempool: Arc<Mempool>,
+    ) -> Result<(), PortError>;
+
+    /// Sets up a Tx queue with its own configuration.
+    ///
+    /// Same rules as [`rx_queue_setup`], runtime setup requires the
+    /// `RTE_ETH_DEV_CAPA_RUNTIME_TX_QUEUE_SETUP` device capability.
+    fn tx_queue_setup(
+        &mut self,
+        queue_id: u16,
+        nb_desc: u16,
+        tx_conf: &rte_eth_txconf,
+    ) -> Result<(), PortError>;
+
+    /// Starts an Rx queue of a started port.
+    fn rx_queue_start(&mut self, queue_id: u16) -> Result<(), PortError>;
+
+    /// Stops an Rx queue of a started port. Rx bursts on the queue are rejected until it is
+    /// started again.
+    fn rx_queue_stop(&mut self, queue_id: u16) -> Result<(), PortError>;
+
+    /// Starts a Tx queue of a started port.
+    fn tx_queue_start(&mut self, queue_id: u16) -> Result<(), PortError>;
+
+    /// Stops a Tx queue of a started port. Tx bursts on the queue are rejected until it is
+    /// started again.
+    fn tx_queue_stop(&mut self, queue_id: u16) -> Result<(), PortError>;
+
+    /// Returns Rx queue information reported by the driver with `rte_eth_rx_queue_info_get`.
+    ///
+    /// # Example
+    /// ```
+    /// let qinfo = dpdk_port.rx_queue_info(0)?;
+    /// println!("descriptors: {} state: {}", qinfo.nb_desc, qinfo.queue_state);
+    /// ```
+    fn rx_queue_info(&self, queue_id: u16) -> Result<rte_eth_rxq_info, PortError>;
+
+    /// Returns Tx queue information reported by the driver with `rte_eth_tx_queue_info_get`.
+    fn tx_queue_info(&self, queue_id: u16) -> Result<rte_eth_txq_info, PortError>;
+
+    /// Updates RSS hash functions, key, algorithm and, if set, the redirection table
+    /// of a configured port.
+    ///
+    /// # Example
+    /// ```
+    /// let rss = RssConfig::new(RTE_ETH_RSS_IP as u64).key(&symmetric_key);
+    /// dpdk_port.rss_update(&rss)?;
+    /// ```
+    fn rss_update(&mut self, rss: &RssConfig) -> Result<(), PortError>;
+
+    /// Updates the RSS redirection table, one Rx queue per table entry.
+    ///
+    /// # Example
+    /// ```
+    /// let reta_size = dpdk_port.port_conf().dev_info.reta_size;
+    /// dpdk_port.rss_reta_update(&RssConfig::default_reta(reta_size, 2))?;
+    /// ```
+    fn rss_reta_update(&mut self, reta: &[u16]) -> Result<(), PortError>;
+
+    /// Returns the RSS configuration currently programmed in the device.
+    ///
+    /// # Example
+    /// ```
+    /// let rss = dpdk_port.rss_query()?;
+    /// println!("RSS functions {:#x} algorithm {:?}", rss.hf, rss.algorithm);
+    /// ```
+    fn rss_query(&self) -> Result<RssConfig, PortError>;
+
+    /// Returns basic port counters, per-queue counters included.
+    ///
+    /// # Example
+    /// ```
+    /// let stats = dpdk_port.stats()?;
+    /// println!("Rx {} packets, {} missed", stats.ipackets, stats.imissed);
+    /// for (queue_id, queue) in stats.queues.iter().enumerate() {
+    ///     println!("  queue {queue_id}: Rx {} Tx {}", queue.ipackets, queue.opackets);
+    /// }
+    /// ```
+    fn stats(&self) -> Result<PortStats, PortError> {
+        let conf = self.port_conf();
+        stats::port_stats(self.port_id(), conf.rxq_num.max(conf.txq_num))
+    }
+
+    /// Returns all extended statistics as name / value pairs.
+    ///
+    /// Use [`stats::xstats_ids`] and [`stats::xstats_by_id`] to poll selected counters
+    /// without fetching names.
+    ///
+    /// # Example
+    /// ```
+    /// for xstat in dpdk_port.xstats()? {
+    ///     println!("{}: {}", xstat.name, xstat.value);
+    /// }
+    /// ```
+    fn xstats(&self) -> Result<Vec<XStat>, PortError> {
+        stats::xstats(self.port_id())
+    }
+
+    /// Returns the selected extended statistics values, in `ids` order.
+    fn xstats_by_id(&self, ids: &[u64]) -> Result<Vec<u64>, PortError> {
+        stats::xstats_by_id(self.port_id(), ids)
+    }
+
+    /// Resets basic and extended port counters.
+    fn reset_stats(&mut self) -> Result<(), PortError> {
+        stats::reset_stats(self.port_id())?;
+        stats::reset_xstats(self.port_id())
+    }
+
+    /// Returns Rx offloads enabled on the port, see [`DpdkPortConf::optional_rx_offloads`].
+    ///
+    /// # Example
+    /// ```
+    /// if !dpdk_port.rx_offloads().contains(RxOffloads::CHECKSUM) {
+    ///     println!("port {}: software Rx checksum", dpdk_port.port_id());
+    /// }
+    /// ```
+    fn rx_offloads(&self) -> RxOffloads {
+        RxOffloads::from_bits_retain(self.port_conf().dev_conf.rxmode.offloads)
+    }
+
+    /// Returns Tx offloads enabled on the port, see [`DpdkPortConf::optional_tx_offloads`].
+    fn tx_offloads(&self) -> TxOffloads {
+        TxOffloads::from_bits_retain(self.port_conf().dev_conf.txmode.offloads)
+    }
+
+    /// Returns the port default MAC address.
+    ///
+    /// # Example
+    /// ```
+    /// println!("port {}: {}", dpdk_port.port_id(), dpdk_port.mac_addr()?);
+    /// ```
+    fn mac_addr(&self) -> Result<EtherAddr, PortError> {
+        ether::mac_addr(self.port_id())
+    }
+
+    /// Returns the default and secondary MAC addresses, the default address first.
+    fn mac_addrs(&self) -> Result<Vec<EtherAddr>, PortError> {
+        ether::mac_addrs(self.port_id(), self.port_conf().dev_info.max_mac_addrs)
+    }
+
+    /// Replaces the port default MAC address.
+    fn set_default_mac_addr(&mut self, addr: EtherAddr) -> Result<(), PortError> {
+        ether::set_default_mac_addr(self.port_id(), addr)
+    }
+
+    /// Adds a secondary MAC address, `pool` is the VMDq pool, 0 without VMDq.
+    ///
+    /// # Example
+    /// ```
+    /// dpdk_port.add_mac_addr("02:00:00:00:00:02".parse()?, 0)?;
+    /// ```
+    fn add_mac_addr(&mut self, addr: EtherAddr, pool: u32) -> Result<(), PortError> {
+        ether::add_mac_addr(self.port_id(), addr, pool)
+    }
+
+    /// Removes a secondary MAC address.
+    fn remove_mac_addr(&mut self, addr: EtherAddr) -> Result<(), PortError> {
+        ether::remove_mac_addr(self.port_id(), addr)
+    }
+
+    /// Replaces the list of multicast addresses the port accepts, an empty list flushes it.
+    ///
+    /// # Example
+    /// ```
+    /// let mdns: EtherAddr = "01:00:5e:00:00:fb".parse()?;
+    /// dpdk_port.set_mc_addr_list(&[mdns])?;
+    /// ```
+    fn set_mc_addr_list(&mut self, addrs: &[EtherAddr]) -> Result<(), PortError> {
+        ether::set_mc_addr_list(self.port_id(), addrs)
+    }
+
+    /// Returns the MTU programmed in the device.
+    fn mtu(&self) -> Result<u16, PortError> {
+        ether::mtu(self.port_id())
+    }
+
+    /// Sets the port MTU, the value is kept in the port configuration and applied again
+    /// when the port is reconfigured.
+    ///
+    /// # Example
+    /// ```
+    /// dpdk_port.set_mtu(9000)?;
+    /// ```
+    fn set_mtu(&mut self, mtu: u16) -> Result<(), PortError>;
+
+    /// Returns `true` if promiscuous mode is enabled.
+    fn promiscuous(&self) -> Result<bool, PortError> {
+        ether::promiscuous(self.port_id())
+    }
+
+    fn set_promiscuous(&mut self, enable: bool) -> Result<(), PortError> {
+        ether::set_promiscuous(self.port_id(), enable)
+    }
+
+    /// Returns `true` if all multicast packets are received.
+    fn allmulticast(&self) -> Result<bool, PortError> {
+        ether::allmulticast(self.port_id())
+    }
+
+    fn set_allmulticast(&mut self, enable: bool) -> Result<(), PortError> {
+        ether::set_allmulticast(self.port_id(), enable)
+    }
+
+    /// Updates VLAN offloads and the VLAN filter of a configured port.
+    ///
+    /// Filter IDs not in `vlan.filter_ids` are removed, new IDs are added.
+    ///
+    /// # Example
+    /// ```
+    /// let vlan = dpdk_port.vlan_query()?.strip(false);
+    /// dpdk_port.vlan_update(&vlan)?;
+    /// ```
+    fn vlan_update(&mut self, vlan: &VlanConfig) -> Result<(), PortError>;
+
+    /// Adds (`on`) or removes a VLAN ID from the VLAN filter.
+    ///
+    /// # Example
+    /// ```
+    /// dpdk_port.vlan_filter(100, true)?;
+    /// ```
+    fn vlan_filter(&mut self, vlan_id: u16, on: bool) -> Result<(), PortError>;
+
+    /// Returns VLAN offloads enabled in the device and the configured filter IDs.
+    fn vlan_query(&self) -> Result<VlanConfig, PortError> {
+        let filter_ids = self.port_conf().vlan.as_ref()
+            .map(|vlan| vlan.filter_ids.clone())
+            .unwrap_or_default();
+        Ok(VlanConfig::from_offload_mask(vlan::vlan_offload(self.port_id())?, filter_ids))
+    }
+
+    /// Enables or disables VLAN stripping on an Rx queue.
+    fn set_vlan_strip_on_queue(&mut self, queue_id: u16, on: bool) -> Result<(), PortError> {
+        vlan::set_vlan_strip_on_queue(self.port_id(), queue_id, on)
+    }
+
+    /// Sets the VLAN ID inserted into untagged Tx packets, `None` disables insertion.
+    fn set_vlan_pvid(&mut self, pvid: Option<u16>) -> Result<(), PortError> {
+        vlan::set_vlan_pvid(self.port_id(), pvid)
+    }
+
+    /// Returns the current link status without waiting for the link to settle.
+    ///
+    /// # Example
+    /// ```
+    /// let link = dpdk_port.link_status()?;
+    /// println!("port {}: {link}", dpdk_port.port_id());
+    /// ```
+    fn link_status(&self) -> Result<LinkStatus, PortError> {
+        link::link_status(self.port_id())
+    }
+
+    /// Enables the device Rx / Tx functionality disabled by [`set_link_down`].
+    fn set_link_up(&mut self) -> Result<(), PortError> {
+        link::set_link_up(self.port_id())
+    }
+
+    /// Sets the link down, Rx / Tx stop until [`set_link_up`] is called.
+    fn set_link_down(&mut self) -> Result<(), PortError> {
+        link::set_link_down(self.port_id())
+    }
+
+    /// Subscribes to port events, events are received from the returned channel until
+    /// the [`EventSubscription`] is dropped.
+    ///
+    /// Link status change and removal events require the port to be configured with
+    /// [`DpdkPortConfBuilder::lsc_interrupt`] and [`DpdkPortConfBuilder::rmv_interrupt`].
+    ///
+    /// # Example
+    /// ```
+    /// let (subscription, events) = dpdk_port.subscribe_events(&PortEventType::ALL)?;
+    /// while let Ok(event) = events.recv() {
+    ///     if event.event == PortEventType::ResetRequired {
+    ///         dpdk_port.reset()?;
+    ///     }
+    /// }
+    /// ```
+    fn subscribe_events(
+        &self,
+        events: &[PortEventType],
+    ) -> Result<(EventSubscription, Receiver<PortEvent>), PortError> {
+        EventSubscription::new(Some(self.port_id()), events)
+    }
 
     /// Receives a burst of packets on the specified Rx queue.
     ///
+    /// Received mbufs are appended to `pkts`, up to the batch free capacity.
+    ///
     /// # Parameters
     /// - `queue_id`: The ID of the Rx queue to receive packets from.
-    /// - `pkts`: A mutable reference to an array of packet buffers (`*mut rte_mbuf`) where received packets
-    ///   will be written.
+    /// - `pkts`: The batch that takes ownership of the received packets.
     ///
     /// # Return Value
     /// - `Ok(u16)` containing the number of packets successfully received.
-    /// - `Err(String)` if the operation failed.
+    /// - `Err(PortError)` if the operation failed.
     ///
     /// # Example
     /// ```
-    /// let pkts: Vec<*mut rte_mbuf> = vec![std::ptr::null_mut(); 32];
-    /// let received = dpdk_port.rx_burst(0, &pkts);
+    /// let mut pkts = MbufBatch::new(32);
+    /// let received = dpdk_port.rx_burst(0, &mut pkts);
     /// match received {
     ///     Ok(count) => println!("Received {} packets", count),
     ///     Err(err) => eprintln!("Rx burst failed: {}", err),
     /// }
     /// ```
-    fn rx_burst(&mut self, queue_id: u16, pkts: &[*mut rte_mbuf]) -> Result<u16, String>;
+    fn rx_burst(&mut self, queue_id: u16, pkts: &mut MbufBatch) -> Result<u16, PortError>;
 
     /// Sends a burst of packets on the specified Tx queue.
     ///
+    /// The port takes ownership of the mbufs it sent. Packets the queue did not accept stay
+    /// in `pkts`, moved to the batch front, so the caller can retry or drop them.
+    /// See [`crate::port::tx_buffer::TxBuffer`] for buffered Tx with an unsent packets policy.
+    ///
     /// # Parameters
     /// - `queue_id`: The ID of the Tx queue to send packets on.
-    /// - `pkts`: A reference to an array of packet buffers (`*mut rte_mbuf`) to send.
+    /// - `pkts`: The batch of packets to send.
     ///
     /// # Return Value
     /// - `Ok(u16)` containing the number of packets successfully sent.
-    /// - `Err(String)` if the operation failed.
+    /// - `Err(PortError)` if the operation failed.
     ///
     /// # Example
     /// ```
-    /// let pkts: Vec<*mut rte_mbuf> = vec![some_packet_ptr1, some_packet_ptr2];
-    /// let sent = dpdk_port.tx_burst(0, &pkts);
+    /// let sent = dpdk_port.tx_burst(0, &mut pkts);
     /// match sent {
-    ///     Ok(count) => println!("Sent {} packets", count),
+    ///     Ok(count) => println!("Sent {} packets, {} unsent", count, pkts.len()),
     ///     Err(err) => eprintln!("Tx burst failed: {}", err),
     /// }
+    /// pkts.clear();
+    /// ```
+    fn tx_burst(&mut self, queue_id: u16, pkts: &mut MbufBatch) -> Result<u16, PortError>;
+
+    /// Returns the handle of a started Rx queue for lock-free bursts.
+    ///
+    /// There is one handle per queue, the queue cannot be taken again until the handle is
+    /// dropped. While any queue handle is alive the port cannot be stopped, configured,
+    /// reset or closed.
+    ///
+    /// # Example
+    /// ```
+    /// let mut rxq = dpdk_port.rx_queue(1)?;
+    /// let mut txq = dpdk_port.tx_queue(1)?;
+    /// std::thread::spawn(move || {
+    ///     let mut pkts = MbufBatch::new(32);
+    ///     loop {
+    ///         rxq.rx_burst(&mut pkts);
+    ///         txq.tx_burst(&mut pkts);
+    ///         pkts.clear();
+    ///     }
+    /// });
     /// ```
-    fn tx_burst(&mut self, queue_id: u16, pkts: &[*mut rte_mbuf]) -> Result<u16, String>;
+    fn rx_queue(&mut self, queue_id: u16) -> Result<RxQueue, PortError>;
+
+    /// Returns the handle of a started Tx queue, see [`rx_queue`].
+    fn tx_queue(&mut self, queue_id: u16) -> Result<TxQueue, PortError>;
 }
 
 pub trait DpdkFlow : DpdkPort {}
 
 pub trait DpdkTmplFlow : DpdkPort {}
-
-pub struct DpdkMempool {
-    pub pool: *mut rte_mempool,
-}
-
-pub fn alloc_mbuf_pool(
-    name: &str,
-    capacity: u32,
-    cache_size: u32,
-    priv_size: u16,
-    data_root_size: u16,
-    socket: i32) -> Result<DpdkMempool, String> {
-    let pool_name = CString::new(name.to_string()).unwrap();
-    let pool= unsafe {rte_pktmbuf_pool_create(
-        pool_name.as_ptr(),
-        capacity,
-        cache_size,
-        priv_size,
-        data_root_size,
-        socket as _
-    )};
-    Ok(DpdkMempool {pool: pool as *mut _})
-}
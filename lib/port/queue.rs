@@ -0,0 +1,186 @@
+use std::os::raw::c_void;
+use std::sync::Arc;
+use crate::port::mbuf::MbufBatch;
+use crate::dpdk_raw::rte_mbuf::rte_mbuf;
+
+/// PMD Rx burst function, `eth_rx_burst_t`.
+pub type RxBurstFn = unsafe extern "C" fn(rxq: *mut c_void, rx_pkts: *mut *mut rte_mbuf, nb_pkts: u16) -> u16;
+
+/// PMD Tx burst function, `eth_tx_burst_t`.
+pub type TxBurstFn = unsafe extern "C" fn(txq: *mut c_void, tx_pkts: *mut *mut rte_mbuf, nb_pkts: u16) -> u16;
+
+/// Queue handles given out by a port, one owner per queue.
+///
+/// A queue is owned while its [`QueueOwner`] is alive, the owner is kept by the
+/// [`RxQueue`] or [`TxQueue`] handle and released when the handle is dropped.
+#[derive(Debug, Default)]
+pub struct QueueOwners {
+    owners: Vec<Arc<()>>,
+}
+
+impl QueueOwners {
+    pub fn new() -> Self {
+        QueueOwners::default()
+    }
+
+    /// Takes ownership of a queue, `None` if a handle for the queue is alive.
+    pub fn claim(&mut self, port_id: u16, queue_id: u16) -> Option<QueueOwner> {
+        if self.owners.len() <= queue_id as usize {
+            self.owners.resize_with(queue_id as usize + 1, Default::default);
+        }
+        let owner = &self.owners[queue_id as usize];
+        if Arc::strong_count(owner) > 1 {
+            return None;
+        }
+        Some(QueueOwner { port_id, queue_id, _owner: owner.clone() })
+    }
+
+    pub fn is_owned(&self, queue_id: u16) -> bool {
+        self.owners
+            .get(queue_id as usize)
+            .is_some_and(|owner| Arc::strong_count(owner) > 1)
+    }
+
+    /// Returns the first queue with a live handle.
+    pub fn first_owned(&self) -> Option<u16> {
+        (0..self.owners.len() as u16).find(|queue_id| self.is_owned(*queue_id))
+    }
+}
+
+/// Ownership of a port queue, see [`QueueOwners::claim`].
+#[derive(Debug)]
+pub struct QueueOwner {
+    port_id: u16,
+    queue_id: u16,
+    _owner: Arc<()>,
+}
+
+/// Rx queue handle.
+///
+/// # Overview
+///
+/// `RxQueue` is returned by [`crate::port::DpdkPort::rx_queue`] and holds the PMD burst
+/// function and queue data resolved when the handle was created. Bursts do not touch the
+/// port object, so each queue can be polled from its own thread or lcore without locking.
+///
+/// The handle can be moved to another thread but not shared, there is one handle per
+/// queue. While the handle is alive the port refuses to stop, reconfigure, reset or close
+/// and rejects [`crate::port::DpdkPort::rx_burst`] on the queue. Drop the handle to
+/// release the queue.
+///
+/// # Example
+///
+/// ```
+/// let mut rxq = dpdk_port.rx_queue(0)?;
+/// std::thread::spawn(move || {
+///     let mut pkts = MbufBatch::new(32);
+///     loop {
+///         rxq.rx_burst(&mut pkts);
+///         pkts.clear();
+///     }
+/// });
+/// ```
+pub struct RxQueue {
+    owner: QueueOwner,
+    burst: RxBurstFn,
+    data: *mut c_void,
+}
+
+unsafe impl Send for RxQueue {}
+
+impl RxQueue {
+    /// Creates a handle for a claimed queue.
+    ///
+    /// # Safety
+    ///
+    /// `burst` must be the Rx function of the started queue and `data` its queue data,
+    /// both valid until the port is stopped.
+    pub unsafe fn new(owner: QueueOwner, burst: RxBurstFn, data: *mut c_void) -> Self {
+        RxQueue { owner, burst, data }
+    }
+
+    pub fn port_id(&self) -> u16 {
+        self.owner.port_id
+    }
+
+    pub fn queue_id(&self) -> u16 {
+        self.owner.queue_id
+    }
+
+    /// Receives a burst of packets, received mbufs are appended to `pkts`.
+    pub fn rx_burst(&mut self, pkts: &mut MbufBatch) -> u16 {
+        let (burst, data) = (self.burst, self.data);
+        unsafe { pkts.rx_with(|rx_pkts, nb_pkts| burst(data, rx_pkts, nb_pkts)) }
+    }
+}
+
+/// Tx queue handle, see [`RxQueue`].
+pub struct TxQueue {
+    owner: QueueOwner,
+    burst: TxBurstFn,
+    data: *mut c_void,
+}
+
+unsafe impl Send for TxQueue {}
+
+impl TxQueue {
+    /// Creates a handle for a claimed queue.
+    ///
+    /// # Safety
+    ///
+    /// `burst` must be the Tx function of the started queue and `data` its queue data,
+    /// both valid until the port is stopped.
+    pub unsafe fn new(owner: QueueOwner, burst: TxBurstFn, data: *mut c_void) -> Self {
+        TxQueue { owner, burst, data }
+    }
+
+    pub fn port_id(&self) -> u16 {
+        self.owner.port_id
+    }
+
+    pub fn queue_id(&self) -> u16 {
+        self.owner.queue_id
+    }
+
+    /// Sends a burst of packets, unsent packets stay in `pkts`.
+    pub fn tx_burst(&mut self, pkts: &mut MbufBatch) -> u16 {
+        let (burst, data) = (self.burst, self.data);
+        unsafe { pkts.tx_with(|tx_pkts, nb_pkts| burst(data, tx_pkts, nb_pkts)) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe extern "C" fn no_burst(_q: *mut c_void, _pkts: *mut *mut rte_mbuf, _nb_pkts: u16) -> u16 {
+        0
+    }
+
+    #[test]
+    fn queue_single_owner() {
+        let mut owners = QueueOwners::new();
+        assert_eq!(owners.first_owned(), None);
+
+        let rxq = unsafe { RxQueue::new(owners.claim(1, 2).unwrap(), no_burst, std::ptr::null_mut()) };
+        assert_eq!((rxq.port_id(), rxq.queue_id()), (1, 2));
+        assert!(owners.is_owned(2) && !owners.is_owned(0) && !owners.is_owned(7));
+        assert!(owners.claim(1, 2).is_none());
+        assert_eq!(owners.first_owned(), Some(2));
+
+        let moved = std::thread::spawn(move || rxq.queue_id()).join().unwrap();
+        assert_eq!(moved, 2);
+        assert!(!owners.is_owned(2));
+        assert!(owners.claim(1, 2).is_some());
+    }
+
+    #[test]
+    fn queue_burst_empty() {
+        let mut owners = QueueOwners::new();
+        let mut txq = unsafe { TxQueue::new(owners.claim(0, 0).unwrap(), no_burst, std::ptr::null_mut()) };
+        // dropping a batch frees mbufs with DPDK
+        let mut pkts = std::mem::ManuallyDrop::new(MbufBatch::new(4));
+        assert_eq!(txq.tx_burst(&mut pkts), 0);
+        assert!(pkts.is_empty());
+    }
+}
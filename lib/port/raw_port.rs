@@ -1,29 +1,52 @@
 use std::ffi::c_void;
-use std::ptr::null_mut;
-use crate::dpdk_raw::rte_mbuf::rte_mbuf;
-use crate::port::{DpdkPort, DpdkPortConf};
+use std::sync::Arc;
+use crate::port::{DpdkPort, DpdkPortConf, PortState};
+use crate::port::error::PortError;
+use crate::port::mbuf::MbufBatch;
+use crate::port::mempool::Mempool;
+use crate::port::rss::{self, RssConfig};
+use crate::port::ether;
+use crate::port::vlan::{self, VlanConfig};
+use crate::port::offload::{self, RxOffloads, TxOffloads};
+use crate::port::queue::{QueueOwner, QueueOwners, RxBurstFn, RxQueue, TxBurstFn, TxQueue};
 use crate::dpdk_raw::rte_ethdev::{
     rte_eth_conf,
     rte_eth_txconf,
     rte_eth_rxconf,
-    rte_eth_rss_conf,
     rte_eth_dev_start,
+    rte_eth_dev_stop,
+    rte_eth_dev_close,
+    rte_eth_dev_reset,
     rte_eth_dev_configure,
     rte_eth_tx_queue_setup,
     rte_eth_rx_queue_setup,
+    rte_eth_dev_rx_queue_start,
+    rte_eth_dev_rx_queue_stop,
+    rte_eth_dev_tx_queue_start,
+    rte_eth_dev_tx_queue_stop,
+    rte_eth_rx_queue_info_get,
+    rte_eth_tx_queue_info_get,
+    rte_eth_rxq_info,
+    rte_eth_txq_info,
     rust_get_port_fp_ops,
     RTE_MAX_QUEUES_PER_PORT,
     RTE_ETH_RSS_IP,
-    rte_eth_rx_mq_mode_RTE_ETH_MQ_RX_VMDQ_DCB_RSS,
+    RTE_ETH_VLAN_ID_MAX,
+    rte_eth_rx_mq_mode_RTE_ETH_MQ_RX_NONE,
     rte_eth_rx_mq_mode_RTE_ETH_MQ_RX_RSS,
 };
 
 use crate::dpdk_raw::ethdev_driver::{
     rte_eth_fp_ops,
+    RTE_ETH_QUEUE_STATE_STOPPED,
+    RTE_ETH_QUEUE_STATE_STARTED,
 };
 
 pub type RawPortRxQueue = Option<(rte_eth_rxconf, u8)>; //(rxq_conf, RTE_ETH_QUEUE_STATE_*)
-pub type RawPortTxQueue = Option<(rte_eth_txconf, u8)>; //(rxq_conf, RTE_ETH_QUEUE_STATE_*)
+pub type RawPortTxQueue = Option<(rte_eth_txconf, u8)>; //(txq_conf, RTE_ETH_QUEUE_STATE_*)
+
+const QUEUE_STOPPED: u8 = RTE_ETH_QUEUE_STATE_STOPPED as u8;
+const QUEUE_STARTED: u8 = RTE_ETH_QUEUE_STATE_STARTED as u8;
 
 unsafe impl Send for RawDpdkPort {}
 unsafe impl Sync for RawDpdkPort {}
@@ -31,28 +54,32 @@ unsafe impl Sync for RawDpdkPort {}
 pub struct RawDpdkPort {
     pub port_id: u16,
     pub port_conf: DpdkPortConf,
-    pub rxq: [Option<RawPortRxQueue>; RTE_MAX_QUEUES_PER_PORT as usize],
-    pub txq: [Option<RawPortTxQueue>; RTE_MAX_QUEUES_PER_PORT as usize],
+    /// Rx queues set up on the device, `None` for queues that were not set up.
+    pub rxq: [RawPortRxQueue; RTE_MAX_QUEUES_PER_PORT as usize],
+    /// Tx queues set up on the device, `None` for queues that were not set up.
+    pub txq: [RawPortTxQueue; RTE_MAX_QUEUES_PER_PORT as usize],
     raw_fp_ops: Option<*mut rte_eth_fp_ops>,
+    state: PortState,
+    rxq_owners: QueueOwners,
+    txq_owners: QueueOwners,
+    // Set when `init` succeeds, ports that failed to initialize are not closed on drop.
+    initialized: bool,
 }
 
 impl RawDpdkPort {
-    pub fn init(port_id: u16, port_conf: &DpdkPortConf) -> Result<Self, String> {
+    pub fn init(port_id: u16, port_conf: &DpdkPortConf) -> Result<Self, PortError> {
 
         let mut port_conf = port_conf.clone();
 
-        port_conf.dev_conf.rx_adv_conf.rss_conf = rte_eth_rss_conf {
-            rss_key: null_mut(),
-            rss_key_len: 0,
-            rss_hf: if port_conf.rxq_num > 1 {
+        if port_conf.rss.is_none() {
+            let rss_hf = if port_conf.dev_conf.rx_adv_conf.rss_conf.rss_hf != 0 {
+                port_conf.dev_conf.rx_adv_conf.rss_conf.rss_hf
+            } else if port_conf.rxq_num > 1 {
                 RTE_ETH_RSS_IP as u64 & port_conf.dev_info.flow_type_rss_offloads
-            } else { 0 },
-            algorithm: 0 as crate::dpdk_raw::rte_ethdev::rte_eth_hash_function,
-        };
-
-        if port_conf.dev_conf.rx_adv_conf.rss_conf.rss_hf != 0 {
-            port_conf.dev_conf.rxmode.mq_mode = rte_eth_rx_mq_mode_RTE_ETH_MQ_RX_VMDQ_DCB_RSS
-                & rte_eth_rx_mq_mode_RTE_ETH_MQ_RX_RSS;
+            } else { 0 };
+            if rss_hf != 0 {
+                port_conf.rss = Some(RssConfig::new(rss_hf));
+            }
         }
 
         let raw_fp_ops: *mut rte_eth_fp_ops = unsafe {
@@ -65,13 +92,207 @@ impl RawDpdkPort {
             rxq: [None; RTE_MAX_QUEUES_PER_PORT as usize],
             txq: [None; RTE_MAX_QUEUES_PER_PORT as usize],
             raw_fp_ops: Some(raw_fp_ops),
+            state: PortState::Unconfigured,
+            rxq_owners: QueueOwners::new(),
+            txq_owners: QueueOwners::new(),
+            initialized: false,
         };
 
-        let _ = dpdk_port.configure();
-        let _ = dpdk_port.start();
+        dpdk_port.configure()?;
+        dpdk_port.start()?;
+        dpdk_port.initialized = true;
 
         Ok(dpdk_port)
     }
+
+    pub fn state(&self) -> PortState {
+        self.state
+    }
+
+    fn check_state(&self, call: &'static str, allowed: &[PortState]) -> Result<(), PortError> {
+        if allowed.contains(&self.state) {
+            Ok(())
+        } else {
+            Err(PortError::InvalidState { port_id: self.port_id, call, state: self.state })
+        }
+    }
+
+    /// Returns `true` if the Rx queue is set up and started.
+    pub fn rx_queue_started(&self, queue_id: u16) -> bool {
+        matches!(self.rxq.get(queue_id as usize), Some(Some((_, QUEUE_STARTED))))
+    }
+
+    /// Returns `true` if the Tx queue is set up and started.
+    pub fn tx_queue_started(&self, queue_id: u16) -> bool {
+        matches!(self.txq.get(queue_id as usize), Some(Some((_, QUEUE_STARTED))))
+    }
+
+    /// Returns `true` if a [`RxQueue`] handle for the queue is alive.
+    pub fn rx_queue_owned(&self, queue_id: u16) -> bool {
+        self.rxq_owners.is_owned(queue_id)
+    }
+
+    /// Returns `true` if a [`TxQueue`] handle for the queue is alive.
+    pub fn tx_queue_owned(&self, queue_id: u16) -> bool {
+        self.txq_owners.is_owned(queue_id)
+    }
+
+    /// Fails if a queue handle is alive, handles keep pointers to the queue data.
+    fn check_queues_released(&self, call: &'static str) -> Result<(), PortError> {
+        match self.rxq_owners.first_owned().or(self.txq_owners.first_owned()) {
+            Some(queue_id) => Err(PortError::QueueState { port_id: self.port_id, queue_id, call }),
+            None => Ok(()),
+        }
+    }
+
+    /// Takes ownership of a started Rx queue for a [`RxQueue`] handle.
+    pub fn claim_rx_queue(&mut self, queue_id: u16) -> Result<QueueOwner, PortError> {
+        self.check_state("rx_queue", &[PortState::Started])?;
+        self.check_rx_queue(queue_id)?;
+        if !self.rx_queue_started(queue_id) {
+            return Err(PortError::QueueState { port_id: self.port_id, queue_id, call: "rx_queue" });
+        }
+        self.rxq_owners
+            .claim(self.port_id, queue_id)
+            .ok_or(PortError::QueueState { port_id: self.port_id, queue_id, call: "rx_queue" })
+    }
+
+    /// Takes ownership of a started Tx queue for a [`TxQueue`] handle.
+    pub fn claim_tx_queue(&mut self, queue_id: u16) -> Result<QueueOwner, PortError> {
+        self.check_state("tx_queue", &[PortState::Started])?;
+        self.check_tx_queue(queue_id)?;
+        if !self.tx_queue_started(queue_id) {
+            return Err(PortError::QueueState { port_id: self.port_id, queue_id, call: "tx_queue" });
+        }
+        self.txq_owners
+            .claim(self.port_id, queue_id)
+            .ok_or(PortError::QueueState { port_id: self.port_id, queue_id, call: "tx_queue" })
+    }
+
+    fn check_rx_queue(&self, queue_id: u16) -> Result<(), PortError> {
+        if queue_id >= self.port_conf.rxq_num {
+            return Err(PortError::InvalidQueue { port_id: self.port_id, queue_id });
+        }
+        Ok(())
+    }
+
+    fn check_tx_queue(&self, queue_id: u16) -> Result<(), PortError> {
+        if queue_id >= self.port_conf.txq_num {
+            return Err(PortError::InvalidQueue { port_id: self.port_id, queue_id });
+        }
+        Ok(())
+    }
+
+    fn setup_rx_queue(
+        &mut self,
+        queue_id: u16,
+        nb_desc: u16,
+        rx_conf: &rte_eth_rxconf,
+        mempool: &Arc<Mempool>,
+    ) -> Result<(), PortError> {
+        self.rxq[queue_id as usize] = None;
+        PortError::check(
+            unsafe {
+                rte_eth_rx_queue_setup(
+                    self.port_id,
+                    queue_id,
+                    nb_desc,
+                    self.port_conf.rxq_socket_id,
+                    rx_conf as *const _ as *mut _,
+                    mempool.as_ptr() as *mut _
+            )},
+            "rte_eth_rx_queue_setup",
+            self.port_id,
+            Some(queue_id),
+        )?;
+        self.rxq[queue_id as usize] = Some((*rx_conf, QUEUE_STOPPED));
+        Ok(())
+    }
+
+    fn setup_tx_queue(
+        &mut self,
+        queue_id: u16,
+        nb_desc: u16,
+        tx_conf: &rte_eth_txconf,
+    ) -> Result<(), PortError> {
+        self.txq[queue_id as usize] = None;
+        PortError::check(
+            unsafe {
+                rte_eth_tx_queue_setup(
+                    self.port_id,
+                    queue_id,
+                    nb_desc,
+                    self.port_conf.txq_socket_id,
+                    tx_conf as *const _ as *mut _
+            )},
+            "rte_eth_tx_queue_setup",
+            self.port_id,
+            Some(queue_id),
+        )?;
+        self.txq[queue_id as usize] = Some((*tx_conf, QUEUE_STOPPED));
+        Ok(())
+    }
+
+    // Resolves port offloads against device capabilities and checks per-queue offloads.
+    fn negotiate_offloads(&mut self) -> Result<(), PortError> {
+        let port_id = self.port_id;
+        let conf = &mut self.port_conf;
+        let dev_info = &conf.dev_info;
+        let invalid = |reason: String| PortError::InvalidConfig { port_id, reason };
+
+        let rx = offload::negotiate(
+            RxOffloads::from_bits_retain(conf.dev_conf.rxmode.offloads),
+            conf.optional_rx_offloads,
+            dev_info.rx_offload_capa,
+        )
+        .map_err(|missing| invalid(format!("unsupported Rx offloads {:#x}", missing.bits())))?;
+        let tx = offload::negotiate(
+            TxOffloads::from_bits_retain(conf.dev_conf.txmode.offloads),
+            conf.optional_tx_offloads,
+            dev_info.tx_offload_capa,
+        )
+        .map_err(|missing| invalid(format!("unsupported Tx offloads {:#x}", missing.bits())))?;
+
+        // queue offloads not enabled on the port must be per-queue capable
+        let rxq_unsupported = conf.rx_conf.offloads & !rx.bits() & !dev_info.rx_queue_offload_capa;
+        if rxq_unsupported != 0 {
+            return Err(invalid(format!("unsupported Rx queue offloads {rxq_unsupported:#x}")));
+        }
+        let txq_unsupported = conf.tx_conf.offloads & !tx.bits() & !dev_info.tx_queue_offload_capa;
+        if txq_unsupported != 0 {
+            return Err(invalid(format!("unsupported Tx queue offloads {txq_unsupported:#x}")));
+        }
+
+        conf.dev_conf.rxmode.offloads = rx.bits();
+        conf.dev_conf.txmode.offloads = tx.bits();
+        Ok(())
+    }
+
+    // Device start starts all queues except the deferred start ones.
+    fn set_queues_started(&mut self) {
+        for (conf, state) in self.rxq.iter_mut().flatten() {
+            *state = if conf.rx_deferred_start != 0 { QUEUE_STOPPED } else { QUEUE_STARTED };
+        }
+        for (conf, state) in self.txq.iter_mut().flatten() {
+            *state = if conf.tx_deferred_start != 0 { QUEUE_STOPPED } else { QUEUE_STARTED };
+        }
+    }
+
+    fn set_queues_stopped(&mut self) {
+        self.rxq.iter_mut().flatten().for_each(|(_, state)| *state = QUEUE_STOPPED);
+        self.txq.iter_mut().flatten().for_each(|(_, state)| *state = QUEUE_STOPPED);
+    }
+}
+
+impl Drop for RawDpdkPort {
+    fn drop(&mut self) {
+        if !self.initialized {
+            return;
+        }
+        if let Err(err) = self.close() {
+            eprintln!("{err}");
+        }
+    }
 }
 
 impl DpdkPort for RawDpdkPort {
@@ -83,65 +304,446 @@ impl DpdkPort for RawDpdkPort {
         &self.port_conf
     }
 
-    fn configure(&mut self) -> Result<(), String> {
-        let _ = unsafe {rte_eth_dev_configure(
+    fn configure(&mut self) -> Result<(), PortError> {
+        self.check_state(
+            "configure",
+            &[PortState::Unconfigured, PortState::Configured, PortState::Stopped],
+        )?;
+        self.check_queues_released("configure")?;
+
+        let dev_conf = &mut self.port_conf.dev_conf;
+        match &self.port_conf.rss {
+            Some(rss) if rss.hf != 0 => {
+                rss.to_rss_conf(&mut dev_conf.rx_adv_conf.rss_conf);
+                if dev_conf.rxmode.mq_mode == rte_eth_rx_mq_mode_RTE_ETH_MQ_RX_NONE {
+                    dev_conf.rxmode.mq_mode = rte_eth_rx_mq_mode_RTE_ETH_MQ_RX_RSS;
+                }
+            }
+            _ => dev_conf.rx_adv_conf.rss_conf = unsafe { std::mem::zeroed() },
+        }
+        if let Some(vlan) = &self.port_conf.vlan {
+            dev_conf.rxmode.offloads =
+                dev_conf.rxmode.offloads & !vlan::VLAN_RX_OFFLOADS | vlan.rx_offloads();
+        }
+        self.negotiate_offloads()?;
+
+        let rc = unsafe {rte_eth_dev_configure(
             self.port_id,
             self.port_conf.rxq_num,
             self.port_conf.txq_num,
             &self.port_conf.dev_conf as *const rte_eth_conf)};
+        // the RSS key belongs to port_conf.rss, do not keep a pointer to it
+        self.port_conf.dev_conf.rx_adv_conf.rss_conf.rss_key = std::ptr::null_mut();
+        self.port_conf.dev_conf.rx_adv_conf.rss_conf.rss_key_len = 0;
+        PortError::check(
+            rc,
+            "rte_eth_dev_configure",
+            self.port_id,
+            None,
+        )?;
 
-        for queue_id in 0..self.port_conf.rxq_num {
-            let _ = unsafe {
-                rte_eth_tx_queue_setup(
-                    self.port_id,
-                    queue_id,
-                    self.port_conf.tx_desc_num,
-                    self.port_conf.txq_socket_id,
-                    &self.port_conf.tx_conf as *const _ as *mut _
-            )};
+        self.rxq = [None; RTE_MAX_QUEUES_PER_PORT as usize];
+        self.txq = [None; RTE_MAX_QUEUES_PER_PORT as usize];
+
+        let tx_conf = self.port_conf.tx_conf;
+        for queue_id in 0..self.port_conf.txq_num {
+            self.setup_tx_queue(queue_id, self.port_conf.tx_desc_num, &tx_conf)?;
         }
 
-        self.port_conf.rx_conf.offloads = 0;
+        let rx_conf = self.port_conf.rx_conf;
         for queue_id in 0..self.port_conf.rxq_num {
-            let _ = unsafe {
-                rte_eth_rx_queue_setup(
-                    self.port_id,
-                    queue_id,
-                    self.port_conf.rx_desc_num,
-                    self.port_conf.rxq_socket_id,
-                    &self.port_conf.rx_conf as *const _ as *mut _,
-                    self.port_conf.rxq_mempool.as_ref().unwrap().pool as *mut _
-            )};
+            let mempool = match self.port_conf.rx_queue_mempool(queue_id) {
+                Some(mempool) => mempool.clone(),
+                None => return Err(PortError::InvalidConfig {
+                    port_id: self.port_id,
+                    reason: format!("Rx queue {queue_id} has no mempool"),
+                }),
+            };
+            self.setup_rx_queue(queue_id, self.port_conf.rx_desc_num, &rx_conf, &mempool)?;
         }
+
+        if let Some(RssConfig { hf, reta: Some(reta), .. }) = &self.port_conf.rss {
+            if *hf != 0 {
+                rss::rss_reta_update(self.port_id, reta)?;
+            }
+        }
+        if let Some(vlan) = &self.port_conf.vlan {
+            for vlan_id in &vlan.filter_ids {
+                vlan::vlan_filter(self.port_id, *vlan_id, true)?;
+            }
+        }
+        self.state = PortState::Configured;
         Ok(())
     }
 
-    fn start(&mut self) -> Result<(), String> {
-        unsafe {rte_eth_dev_start(self.port_id)};
+    fn start(&mut self) -> Result<(), PortError> {
+        if self.state == PortState::Started {
+            return Ok(());
+        }
+        self.check_state("start", &[PortState::Configured, PortState::Stopped])?;
+
+        PortError::check(
+            unsafe {rte_eth_dev_start(self.port_id)},
+            "rte_eth_dev_start",
+            self.port_id,
+            None,
+        )?;
+        self.state = PortState::Started;
+        self.set_queues_started();
         Ok(())
     }
 
-    fn rx_burst(&mut self, queue_id:u16, pkts: &[*mut rte_mbuf]) -> Result<u16, String> {
+    fn stop(&mut self) -> Result<(), PortError> {
+        if self.state != PortState::Started {
+            return self.check_state(
+                "stop",
+                &[PortState::Unconfigured, PortState::Configured, PortState::Stopped],
+            );
+        }
+        self.check_queues_released("stop")?;
+
+        PortError::check(
+            unsafe {rte_eth_dev_stop(self.port_id)},
+            "rte_eth_dev_stop",
+            self.port_id,
+            None,
+        )?;
+        self.state = PortState::Stopped;
+        self.set_queues_stopped();
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), PortError> {
+        if self.state == PortState::Closed {
+            return Ok(());
+        }
+        self.check_queues_released("close")?;
+        self.stop()?;
+
+        PortError::check(
+            unsafe {rte_eth_dev_close(self.port_id)},
+            "rte_eth_dev_close",
+            self.port_id,
+            None,
+        )?;
+        self.state = PortState::Closed;
+        self.rxq = [None; RTE_MAX_QUEUES_PER_PORT as usize];
+        self.txq = [None; RTE_MAX_QUEUES_PER_PORT as usize];
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<(), PortError> {
+        self.check_state(
+            "reset",
+            &[PortState::Unconfigured, PortState::Configured, PortState::Started, PortState::Stopped],
+        )?;
+        self.check_queues_released("reset")?;
+
+        // rte_eth_dev_reset() stops the port before re-initializing it.
+        PortError::check(
+            unsafe {rte_eth_dev_reset(self.port_id)},
+            "rte_eth_dev_reset",
+            self.port_id,
+            None,
+        )?;
+        self.state = PortState::Unconfigured;
+        self.rxq = [None; RTE_MAX_QUEUES_PER_PORT as usize];
+        self.txq = [None; RTE_MAX_QUEUES_PER_PORT as usize];
+        Ok(())
+    }
+
+    fn is_started(&self) -> bool {
+        self.state == PortState::Started
+    }
+
+    fn rx_queue_setup(
+        &mut self,
+        queue_id: u16,
+        nb_desc: u16,
+        rx_conf: &rte_eth_rxconf,
+        mempool: Arc<Mempool>,
+    ) -> Result<(), PortError> {
+        self.check_state(
+            "rx_queue_setup",
+            &[PortState::Configured, PortState::Started, PortState::Stopped],
+        )?;
+        self.check_rx_queue(queue_id)?;
+        if self.rx_queue_started(queue_id) || self.rxq_owners.is_owned(queue_id) {
+            return Err(PortError::QueueState { port_id: self.port_id, queue_id, call: "rx_queue_setup" });
+        }
+
+        self.setup_rx_queue(queue_id, nb_desc, rx_conf, &mempool)?;
+        let mempools = &mut self.port_conf.rxq_mempools;
+        if mempools.len() <= queue_id as usize {
+            mempools.resize(queue_id as usize + 1, None);
+        }
+        mempools[queue_id as usize] = Some(mempool);
+        Ok(())
+    }
+
+    fn tx_queue_setup(
+        &mut self,
+        queue_id: u16,
+        nb_desc: u16,
+        tx_conf: &rte_eth_txconf,
+    ) -> Result<(), PortError> {
+        self.check_state(
+            "tx_queue_setup",
+            &[PortState::Configured, PortState::Started, PortState::Stopped],
+        )?;
+        self.check_tx_queue(queue_id)?;
+        if self.tx_queue_started(queue_id) || self.txq_owners.is_owned(queue_id) {
+            return Err(PortError::QueueState { port_id: self.port_id, queue_id, call: "tx_queue_setup" });
+        }
+
+        self.setup_tx_queue(queue_id, nb_desc, tx_conf)
+    }
+
+    fn rx_queue_start(&mut self, queue_id: u16) -> Result<(), PortError> {
+        self.check_state("rx_queue_start", &[PortState::Started])?;
+        self.check_rx_queue(queue_id)?;
+        PortError::check(
+            unsafe {rte_eth_dev_rx_queue_start(self.port_id, queue_id)},
+            "rte_eth_dev_rx_queue_start",
+            self.port_id,
+            Some(queue_id),
+        )?;
+        if let Some((_, state)) = &mut self.rxq[queue_id as usize] {
+            *state = QUEUE_STARTED;
+        }
+        Ok(())
+    }
+
+    fn rx_queue_stop(&mut self, queue_id: u16) -> Result<(), PortError> {
+        self.check_state("rx_queue_stop", &[PortState::Started])?;
+        self.check_rx_queue(queue_id)?;
+        if self.rxq_owners.is_owned(queue_id) {
+            return Err(PortError::QueueState { port_id: self.port_id, queue_id, call: "rx_queue_stop" });
+        }
+        PortError::check(
+            unsafe {rte_eth_dev_rx_queue_stop(self.port_id, queue_id)},
+            "rte_eth_dev_rx_queue_stop",
+            self.port_id,
+            Some(queue_id),
+        )?;
+        if let Some((_, state)) = &mut self.rxq[queue_id as usize] {
+            *state = QUEUE_STOPPED;
+        }
+        Ok(())
+    }
+
+    fn tx_queue_start(&mut self, queue_id: u16) -> Result<(), PortError> {
+        self.check_state("tx_queue_start", &[PortState::Started])?;
+        self.check_tx_queue(queue_id)?;
+        PortError::check(
+            unsafe {rte_eth_dev_tx_queue_start(self.port_id, queue_id)},
+            "rte_eth_dev_tx_queue_start",
+            self.port_id,
+            Some(queue_id),
+        )?;
+        if let Some((_, state)) = &mut self.txq[queue_id as usize] {
+            *state = QUEUE_STARTED;
+        }
+        Ok(())
+    }
+
+    fn tx_queue_stop(&mut self, queue_id: u16) -> Result<(), PortError> {
+        self.check_state("tx_queue_stop", &[PortState::Started])?;
+        self.check_tx_queue(queue_id)?;
+        if self.txq_owners.is_owned(queue_id) {
+            return Err(PortError::QueueState { port_id: self.port_id, queue_id, call: "tx_queue_stop" });
+        }
+        PortError::check(
+            unsafe {rte_eth_dev_tx_queue_stop(self.port_id, queue_id)},
+            "rte_eth_dev_tx_queue_stop",
+            self.port_id,
+            Some(queue_id),
+        )?;
+        if let Some((_, state)) = &mut self.txq[queue_id as usize] {
+            *state = QUEUE_STOPPED;
+        }
+        Ok(())
+    }
+
+    fn rx_queue_info(&self, queue_id: u16) -> Result<rte_eth_rxq_info, PortError> {
+        self.check_rx_queue(queue_id)?;
+        let mut qinfo: rte_eth_rxq_info = unsafe { std::mem::zeroed() };
+        PortError::check(
+            unsafe {rte_eth_rx_queue_info_get(self.port_id, queue_id, &mut qinfo)},
+            "rte_eth_rx_queue_info_get",
+            self.port_id,
+            Some(queue_id),
+        )?;
+        Ok(qinfo)
+    }
+
+    fn tx_queue_info(&self, queue_id: u16) -> Result<rte_eth_txq_info, PortError> {
+        self.check_tx_queue(queue_id)?;
+        let mut qinfo: rte_eth_txq_info = unsafe { std::mem::zeroed() };
+        PortError::check(
+            unsafe {rte_eth_tx_queue_info_get(self.port_id, queue_id, &mut qinfo)},
+            "rte_eth_tx_queue_info_get",
+            self.port_id,
+            Some(queue_id),
+        )?;
+        Ok(qinfo)
+    }
+
+    fn rss_update(&mut self, rss: &RssConfig) -> Result<(), PortError> {
+        self.check_state(
+            "rss_update",
+            &[PortState::Configured, PortState::Started, PortState::Stopped],
+        )?;
+        rss.validate(&self.port_conf.dev_info, self.port_conf.rxq_num)
+            .map_err(|reason| PortError::InvalidConfig { port_id: self.port_id, reason })?;
+
+        rss::rss_hash_update(self.port_id, rss)?;
+        self.port_conf.dev_conf.rx_adv_conf.rss_conf.rss_hf = rss.hf;
+        self.port_conf.dev_conf.rx_adv_conf.rss_conf.algorithm = rss.algorithm.to_raw();
+        self.port_conf.rss = Some(rss.clone());
+        Ok(())
+    }
+
+    fn rss_reta_update(&mut self, reta: &[u16]) -> Result<(), PortError> {
+        self.check_state(
+            "rss_reta_update",
+            &[PortState::Configured, PortState::Started, PortState::Stopped],
+        )?;
+        let mut rss = self.port_conf.rss.clone().unwrap_or_default().reta(reta.to_vec());
+        rss.validate(&self.port_conf.dev_info, self.port_conf.rxq_num)
+            .map_err(|reason| PortError::InvalidConfig { port_id: self.port_id, reason })?;
+
+        rss::rss_reta_update(self.port_id, reta)?;
+        rss.reta = Some(reta.to_vec());
+        self.port_conf.rss = Some(rss);
+        Ok(())
+    }
+
+    fn rss_query(&self) -> Result<RssConfig, PortError> {
+        self.check_state(
+            "rss_query",
+            &[PortState::Configured, PortState::Started, PortState::Stopped],
+        )?;
+        rss::rss_query(self.port_id, &self.port_conf.dev_info)
+    }
+
+    fn vlan_update(&mut self, vlan: &VlanConfig) -> Result<(), PortError> {
+        self.check_state(
+            "vlan_update",
+            &[PortState::Configured, PortState::Started, PortState::Stopped],
+        )?;
+        vlan.validate(&self.port_conf.dev_info)
+            .map_err(|reason| PortError::InvalidConfig { port_id: self.port_id, reason })?;
+
+        let filter_ids = self.port_conf.vlan.as_ref()
+            .map(|vlan| vlan.filter_ids.clone())
+            .unwrap_or_default();
+        vlan::set_vlan_offload(self.port_id, vlan)?;
+        let rxmode = &mut self.port_conf.dev_conf.rxmode;
+        rxmode.offloads = rxmode.offloads & !vlan::VLAN_RX_OFFLOADS | vlan.rx_offloads();
+
+        // the stored filter follows the device if a filter update fails
+        let mut stored = VlanConfig { filter_ids: filter_ids.clone(), ..vlan.clone() };
+        let res = filter_ids.iter()
+            .filter(|id| !vlan.filter_ids.contains(id))
+            .map(|id| (*id, false))
+            .chain(vlan.filter_ids.iter().filter(|id| !filter_ids.contains(id)).map(|id| (*id, true)))
+            .try_for_each(|(id, on)| {
+                vlan::vlan_filter(self.port_id, id, on)?;
+                stored.set_filter_id(id, on);
+                Ok(())
+            });
+        self.port_conf.vlan = Some(stored);
+        res
+    }
+
+    fn vlan_filter(&mut self, vlan_id: u16, on: bool) -> Result<(), PortError> {
+        self.check_state(
+            "vlan_filter",
+            &[PortState::Configured, PortState::Started, PortState::Stopped],
+        )?;
+        if vlan_id > RTE_ETH_VLAN_ID_MAX as u16 {
+            return Err(PortError::InvalidConfig {
+                port_id: self.port_id,
+                reason: format!("invalid VLAN ID {vlan_id}"),
+            });
+        }
+
+        vlan::vlan_filter(self.port_id, vlan_id, on)?;
+        let vlan = self.port_conf.vlan.get_or_insert_with(|| VlanConfig::new().filter(true));
+        vlan.set_filter_id(vlan_id, on);
+        Ok(())
+    }
+
+    fn set_mtu(&mut self, mtu: u16) -> Result<(), PortError> {
+        self.check_state(
+            "set_mtu",
+            &[PortState::Unconfigured, PortState::Configured, PortState::Started, PortState::Stopped],
+        )?;
+        self.check_queues_released("set_mtu")?;
+        let dev_info = &self.port_conf.dev_info;
+        if mtu < dev_info.min_mtu || mtu > dev_info.max_mtu {
+            return Err(PortError::InvalidConfig {
+                port_id: self.port_id,
+                reason: format!("MTU {} out of range [{}, {}]", mtu, dev_info.min_mtu, dev_info.max_mtu),
+            });
+        }
+
+        // an unconfigured port gets the MTU from the device configuration
+        if self.state != PortState::Unconfigured {
+            ether::set_mtu(self.port_id, mtu)?;
+        }
+        self.port_conf.dev_conf.rxmode.mtu = mtu as u32;
+        Ok(())
+    }
+
+    fn rx_burst(&mut self, queue_id:u16, pkts: &mut MbufBatch) -> Result<u16, PortError> {
+        self.check_state("rx_burst", &[PortState::Started])?;
+        self.check_rx_queue(queue_id)?;
+        if !self.rx_queue_started(queue_id) || self.rxq_owners.is_owned(queue_id) {
+            return Err(PortError::QueueState { port_id: self.port_id, queue_id, call: "rx_burst" });
+        }
 
         let ops = unsafe { &mut *self.raw_fp_ops.unwrap() };
         let rxqd:*mut c_void = unsafe { *ops.rxq.data.wrapping_add(queue_id as usize) };
 
+        let rxfn = ops.rx_pkt_burst.unwrap();
         let nb_rx: u16 = unsafe {
-            let rxfn = ops.rx_pkt_burst.unwrap();
-            rxfn(rxqd, pkts.as_ptr() as _, pkts.len() as u16)
+            pkts.rx_with(|rx_pkts, nb_pkts| rxfn(rxqd, rx_pkts as _, nb_pkts))
         };
 
         Ok(nb_rx)
     }
 
-    fn tx_burst(&mut self, queue_id:u16, pkts: &[*mut rte_mbuf]) -> Result<u16, String> {
+    fn tx_burst(&mut self, queue_id:u16, pkts: &mut MbufBatch) -> Result<u16, PortError> {
+        self.check_state("tx_burst", &[PortState::Started])?;
+        self.check_tx_queue(queue_id)?;
+        if !self.tx_queue_started(queue_id) || self.txq_owners.is_owned(queue_id) {
+            return Err(PortError::QueueState { port_id: self.port_id, queue_id, call: "tx_burst" });
+        }
         let ops = unsafe { &mut *self.raw_fp_ops.unwrap() };
         let txqd:*mut c_void = unsafe { *ops.txq.data.wrapping_add(queue_id as usize) };
+        let txfn = ops.tx_pkt_burst.unwrap();
         let nb_tx: u16 = unsafe {
-            let txfn = ops.tx_pkt_burst.unwrap();
-            txfn(txqd, pkts.as_ptr() as _, pkts.len() as u16)
+            pkts.tx_with(|tx_pkts, nb_pkts| txfn(txqd, tx_pkts as _, nb_pkts))
         };
 
         Ok(nb_tx)
     }
+
+    fn rx_queue(&mut self, queue_id: u16) -> Result<RxQueue, PortError> {
+        let owner = self.claim_rx_queue(queue_id)?;
+        let ops = unsafe { &*self.raw_fp_ops.unwrap() };
+        let rxqd: *mut c_void = unsafe { *ops.rxq.data.wrapping_add(queue_id as usize) };
+        let rxfn: RxBurstFn = unsafe { std::mem::transmute(ops.rx_pkt_burst.unwrap()) };
+        Ok(unsafe { RxQueue::new(owner, rxfn, rxqd) })
+    }
+
+    fn tx_queue(&mut self, queue_id: u16) -> Result<TxQueue, PortError> {
+        let owner = self.claim_tx_queue(queue_id)?;
+        let ops = unsafe { &*self.raw_fp_ops.unwrap() };
+        let txqd: *mut c_void = unsafe { *ops.txq.data.wrapping_add(queue_id as usize) };
+        let txfn: TxBurstFn = unsafe { std::mem::transmute(ops.tx_pkt_burst.unwrap()) };
+        Ok(unsafe { TxQueue::new(owner, txfn, txqd) })
+    }
 }
@@ -0,0 +1,309 @@
+use crate::port::error::PortError;
+use crate::dpdk_raw::rte_ethdev::{
+    rte_eth_dev_info,
+    rte_eth_rss_conf,
+    rte_eth_rss_reta_entry64,
+    rte_eth_hash_function,
+    rte_eth_dev_rss_hash_update,
+    rte_eth_dev_rss_hash_conf_get,
+    rte_eth_dev_rss_reta_update,
+    rte_eth_dev_rss_reta_query,
+    rte_eth_hash_function_RTE_ETH_HASH_FUNCTION_DEFAULT,
+    rte_eth_hash_function_RTE_ETH_HASH_FUNCTION_TOEPLITZ,
+    rte_eth_hash_function_RTE_ETH_HASH_FUNCTION_SIMPLE_XOR,
+    rte_eth_hash_function_RTE_ETH_HASH_FUNCTION_SYMMETRIC_TOEPLITZ,
+    rte_eth_hash_function_RTE_ETH_HASH_FUNCTION_SYMMETRIC_TOEPLITZ_SORT,
+    RTE_ETH_RETA_GROUP_SIZE,
+};
+
+/// RSS hash algorithm (`RTE_ETH_HASH_FUNCTION_*`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RssHashFunction {
+    /// Device default algorithm.
+    #[default]
+    Default,
+    Toeplitz,
+    SimpleXor,
+    SymmetricToeplitz,
+    SymmetricToeplitzSort,
+}
+
+impl RssHashFunction {
+    pub fn to_raw(self) -> rte_eth_hash_function {
+        match self {
+            RssHashFunction::Default => rte_eth_hash_function_RTE_ETH_HASH_FUNCTION_DEFAULT,
+            RssHashFunction::Toeplitz => rte_eth_hash_function_RTE_ETH_HASH_FUNCTION_TOEPLITZ,
+            RssHashFunction::SimpleXor => rte_eth_hash_function_RTE_ETH_HASH_FUNCTION_SIMPLE_XOR,
+            RssHashFunction::SymmetricToeplitz =>
+                rte_eth_hash_function_RTE_ETH_HASH_FUNCTION_SYMMETRIC_TOEPLITZ,
+            RssHashFunction::SymmetricToeplitzSort =>
+                rte_eth_hash_function_RTE_ETH_HASH_FUNCTION_SYMMETRIC_TOEPLITZ_SORT,
+        }
+    }
+
+    pub fn from_raw(algorithm: rte_eth_hash_function) -> Option<Self> {
+        [
+            RssHashFunction::Default,
+            RssHashFunction::Toeplitz,
+            RssHashFunction::SimpleXor,
+            RssHashFunction::SymmetricToeplitz,
+            RssHashFunction::SymmetricToeplitzSort,
+        ]
+        .into_iter()
+        .find(|function| function.to_raw() == algorithm)
+    }
+}
+
+/// Receive side scaling configuration.
+///
+/// # Overview
+///
+/// `RssConfig` describes how the port spreads received packets over Rx queues:
+/// - `hf` - hashed packet fields (`RTE_ETH_RSS_*`), 0 disables RSS.
+/// - `key` - hash key, `None` keeps the device key.
+/// - `algorithm` - hash algorithm.
+/// - `reta` - redirection table, one Rx queue per entry, `None` keeps the device table.
+///
+/// The configuration is applied when the port is configured and can be changed at runtime
+/// with [`crate::port::DpdkPort::rss_update`].
+///
+/// # Example
+///
+/// ```
+/// let rss = RssConfig::new((RTE_ETH_RSS_IP | RTE_ETH_RSS_UDP) as u64)
+///     .algorithm(RssHashFunction::SymmetricToeplitz)
+///     .reta(RssConfig::default_reta(dev_info.reta_size, 4));
+/// let conf = DpdkPortConf::builder(port_id).rx_queues(4).rss(rss).build()?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RssConfig {
+    pub hf: u64,
+    pub key: Option<Vec<u8>>,
+    pub algorithm: RssHashFunction,
+    pub reta: Option<Vec<u16>>,
+}
+
+impl RssConfig {
+    pub fn new(hf: u64) -> Self {
+        RssConfig { hf, ..Default::default() }
+    }
+
+    pub fn key(mut self, key: &[u8]) -> Self {
+        self.key = Some(key.to_vec());
+        self
+    }
+
+    pub fn algorithm(mut self, algorithm: RssHashFunction) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn reta(mut self, reta: Vec<u16>) -> Self {
+        self.reta = Some(reta);
+        self
+    }
+
+    /// Returns a redirection table that spreads `reta_size` entries round robin over
+    /// `nb_queues` Rx queues.
+    pub fn default_reta(reta_size: u16, nb_queues: u16) -> Vec<u16> {
+        (0..reta_size).map(|i| i % nb_queues.max(1)).collect()
+    }
+
+    /// Checks the configuration against the device capabilities.
+    pub fn validate(&self, dev_info: &rte_eth_dev_info, rxq_num: u16) -> Result<(), String> {
+        if self.hf & !dev_info.flow_type_rss_offloads != 0 {
+            return Err(format!(
+                "unsupported RSS hash functions {:#x}",
+                self.hf & !dev_info.flow_type_rss_offloads
+            ));
+        }
+
+        if let Some(key) = &self.key {
+            if key.len() != dev_info.hash_key_size as usize {
+                return Err(format!(
+                    "RSS key length {}, device requires {}",
+                    key.len(), dev_info.hash_key_size
+                ));
+            }
+        }
+
+        // RTE_ETH_HASH_ALGO_TO_CAPA()
+        let algorithm = self.algorithm.to_raw();
+        if self.algorithm != RssHashFunction::Default
+            && dev_info.rss_algo_capa & (1 << algorithm) == 0
+        {
+            return Err(format!("unsupported RSS hash algorithm {:?}", self.algorithm));
+        }
+
+        if let Some(reta) = &self.reta {
+            if reta.len() != dev_info.reta_size as usize {
+                return Err(format!(
+                    "RSS RETA size {}, device requires {}",
+                    reta.len(), dev_info.reta_size
+                ));
+            }
+            if let Some(queue) = reta.iter().find(|q| **q >= rxq_num) {
+                return Err(format!("RSS RETA references Rx queue {queue}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes hash functions, algorithm and key into `rss_conf`.
+    ///
+    /// `rss_conf.rss_key` points into `self` and must not be used after `self` is changed
+    /// or dropped.
+    pub fn to_rss_conf(&self, rss_conf: &mut rte_eth_rss_conf) {
+        rss_conf.rss_hf = self.hf;
+        rss_conf.algorithm = self.algorithm.to_raw();
+        match &self.key {
+            Some(key) => {
+                rss_conf.rss_key = key.as_ptr() as *mut u8;
+                rss_conf.rss_key_len = key.len() as u8;
+            }
+            None => {
+                rss_conf.rss_key = std::ptr::null_mut();
+                rss_conf.rss_key_len = 0;
+            }
+        }
+    }
+}
+
+/// Packs a redirection table into `rte_eth_rss_reta_entry64` groups with all entries selected.
+pub fn reta_entries(reta: &[u16]) -> Vec<rte_eth_rss_reta_entry64> {
+    let group_size = RTE_ETH_RETA_GROUP_SIZE as usize;
+    let mut entries = vec![
+        rte_eth_rss_reta_entry64 { mask: 0, reta: [0; RTE_ETH_RETA_GROUP_SIZE as usize] };
+        reta.len().div_ceil(group_size)
+    ];
+    for (i, queue) in reta.iter().enumerate() {
+        let entry = &mut entries[i / group_size];
+        entry.mask |= 1 << (i % group_size);
+        entry.reta[i % group_size] = *queue;
+    }
+    entries
+}
+
+/// Updates RSS hash functions, key and algorithm, then the redirection table if set.
+pub fn rss_hash_update(port_id: u16, rss: &RssConfig) -> Result<(), PortError> {
+    let mut rss_conf: rte_eth_rss_conf = unsafe { std::mem::zeroed() };
+    rss.to_rss_conf(&mut rss_conf);
+    PortError::check(
+        unsafe { rte_eth_dev_rss_hash_update(port_id, &mut rss_conf) },
+        "rte_eth_dev_rss_hash_update",
+        port_id,
+        None,
+    )?;
+
+    if let Some(reta) = &rss.reta {
+        rss_reta_update(port_id, reta)?;
+    }
+    Ok(())
+}
+
+/// Updates the whole RSS redirection table.
+pub fn rss_reta_update(port_id: u16, reta: &[u16]) -> Result<(), PortError> {
+    let mut entries = reta_entries(reta);
+    PortError::check(
+        unsafe { rte_eth_dev_rss_reta_update(port_id, entries.as_mut_ptr(), reta.len() as u16) },
+        "rte_eth_dev_rss_reta_update",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+/// Reads the current RSS configuration of the port, including key and redirection table
+/// when the device exposes them.
+pub fn rss_query(port_id: u16, dev_info: &rte_eth_dev_info) -> Result<RssConfig, PortError> {
+    let mut key = vec![0u8; dev_info.hash_key_size as usize];
+    let mut rss_conf: rte_eth_rss_conf = unsafe { std::mem::zeroed() };
+    if !key.is_empty() {
+        rss_conf.rss_key = key.as_mut_ptr();
+        rss_conf.rss_key_len = key.len() as u8;
+    }
+    PortError::check(
+        unsafe { rte_eth_dev_rss_hash_conf_get(port_id, &mut rss_conf) },
+        "rte_eth_dev_rss_hash_conf_get",
+        port_id,
+        None,
+    )?;
+    key.truncate(rss_conf.rss_key_len as usize);
+
+    let reta = if dev_info.reta_size > 0 {
+        let reta = vec![0u16; dev_info.reta_size as usize];
+        let mut entries = reta_entries(&reta);
+        PortError::check(
+            unsafe { rte_eth_dev_rss_reta_query(port_id, entries.as_mut_ptr(), dev_info.reta_size) },
+            "rte_eth_dev_rss_reta_query",
+            port_id,
+            None,
+        )?;
+        let group_size = RTE_ETH_RETA_GROUP_SIZE as usize;
+        Some((0..reta.len()).map(|i| entries[i / group_size].reta[i % group_size]).collect())
+    } else {
+        None
+    };
+
+    Ok(RssConfig {
+        hf: rss_conf.rss_hf,
+        key: if key.is_empty() { None } else { Some(key) },
+        algorithm: RssHashFunction::from_raw(rss_conf.algorithm).unwrap_or_default(),
+        reta,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dev_info() -> rte_eth_dev_info {
+        let mut dev_info: rte_eth_dev_info = unsafe { std::mem::zeroed() };
+        dev_info.flow_type_rss_offloads = 0xf0;
+        dev_info.hash_key_size = 40;
+        dev_info.reta_size = 128;
+        dev_info.rss_algo_capa = 1 << rte_eth_hash_function_RTE_ETH_HASH_FUNCTION_TOEPLITZ;
+        dev_info
+    }
+
+    #[test]
+    fn validate_rss_config() {
+        let rss = RssConfig::new(0x30)
+            .key(&[0x6d; 40])
+            .algorithm(RssHashFunction::Toeplitz)
+            .reta(RssConfig::default_reta(128, 4));
+        assert_eq!(rss.validate(&dev_info(), 4), Ok(()));
+
+        let err = RssConfig::new(0x130).validate(&dev_info(), 4).unwrap_err();
+        assert_eq!(err, "unsupported RSS hash functions 0x100");
+
+        let err = RssConfig::new(0x30).key(&[0; 52]).validate(&dev_info(), 4).unwrap_err();
+        assert_eq!(err, "RSS key length 52, device requires 40");
+
+        let err = RssConfig::new(0x30)
+            .algorithm(RssHashFunction::SimpleXor)
+            .validate(&dev_info(), 4)
+            .unwrap_err();
+        assert_eq!(err, "unsupported RSS hash algorithm SimpleXor");
+
+        let err = RssConfig::new(0x30)
+            .reta(RssConfig::default_reta(128, 4))
+            .validate(&dev_info(), 2)
+            .unwrap_err();
+        assert_eq!(err, "RSS RETA references Rx queue 2");
+    }
+
+    #[test]
+    fn reta_groups() {
+        let reta = RssConfig::default_reta(100, 3);
+        assert_eq!(&reta[0..4], &[0, 1, 2, 0]);
+
+        let entries = reta_entries(&reta);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].mask, u64::MAX);
+        assert_eq!(entries[1].mask, (1 << 36) - 1);
+        assert_eq!(entries[1].reta[0], reta[64]);
+        assert_eq!(entries[1].reta[35], reta[99]);
+    }
+}
@@ -0,0 +1,321 @@
+use std::ffi::{CStr, CString};
+use crate::port::error::PortError;
+use crate::dpdk_raw::rte_ethdev::{
+    rte_eth_stats,
+    rte_eth_xstat,
+    rte_eth_xstat_name,
+    rte_eth_stats_get,
+    rte_eth_stats_reset,
+    rte_eth_xstats_get,
+    rte_eth_xstats_get_names,
+    rte_eth_xstats_get_by_id,
+    rte_eth_xstats_get_id_by_name,
+    rte_eth_xstats_reset,
+    rte_get_tsc_hz,
+    RTE_ETHDEV_QUEUE_STAT_CNTRS,
+};
+
+/// Per-queue counters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueueStats {
+    pub ipackets: u64,
+    pub opackets: u64,
+    pub ibytes: u64,
+    pub obytes: u64,
+    pub errors: u64,
+}
+
+/// Basic port counters reported by `rte_eth_stats_get`.
+///
+/// `queues` holds counters of the first `RTE_ETHDEV_QUEUE_STAT_CNTRS` port queues.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PortStats {
+    pub ipackets: u64,
+    pub opackets: u64,
+    pub ibytes: u64,
+    pub obytes: u64,
+    pub imissed: u64,
+    pub ierrors: u64,
+    pub oerrors: u64,
+    pub rx_nombuf: u64,
+    pub queues: Vec<QueueStats>,
+}
+
+impl PortStats {
+    /// Converts DPDK counters, keeping `nb_queues` per-queue entries.
+    pub fn from_raw(stats: &rte_eth_stats, nb_queues: u16) -> Self {
+        let nb_queues = (nb_queues as usize).min(RTE_ETHDEV_QUEUE_STAT_CNTRS as usize);
+        PortStats {
+            ipackets: stats.ipackets,
+            opackets: stats.opackets,
+            ibytes: stats.ibytes,
+            obytes: stats.obytes,
+            imissed: stats.imissed,
+            ierrors: stats.ierrors,
+            oerrors: stats.oerrors,
+            rx_nombuf: stats.rx_nombuf,
+            queues: (0..nb_queues)
+                .map(|q| QueueStats {
+                    ipackets: stats.q_ipackets[q],
+                    opackets: stats.q_opackets[q],
+                    ibytes: stats.q_ibytes[q],
+                    obytes: stats.q_obytes[q],
+                    errors: stats.q_errors[q],
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Extended statistic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XStat {
+    /// ID for [`xstats_by_id`].
+    pub id: u64,
+    pub name: String,
+    pub value: u64,
+}
+
+pub fn port_stats(port_id: u16, nb_queues: u16) -> Result<PortStats, PortError> {
+    let mut stats: rte_eth_stats = unsafe { std::mem::zeroed() };
+    PortError::check(
+        unsafe { rte_eth_stats_get(port_id, &mut stats) },
+        "rte_eth_stats_get",
+        port_id,
+        None,
+    )?;
+    Ok(PortStats::from_raw(&stats, nb_queues))
+}
+
+pub fn reset_stats(port_id: u16) -> Result<(), PortError> {
+    PortError::check(
+        unsafe { rte_eth_stats_reset(port_id) },
+        "rte_eth_stats_reset",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+/// Reads all extended statistics with their names.
+pub fn xstats(port_id: u16) -> Result<Vec<XStat>, PortError> {
+    let count = PortError::check(
+        unsafe { rte_eth_xstats_get_names(port_id, std::ptr::null_mut(), 0) },
+        "rte_eth_xstats_get_names",
+        port_id,
+        None,
+    )? as usize;
+
+    let mut names: Vec<rte_eth_xstat_name> = vec![unsafe { std::mem::zeroed() }; count];
+    let nb_names = PortError::check(
+        unsafe { rte_eth_xstats_get_names(port_id, names.as_mut_ptr(), count as u32) },
+        "rte_eth_xstats_get_names",
+        port_id,
+        None,
+    )? as usize;
+
+    let mut values: Vec<rte_eth_xstat> = vec![rte_eth_xstat { id: 0, value: 0 }; count];
+    let nb_values = PortError::check(
+        unsafe { rte_eth_xstats_get(port_id, values.as_mut_ptr(), count as u32) },
+        "rte_eth_xstats_get",
+        port_id,
+        None,
+    )? as usize;
+
+    Ok(values[..nb_values.min(count)]
+        .iter()
+        .filter_map(|xstat| {
+            let name = names[..nb_names.min(count)].get(xstat.id as usize)?;
+            Some(XStat {
+                id: xstat.id,
+                name: unsafe { CStr::from_ptr(name.name.as_ptr()) }.to_string_lossy().into_owned(),
+                value: xstat.value,
+            })
+        })
+        .collect())
+}
+
+/// Resolves extended statistic names to IDs for [`xstats_by_id`].
+pub fn xstats_ids(port_id: u16, names: &[&str]) -> Result<Vec<u64>, PortError> {
+    names
+        .iter()
+        .map(|name| {
+            let c_name = CString::new(*name).map_err(|_| PortError::InvalidConfig {
+                port_id,
+                reason: format!("invalid xstat name \"{name}\""),
+            })?;
+            let mut id: u64 = 0;
+            PortError::check(
+                unsafe { rte_eth_xstats_get_id_by_name(port_id, c_name.as_ptr(), &mut id) },
+                "rte_eth_xstats_get_id_by_name",
+                port_id,
+                None,
+            )?;
+            Ok(id)
+        })
+        .collect()
+}
+
+/// Reads the selected extended statistics, values are returned in `ids` order.
+pub fn xstats_by_id(port_id: u16, ids: &[u64]) -> Result<Vec<u64>, PortError> {
+    let mut values = vec![0u64; ids.len()];
+    PortError::check(
+        unsafe {
+            rte_eth_xstats_get_by_id(port_id, ids.as_ptr(), values.as_mut_ptr(), ids.len() as u32)
+        },
+        "rte_eth_xstats_get_by_id",
+        port_id,
+        None,
+    )?;
+    Ok(values)
+}
+
+pub fn reset_xstats(port_id: u16) -> Result<(), PortError> {
+    PortError::check(
+        unsafe { rte_eth_xstats_reset(port_id) },
+        "rte_eth_xstats_reset",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+/// Reads the CPU time stamp counter.
+#[cfg(target_arch = "x86_64")]
+pub fn rdtsc() -> u64 {
+    unsafe { std::arch::x86_64::_rdtsc() }
+}
+
+/// Reads the CPU time stamp counter.
+#[cfg(target_arch = "aarch64")]
+pub fn rdtsc() -> u64 {
+    let tsc: u64;
+    unsafe { std::arch::asm!("mrs {}, cntvct_el0", out(reg) tsc) };
+    tsc
+}
+
+/// Time stamp counter frequency.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+pub fn tsc_hz() -> u64 {
+    unsafe { rte_get_tsc_hz() }
+}
+
+/// Reads the time stamp counter, nanoseconds since the first call.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub fn rdtsc() -> u64 {
+    static EPOCH: once_cell::sync::Lazy<std::time::Instant> =
+        once_cell::sync::Lazy::new(std::time::Instant::now);
+    EPOCH.elapsed().as_nanos() as u64
+}
+
+/// Time stamp counter frequency.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub fn tsc_hz() -> u64 {
+    1_000_000_000
+}
+
+/// Port counters with the TSC value they were read at.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StatsSnapshot {
+    pub tsc: u64,
+    pub stats: PortStats,
+}
+
+impl StatsSnapshot {
+    pub fn new(stats: PortStats) -> Self {
+        StatsSnapshot { tsc: rdtsc(), stats }
+    }
+}
+
+/// Port throughput between two [`StatsSnapshot`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PortRate {
+    /// Interval between the snapshots, in seconds.
+    pub interval: f64,
+    pub rx_pps: f64,
+    pub tx_pps: f64,
+    /// Rx bits per second.
+    pub rx_bps: f64,
+    /// Tx bits per second.
+    pub tx_bps: f64,
+}
+
+impl PortRate {
+    /// Computes rates from two snapshots, `tsc_hz` is the TSC frequency, see [`tsc_hz`].
+    ///
+    /// Counters that went backwards, for example after a reset, count as 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let prev = StatsSnapshot::new(port.stats()?);
+    /// std::thread::sleep(std::time::Duration::from_secs(1));
+    /// let cur = StatsSnapshot::new(port.stats()?);
+    /// let rate = PortRate::between(&prev, &cur, tsc_hz());
+    /// println!("Rx {:.0} pps {:.0} bps", rate.rx_pps, rate.rx_bps);
+    /// ```
+    pub fn between(prev: &StatsSnapshot, cur: &StatsSnapshot, tsc_hz: u64) -> Self {
+        let cycles = cur.tsc.saturating_sub(prev.tsc);
+        if cycles == 0 || tsc_hz == 0 {
+            return PortRate::default();
+        }
+        let interval = cycles as f64 / tsc_hz as f64;
+        let rate = |cur: u64, prev: u64| cur.saturating_sub(prev) as f64 / interval;
+
+        PortRate {
+            interval,
+            rx_pps: rate(cur.stats.ipackets, prev.stats.ipackets),
+            tx_pps: rate(cur.stats.opackets, prev.stats.opackets),
+            rx_bps: rate(cur.stats.ibytes, prev.stats.ibytes) * 8.0,
+            tx_bps: rate(cur.stats.obytes, prev.stats.obytes) * 8.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(tsc: u64, packets: u64, bytes: u64) -> StatsSnapshot {
+        StatsSnapshot {
+            tsc,
+            stats: PortStats {
+                ipackets: packets,
+                opackets: packets / 2,
+                ibytes: bytes,
+                obytes: bytes / 2,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn rate_between_snapshots() {
+        let prev = snapshot(1_000, 100, 6_400);
+        let cur = snapshot(3_000, 2_100, 134_400);
+        let rate = PortRate::between(&prev, &cur, 1_000);
+        assert_eq!(rate.interval, 2.0);
+        assert_eq!(rate.rx_pps, 1_000.0);
+        assert_eq!(rate.tx_pps, 500.0);
+        assert_eq!(rate.rx_bps, 512_000.0);
+        assert_eq!(rate.tx_bps, 256_000.0);
+
+        // counters reset between snapshots
+        let rate = PortRate::between(&cur, &snapshot(4_000, 0, 0), 1_000);
+        assert_eq!(rate.rx_pps, 0.0);
+        assert_eq!(PortRate::between(&cur, &cur, 1_000), PortRate::default());
+    }
+
+    #[test]
+    fn stats_from_raw() {
+        let mut raw: rte_eth_stats = unsafe { std::mem::zeroed() };
+        raw.ipackets = 10;
+        raw.q_ipackets[1] = 7;
+        raw.q_errors[1] = 1;
+        let stats = PortStats::from_raw(&raw, 2);
+        assert_eq!(stats.ipackets, 10);
+        assert_eq!(stats.queues.len(), 2);
+        assert_eq!(stats.queues[1], QueueStats { ipackets: 7, errors: 1, ..Default::default() });
+        assert_eq!(PortStats::from_raw(&raw, 1024).queues.len(), RTE_ETHDEV_QUEUE_STAT_CNTRS as usize);
+    }
+}
@@ -0,0 +1,251 @@
+use std::alloc::{alloc_zeroed, dealloc, Layout};
+use std::mem::size_of;
+use std::os::raw::c_void;
+use std::ptr::{self, null_mut, NonNull};
+use crate::port::DpdkPort;
+use crate::port::error::PortError;
+use crate::port::mbuf::{Mbuf, MbufBatch};
+use crate::dpdk_raw::rte_mbuf::rte_pktmbuf_free_bulk;
+use crate::dpdk_raw::rte_ethdev::{
+    rte_mbuf,
+    rte_eth_dev_tx_buffer,
+    rte_eth_tx_buffer_init,
+    rte_eth_tx_buffer_set_err_callback,
+    rte_eth_tx_buffer_drop_callback,
+    rte_eth_tx_buffer_count_callback,
+};
+
+/// What [`TxBuffer`] does with packets the Tx queue did not accept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxBufferPolicy {
+    /// Free unsent packets with `rte_eth_tx_buffer_drop_callback`.
+    Drop,
+
+    /// Free unsent packets and count them with `rte_eth_tx_buffer_count_callback`.
+    Count,
+
+    /// Retry the Tx burst up to the given number of times, then free and count the
+    /// remaining packets.
+    Retry(u32),
+}
+
+/// Buffered Tx on a single port queue.
+///
+/// # Overview
+///
+/// `TxBuffer` accumulates packets in an `rte_eth_dev_tx_buffer` and sends them with
+/// [`DpdkPort::tx_burst`] when the buffer is full or on [`TxBuffer::flush`], the same way
+/// `rte_eth_tx_buffer` / `rte_eth_tx_buffer_flush` do. Packets left after the flush are
+/// handled according to the [`TxBufferPolicy`].
+///
+/// Buffered packets are freed when the buffer is dropped, call [`TxBuffer::flush`] first.
+///
+/// # Example
+///
+/// ```
+/// let mut tx_buffer = TxBuffer::new(port.port_id(), 0, 32, TxBufferPolicy::Retry(3))?;
+/// for mbuf in rx_pkts.drain() {
+///     tx_buffer.buffer(&mut *port, mbuf)?;
+/// }
+/// tx_buffer.flush(&mut *port)?;
+/// println!("dropped {} packets", tx_buffer.dropped());
+/// ```
+pub struct TxBuffer {
+    port_id: u16,
+    queue_id: u16,
+    policy: TxBufferPolicy,
+    buffer: NonNull<rte_eth_dev_tx_buffer>,
+    layout: Layout,
+    // Staging batch for `DpdkPort::tx_burst`.
+    pkts: MbufBatch,
+    // `rte_eth_tx_buffer_count_callback` user data, boxed to keep its address stable.
+    dropped: Box<u64>,
+}
+
+// The buffer and the buffered mbufs are owned by the `TxBuffer` object.
+unsafe impl Send for TxBuffer {}
+
+impl TxBuffer {
+    /// Creates a buffer of `size` packets for the port Tx queue.
+    pub fn new(
+        port_id: u16,
+        queue_id: u16,
+        size: u16,
+        policy: TxBufferPolicy,
+    ) -> Result<Self, PortError> {
+        if size == 0 {
+            return Err(PortError::InvalidConfig {
+                port_id,
+                reason: "Tx buffer size must not be 0".to_string(),
+            });
+        }
+
+        // RTE_ETH_TX_BUFFER_SIZE()
+        let layout = Layout::from_size_align(
+            size_of::<rte_eth_dev_tx_buffer>() + size as usize * size_of::<*mut rte_mbuf>(),
+            align_of::<rte_eth_dev_tx_buffer>(),
+        )
+        .unwrap();
+        let buffer = NonNull::new(unsafe { alloc_zeroed(layout) } as *mut rte_eth_dev_tx_buffer)
+            .expect("Tx buffer allocation failed");
+
+        let mut tx_buffer = TxBuffer {
+            port_id,
+            queue_id,
+            policy,
+            buffer,
+            layout,
+            pkts: MbufBatch::new(size as usize),
+            dropped: Box::new(0),
+        };
+
+        PortError::check(
+            unsafe { rte_eth_tx_buffer_init(buffer.as_ptr(), size) },
+            "rte_eth_tx_buffer_init",
+            port_id,
+            Some(queue_id),
+        )?;
+
+        let (callback, userdata) = match policy {
+            TxBufferPolicy::Drop => (rte_eth_tx_buffer_drop_callback as _, null_mut()),
+            TxBufferPolicy::Count | TxBufferPolicy::Retry(_) => (
+                rte_eth_tx_buffer_count_callback as _,
+                &mut *tx_buffer.dropped as *mut u64 as *mut c_void,
+            ),
+        };
+        PortError::check(
+            unsafe { rte_eth_tx_buffer_set_err_callback(buffer.as_ptr(), Some(callback), userdata) },
+            "rte_eth_tx_buffer_set_err_callback",
+            port_id,
+            Some(queue_id),
+        )?;
+
+        Ok(tx_buffer)
+    }
+
+    pub fn port_id(&self) -> u16 {
+        self.port_id
+    }
+
+    pub fn queue_id(&self) -> u16 {
+        self.queue_id
+    }
+
+    pub fn policy(&self) -> TxBufferPolicy {
+        self.policy
+    }
+
+    /// Maximal number of buffered packets.
+    pub fn size(&self) -> u16 {
+        unsafe { self.buffer.as_ref().size }
+    }
+
+    /// Number of buffered packets.
+    pub fn len(&self) -> u16 {
+        unsafe { self.buffer.as_ref().length }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of packets dropped so far. Always 0 with [`TxBufferPolicy::Drop`].
+    pub fn dropped(&self) -> u64 {
+        *self.dropped
+    }
+
+    fn buffer_pkts(&mut self) -> *mut *mut rte_mbuf {
+        unsafe { self.buffer.as_mut().pkts.as_mut_ptr() }
+    }
+
+    /// Buffers a packet, flushing the buffer when it becomes full.
+    ///
+    /// Returns the number of packets sent by the flush, 0 if the packet was only buffered.
+    pub fn buffer<P>(&mut self, port: &mut P, mbuf: Mbuf) -> Result<u16, PortError>
+    where
+        P: DpdkPort + ?Sized,
+    {
+        let length = self.len();
+        unsafe {
+            *self.buffer_pkts().add(length as usize) = mbuf.into_raw() as *mut rte_mbuf;
+            self.buffer.as_mut().length = length + 1;
+        }
+
+        if self.len() < self.size() {
+            return Ok(0);
+        }
+        self.flush(port)
+    }
+
+    /// Sends all buffered packets.
+    ///
+    /// Returns the number of packets sent. Packets that were not sent are handled by the
+    /// buffer policy, also when the Tx burst fails.
+    pub fn flush<P>(&mut self, port: &mut P) -> Result<u16, PortError>
+    where
+        P: DpdkPort + ?Sized,
+    {
+        debug_assert_eq!(port.port_id(), self.port_id);
+
+        let length = self.len();
+        if length == 0 {
+            return Ok(0);
+        }
+
+        let buffer_pkts = self.buffer_pkts();
+        unsafe {
+            self.pkts.rx_with(|slots, _| {
+                ptr::copy_nonoverlapping(buffer_pkts, slots as *mut *mut rte_mbuf, length as usize);
+                length
+            });
+            self.buffer.as_mut().length = 0;
+        }
+
+        let retries = match self.policy {
+            TxBufferPolicy::Retry(retries) => retries,
+            _ => 0,
+        };
+        let mut sent = 0;
+        let mut attempt = 0;
+        let res = loop {
+            match port.tx_burst(self.queue_id, &mut self.pkts) {
+                Ok(nb_tx) => sent += nb_tx,
+                Err(err) => break Err(err),
+            }
+            if self.pkts.is_empty() || attempt == retries {
+                break Ok(sent);
+            }
+            attempt += 1;
+        };
+
+        if !self.pkts.is_empty() {
+            self.release_unsent();
+        }
+        res
+    }
+
+    // Hands the packets left in the staging batch to the buffer error callback.
+    fn release_unsent(&mut self) {
+        let buffer_pkts = self.buffer_pkts();
+        let mut unsent: u16 = 0;
+        for mbuf in self.pkts.drain() {
+            unsafe { *buffer_pkts.add(unsent as usize) = mbuf.into_raw() as *mut rte_mbuf };
+            unsent += 1;
+        }
+
+        let buffer = unsafe { self.buffer.as_ref() };
+        if let Some(callback) = buffer.error_callback {
+            unsafe { callback(buffer_pkts, unsent, buffer.error_userdata) };
+        }
+    }
+}
+
+impl Drop for TxBuffer {
+    fn drop(&mut self) {
+        let length = self.len();
+        if length > 0 {
+            unsafe { rte_pktmbuf_free_bulk(self.buffer_pkts() as *mut _, length as u32) };
+        }
+        unsafe { dealloc(self.buffer.as_ptr() as *mut u8, self.layout) };
+    }
+}
@@ -0,0 +1,254 @@
+use std::os::raw::c_int;
+use crate::port::error::PortError;
+use crate::dpdk_raw::rte_ethdev::{
+    rte_eth_dev_info,
+    rte_eth_dev_vlan_filter,
+    rte_eth_dev_set_vlan_offload,
+    rte_eth_dev_get_vlan_offload,
+    rte_eth_dev_set_vlan_strip_on_queue,
+    rte_eth_dev_set_vlan_pvid,
+    RTE_ETH_VLAN_STRIP_OFFLOAD,
+    RTE_ETH_VLAN_FILTER_OFFLOAD,
+    RTE_ETH_VLAN_EXTEND_OFFLOAD,
+    RTE_ETH_QINQ_STRIP_OFFLOAD,
+    RTE_ETH_RX_OFFLOAD_VLAN_STRIP,
+    RTE_ETH_RX_OFFLOAD_VLAN_FILTER,
+    RTE_ETH_RX_OFFLOAD_VLAN_EXTEND,
+    RTE_ETH_RX_OFFLOAD_QINQ_STRIP,
+    RTE_ETH_VLAN_ID_MAX,
+};
+
+/// VLAN Rx offloads (`RTE_ETH_RX_OFFLOAD_VLAN_*`) controlled by [`VlanConfig`].
+pub const VLAN_RX_OFFLOADS: u64 = (RTE_ETH_RX_OFFLOAD_VLAN_STRIP
+    | RTE_ETH_RX_OFFLOAD_VLAN_FILTER
+    | RTE_ETH_RX_OFFLOAD_VLAN_EXTEND
+    | RTE_ETH_RX_OFFLOAD_QINQ_STRIP) as u64;
+
+/// VLAN offload and filtering configuration.
+///
+/// # Overview
+///
+/// - `strip` - strip the VLAN tag into `mbuf.vlan_tci`.
+/// - `filter` - drop packets with VLAN IDs not in `filter_ids`.
+/// - `extend` - double VLAN (extended) mode.
+/// - `qinq_strip` - strip both QinQ tags into `mbuf.vlan_tci` and `mbuf.vlan_tci_outer`.
+/// - `filter_ids` - VLAN IDs accepted by the filter.
+///
+/// The configuration is applied when the port is configured and can be changed at runtime
+/// with [`crate::port::DpdkPort::vlan_update`] and [`crate::port::DpdkPort::vlan_filter`].
+///
+/// # Example
+///
+/// ```
+/// let vlan = VlanConfig::new().strip(true).filter_ids(&[100, 200]);
+/// let conf = DpdkPortConf::builder(port_id).vlan(vlan).build()?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VlanConfig {
+    pub strip: bool,
+    pub filter: bool,
+    pub extend: bool,
+    pub qinq_strip: bool,
+    /// Sorted VLAN IDs accepted by the filter.
+    pub filter_ids: Vec<u16>,
+}
+
+impl VlanConfig {
+    pub fn new() -> Self {
+        VlanConfig::default()
+    }
+
+    pub fn strip(mut self, strip: bool) -> Self {
+        self.strip = strip;
+        self
+    }
+
+    pub fn filter(mut self, filter: bool) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn extend(mut self, extend: bool) -> Self {
+        self.extend = extend;
+        self
+    }
+
+    pub fn qinq_strip(mut self, qinq_strip: bool) -> Self {
+        self.qinq_strip = qinq_strip;
+        self
+    }
+
+    /// Sets VLAN IDs accepted by the filter and enables filtering.
+    pub fn filter_ids(mut self, ids: &[u16]) -> Self {
+        self.filter = true;
+        self.filter_ids = ids.to_vec();
+        self.filter_ids.sort_unstable();
+        self.filter_ids.dedup();
+        self
+    }
+
+    /// Adds or removes a filter VLAN ID, returns `false` if nothing changed.
+    pub fn set_filter_id(&mut self, vlan_id: u16, on: bool) -> bool {
+        match (self.filter_ids.binary_search(&vlan_id), on) {
+            (Err(pos), true) => self.filter_ids.insert(pos, vlan_id),
+            (Ok(pos), false) => {
+                self.filter_ids.remove(pos);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Port Rx offloads, `RTE_ETH_RX_OFFLOAD_*` bits within [`VLAN_RX_OFFLOADS`].
+    pub fn rx_offloads(&self) -> u64 {
+        [
+            (self.strip, RTE_ETH_RX_OFFLOAD_VLAN_STRIP),
+            (self.filter, RTE_ETH_RX_OFFLOAD_VLAN_FILTER),
+            (self.extend, RTE_ETH_RX_OFFLOAD_VLAN_EXTEND),
+            (self.qinq_strip, RTE_ETH_RX_OFFLOAD_QINQ_STRIP),
+        ]
+        .iter()
+        .filter(|(on, _)| *on)
+        .fold(0, |offloads, (_, offload)| offloads | *offload as u64)
+    }
+
+    /// `rte_eth_dev_set_vlan_offload` argument, `RTE_ETH_*_OFFLOAD` bits.
+    pub fn offload_mask(&self) -> c_int {
+        [
+            (self.strip, RTE_ETH_VLAN_STRIP_OFFLOAD),
+            (self.filter, RTE_ETH_VLAN_FILTER_OFFLOAD),
+            (self.extend, RTE_ETH_VLAN_EXTEND_OFFLOAD),
+            (self.qinq_strip, RTE_ETH_QINQ_STRIP_OFFLOAD),
+        ]
+        .iter()
+        .filter(|(on, _)| *on)
+        .fold(0, |mask, (_, offload)| mask | *offload as c_int)
+    }
+
+    /// Builds a configuration from the `rte_eth_dev_get_vlan_offload` value.
+    pub fn from_offload_mask(mask: c_int, filter_ids: Vec<u16>) -> Self {
+        let mask = mask as u32;
+        VlanConfig {
+            strip: mask & RTE_ETH_VLAN_STRIP_OFFLOAD != 0,
+            filter: mask & RTE_ETH_VLAN_FILTER_OFFLOAD != 0,
+            extend: mask & RTE_ETH_VLAN_EXTEND_OFFLOAD != 0,
+            qinq_strip: mask & RTE_ETH_QINQ_STRIP_OFFLOAD != 0,
+            filter_ids,
+        }
+    }
+
+    /// Checks the configuration against device capabilities.
+    pub fn validate(&self, dev_info: &rte_eth_dev_info) -> Result<(), String> {
+        let unsupported = self.rx_offloads() & !dev_info.rx_offload_capa;
+        if unsupported != 0 {
+            return Err(format!("unsupported VLAN Rx offloads {unsupported:#x}"));
+        }
+        if let Some(vlan_id) = self.filter_ids.iter().find(|id| **id > RTE_ETH_VLAN_ID_MAX as u16) {
+            return Err(format!("invalid VLAN ID {vlan_id}"));
+        }
+        if !self.filter && !self.filter_ids.is_empty() {
+            return Err("VLAN filter IDs set with VLAN filter disabled".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub fn vlan_filter(port_id: u16, vlan_id: u16, on: bool) -> Result<(), PortError> {
+    PortError::check(
+        unsafe { rte_eth_dev_vlan_filter(port_id, vlan_id, on as c_int) },
+        "rte_eth_dev_vlan_filter",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+pub fn set_vlan_offload(port_id: u16, vlan: &VlanConfig) -> Result<(), PortError> {
+    PortError::check(
+        unsafe { rte_eth_dev_set_vlan_offload(port_id, vlan.offload_mask()) },
+        "rte_eth_dev_set_vlan_offload",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+/// Returns the enabled VLAN offloads, `RTE_ETH_*_OFFLOAD` bits.
+pub fn vlan_offload(port_id: u16) -> Result<c_int, PortError> {
+    PortError::check(
+        unsafe { rte_eth_dev_get_vlan_offload(port_id) },
+        "rte_eth_dev_get_vlan_offload",
+        port_id,
+        None,
+    )
+}
+
+pub fn set_vlan_strip_on_queue(port_id: u16, queue_id: u16, on: bool) -> Result<(), PortError> {
+    PortError::check(
+        unsafe { rte_eth_dev_set_vlan_strip_on_queue(port_id, queue_id, on as c_int) },
+        "rte_eth_dev_set_vlan_strip_on_queue",
+        port_id,
+        Some(queue_id),
+    )?;
+    Ok(())
+}
+
+/// Sets the port VLAN ID inserted into untagged Tx packets, `None` disables it.
+pub fn set_vlan_pvid(port_id: u16, pvid: Option<u16>) -> Result<(), PortError> {
+    PortError::check(
+        unsafe { rte_eth_dev_set_vlan_pvid(port_id, pvid.unwrap_or(0), pvid.is_some() as c_int) },
+        "rte_eth_dev_set_vlan_pvid",
+        port_id,
+        None,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vlan_offloads() {
+        let vlan = VlanConfig::new().strip(true).qinq_strip(true);
+        assert_eq!(
+            vlan.rx_offloads(),
+            (RTE_ETH_RX_OFFLOAD_VLAN_STRIP | RTE_ETH_RX_OFFLOAD_QINQ_STRIP) as u64
+        );
+        assert_eq!(
+            vlan.offload_mask(),
+            (RTE_ETH_VLAN_STRIP_OFFLOAD | RTE_ETH_QINQ_STRIP_OFFLOAD) as c_int
+        );
+        assert_eq!(VlanConfig::from_offload_mask(vlan.offload_mask(), Vec::new()), vlan);
+        assert_eq!(VlanConfig::new().rx_offloads(), 0);
+    }
+
+    #[test]
+    fn vlan_filter_ids() {
+        let mut vlan = VlanConfig::new().filter_ids(&[200, 100, 200]);
+        assert!(vlan.filter);
+        assert_eq!(vlan.filter_ids, [100, 200]);
+        assert!(vlan.set_filter_id(150, true));
+        assert!(!vlan.set_filter_id(150, true));
+        assert!(vlan.set_filter_id(100, false));
+        assert!(!vlan.set_filter_id(100, false));
+        assert_eq!(vlan.filter_ids, [150, 200]);
+    }
+
+    #[test]
+    fn validate_vlan_config() {
+        let mut dev_info: rte_eth_dev_info = unsafe { std::mem::zeroed() };
+        dev_info.rx_offload_capa = (RTE_ETH_RX_OFFLOAD_VLAN_STRIP | RTE_ETH_RX_OFFLOAD_VLAN_FILTER) as u64;
+
+        assert_eq!(VlanConfig::new().strip(true).filter_ids(&[1, 4095]).validate(&dev_info), Ok(()));
+        assert_eq!(
+            VlanConfig::new().extend(true).validate(&dev_info),
+            Err(format!("unsupported VLAN Rx offloads {:#x}", RTE_ETH_RX_OFFLOAD_VLAN_EXTEND))
+        );
+        assert_eq!(
+            VlanConfig::new().filter_ids(&[4096]).validate(&dev_info),
+            Err("invalid VLAN ID 4096".to_string())
+        );
+        assert!(VlanConfig::new().filter_ids(&[1]).filter(false).validate(&dev_info).is_err());
+    }
+}
@@ -2,6 +2,7 @@ use std::os::raw::c_void;
 use rdpdk::dpdk_raw::ethdev_driver::{rte_eth_dev};
 use rdpdk::dpdk_raw::rte_ethdev::{rust_get_port_eth_device};
 use rdpdk::port::{DpdkPort, DpdkPortConf};
+use rdpdk::port::error::PortError;
 use crate::mlx5_raw::mlx5::{
     mlx5_priv,
     mlx5_select_rx_function_index,
@@ -9,7 +10,13 @@ use crate::mlx5_raw::mlx5::{
 };
 use crate::mlx5_raw::mlx5_rx::{mlx5_rx_functions, mlx5_rxq_data};
 use crate::mlx5_raw::mlx5_tx::{mlx5_txq_data, txoff_func};
-use rdpdk::dpdk_raw::rte_mbuf::rte_mbuf;
+use rdpdk::port::mbuf::MbufBatch;
+use rdpdk::port::queue::{RxBurstFn, RxQueue, TxBurstFn, TxQueue};
+use rdpdk::port::mempool::Mempool;
+use rdpdk::port::rss::RssConfig;
+use rdpdk::port::vlan::VlanConfig;
+use rdpdk::dpdk_raw::rte_ethdev::{rte_eth_rxconf, rte_eth_txconf, rte_eth_rxq_info, rte_eth_txq_info};
+use std::sync::Arc;
 
 #[path = "mlx5_raw/mlx5_raw.rs"]
 pub mod mlx5_raw;
@@ -24,54 +31,86 @@ pub struct Mlx5Port {
     tx_id: i32,
     rxq_data: *mut *mut mlx5_rxq_data,
     txq_data: *mut *mut mlx5_txq_data,
+    nb_rxq: u16,
+    nb_txq: u16,
 }
 
 impl Mlx5Port {
-    pub fn from(port_id: u16, port_conf: &DpdkPortConf) -> Self {
+    pub fn from(port_id: u16, port_conf: &DpdkPortConf) -> Result<Self, PortError> {
 
-        let dpdk_port = RawDpdkPort::init(port_id, port_conf).unwrap();
+        let dpdk_port = RawDpdkPort::init(port_id, port_conf)?;
 
         let dev: *mut rdpdk::dpdk_raw::ethdev_driver::rte_eth_dev = unsafe {
             rust_get_port_eth_device(port_id) as *mut rte_eth_dev
         };
 
-        let mlx5_priv: &mlx5_priv = unsafe {
-            let data = (&mut *dev).data.as_mut().unwrap();
-            (data.dev_private as *mut mlx5_priv).as_mut().unwrap()
-        };
-
-        let rxq_data = unsafe {
-            mlx5_priv.
-                dev_data.
-                as_ref()
-                .unwrap()
-                .rx_queues as *mut *mut mlx5_rxq_data
-        };
-
         // TODO: fix eth_dev initialization
         let rx_id = 0 * unsafe { mlx5_select_rx_function_index(dev as *mut _) };
         let tx_td = unsafe { mlx5_select_tx_function_index(dev as *mut _)};
 
-        let txq_data = unsafe {
-            mlx5_priv.
-                dev_data.
-                as_ref()
-                .unwrap()
-                .tx_queues as *mut *mut mlx5_txq_data
-        };
-
-        Mlx5Port {
+        let mut port = Mlx5Port {
             dpdk_port: dpdk_port,
             rx_id: rx_id,
             tx_id: tx_td,
-            rxq_data: rxq_data,
-            txq_data: txq_data,
+            rxq_data: std::ptr::null_mut(),
+            txq_data: std::ptr::null_mut(),
+            nb_rxq: 0,
+            nb_txq: 0,
+        };
+        port.update_queue_data();
+        Ok(port)
+    }
+
+    // Queue arrays are re-allocated when the device is configured.
+    fn update_queue_data(&mut self) {
+        let dev: *mut rdpdk::dpdk_raw::ethdev_driver::rte_eth_dev = unsafe {
+            rust_get_port_eth_device(self.port_id()) as *mut rte_eth_dev
+        };
+
+        let mlx5_priv: &mlx5_priv = unsafe {
+            let data = (&mut *dev).data.as_mut().unwrap();
+            (data.dev_private as *mut mlx5_priv).as_mut().unwrap()
+        };
+        let dev_data = unsafe { mlx5_priv.dev_data.as_ref().unwrap() };
+
+        self.rxq_data = dev_data.rx_queues as *mut *mut mlx5_rxq_data;
+        self.txq_data = dev_data.tx_queues as *mut *mut mlx5_txq_data;
+        self.nb_rxq = dev_data.nb_rx_queues;
+        self.nb_txq = dev_data.nb_tx_queues;
+    }
+
+    // `dev_data->rx_queues` entry of the queue.
+    fn rxq(&self, queue_id: u16) -> Result<*mut c_void, PortError> {
+        if queue_id >= self.nb_rxq {
+            return Err(PortError::InvalidQueue { port_id: self.port_id(), queue_id });
+        }
+        Ok(unsafe { *self.rxq_data.add(queue_id as usize) } as *mut c_void)
+    }
+
+    // `dev_data->tx_queues` entry of the queue.
+    fn txq(&self, queue_id: u16) -> Result<*mut c_void, PortError> {
+        if queue_id >= self.nb_txq {
+            return Err(PortError::InvalidQueue { port_id: self.port_id(), queue_id });
         }
+        Ok(unsafe { *self.txq_data.add(queue_id as usize) } as *mut c_void)
     }
 }
 
 impl DpdkPort for Mlx5Port {
-    fn rx_burst(&mut self, queue_id: u16, pkts: &[*mut rte_mbuf]) -> Result<u16, String> {
+    fn rx_burst(&mut self, queue_id: u16, pkts: &mut MbufBatch) -> Result<u16, PortError> {
+        if !self.is_started() {
+            return Err(PortError::InvalidState {
+                port_id: self.port_id(),
+                call: "rx_burst",
+                state: self.dpdk_port.state(),
+            });
+        }
+        if queue_id >= self.port_conf().rxq_num {
+            return Err(PortError::InvalidQueue { port_id: self.port_id(), queue_id });
+        }
+        if !self.dpdk_port.rx_queue_started(queue_id) || self.dpdk_port.rx_queue_owned(queue_id) {
+            return Err(PortError::QueueState { port_id: self.port_id(), queue_id, call: "rx_burst" });
+        }
 
         let rxfn = unsafe {
             mlx5_rx_functions
@@ -82,16 +121,24 @@ impl DpdkPort for Mlx5Port {
                 .unwrap()
         };
 
-        Ok(unsafe {
-            rxfn(
-                (*self.rxq_data).wrapping_add(queue_id as usize) as *mut c_void,
-                pkts.as_ptr() as *mut _,
-                pkts.len() as u16
-            )
-        })
+        let rxq = self.rxq(queue_id)?;
+        Ok(unsafe { pkts.rx_with(|rx_pkts, nb_pkts| rxfn(rxq, rx_pkts as *mut _, nb_pkts)) })
     }
 
-    fn tx_burst(&mut self, queue_id: u16, pkts: &[*mut rte_mbuf]) -> Result<u16, String> {
+    fn tx_burst(&mut self, queue_id: u16, pkts: &mut MbufBatch) -> Result<u16, PortError> {
+        if !self.is_started() {
+            return Err(PortError::InvalidState {
+                port_id: self.port_id(),
+                call: "tx_burst",
+                state: self.dpdk_port.state(),
+            });
+        }
+        if queue_id >= self.port_conf().txq_num {
+            return Err(PortError::InvalidQueue { port_id: self.port_id(), queue_id });
+        }
+        if !self.dpdk_port.tx_queue_started(queue_id) || self.dpdk_port.tx_queue_owned(queue_id) {
+            return Err(PortError::QueueState { port_id: self.port_id(), queue_id, call: "tx_burst" });
+        }
 
         let txfn = unsafe {
             txoff_func
@@ -103,23 +150,136 @@ impl DpdkPort for Mlx5Port {
                 .unwrap()
         };
 
-        Ok(unsafe {
-            txfn(
-                (*self.txq_data).wrapping_add(queue_id as usize) as *mut c_void,
-                pkts.as_ptr() as *mut _,
-                pkts.len() as u16,
+        let txq = self.txq(queue_id)?;
+        Ok(unsafe { pkts.tx_with(|tx_pkts, nb_pkts| txfn(txq, tx_pkts as *mut _, nb_pkts)) })
+    }
+
+    fn rx_queue(&mut self, queue_id: u16) -> Result<RxQueue, PortError> {
+        let owner = self.dpdk_port.claim_rx_queue(queue_id)?;
+        let rxq = self.rxq(queue_id)?;
+        let rxfn: RxBurstFn = unsafe {
+            std::mem::transmute(
+                mlx5_rx_functions
+                    .as_ptr()
+                    .wrapping_add(self.rx_id as usize)
+                    .as_ref()
+                    .unwrap()
+                    .unwrap(),
             )
-        })
+        };
+        Ok(unsafe { RxQueue::new(owner, rxfn, rxq) })
     }
 
-    fn configure(&mut self) -> Result<(), String> {
-        self.dpdk_port.configure()
+    fn tx_queue(&mut self, queue_id: u16) -> Result<TxQueue, PortError> {
+        let owner = self.dpdk_port.claim_tx_queue(queue_id)?;
+        let txq = self.txq(queue_id)?;
+        let txfn: TxBurstFn = unsafe {
+            std::mem::transmute(
+                txoff_func
+                    .as_ptr()
+                    .wrapping_add(self.tx_id as usize)
+                    .as_ref()
+                    .unwrap()
+                    .func
+                    .unwrap(),
+            )
+        };
+        Ok(unsafe { TxQueue::new(owner, txfn, txq) })
+    }
+
+    fn configure(&mut self) -> Result<(), PortError> {
+        self.dpdk_port.configure()?;
+        self.update_queue_data();
+        Ok(())
     }
     
-    fn start(&mut self) -> Result<(), String> {
+    fn start(&mut self) -> Result<(), PortError> {
         self.dpdk_port.start()
     }
 
+    fn stop(&mut self) -> Result<(), PortError> {
+        self.dpdk_port.stop()
+    }
+
+    fn close(&mut self) -> Result<(), PortError> {
+        self.dpdk_port.close()
+    }
+
+    fn reset(&mut self) -> Result<(), PortError> {
+        self.dpdk_port.reset()
+    }
+
+    fn is_started(&self) -> bool {
+        self.dpdk_port.is_started()
+    }
+
+    fn rx_queue_setup(
+        &mut self,
+        queue_id: u16,
+        nb_desc: u16,
+        rx_conf: &rte_eth_rxconf,
+        mempool: Arc<Mempool>,
+    ) -> Result<(), PortError> {
+        self.dpdk_port.rx_queue_setup(queue_id, nb_desc, rx_conf, mempool)
+    }
+
+    fn tx_queue_setup(
+        &mut self,
+        queue_id: u16,
+        nb_desc: u16,
+        tx_conf: &rte_eth_txconf,
+    ) -> Result<(), PortError> {
+        self.dpdk_port.tx_queue_setup(queue_id, nb_desc, tx_conf)
+    }
+
+    fn rx_queue_start(&mut self, queue_id: u16) -> Result<(), PortError> {
+        self.dpdk_port.rx_queue_start(queue_id)
+    }
+
+    fn rx_queue_stop(&mut self, queue_id: u16) -> Result<(), PortError> {
+        self.dpdk_port.rx_queue_stop(queue_id)
+    }
+
+    fn tx_queue_start(&mut self, queue_id: u16) -> Result<(), PortError> {
+        self.dpdk_port.tx_queue_start(queue_id)
+    }
+
+    fn tx_queue_stop(&mut self, queue_id: u16) -> Result<(), PortError> {
+        self.dpdk_port.tx_queue_stop(queue_id)
+    }
+
+    fn rx_queue_info(&self, queue_id: u16) -> Result<rte_eth_rxq_info, PortError> {
+        self.dpdk_port.rx_queue_info(queue_id)
+    }
+
+    fn tx_queue_info(&self, queue_id: u16) -> Result<rte_eth_txq_info, PortError> {
+        self.dpdk_port.tx_queue_info(queue_id)
+    }
+
+    fn rss_update(&mut self, rss: &RssConfig) -> Result<(), PortError> {
+        self.dpdk_port.rss_update(rss)
+    }
+
+    fn rss_reta_update(&mut self, reta: &[u16]) -> Result<(), PortError> {
+        self.dpdk_port.rss_reta_update(reta)
+    }
+
+    fn rss_query(&self) -> Result<RssConfig, PortError> {
+        self.dpdk_port.rss_query()
+    }
+
+    fn set_mtu(&mut self, mtu: u16) -> Result<(), PortError> {
+        self.dpdk_port.set_mtu(mtu)
+    }
+
+    fn vlan_update(&mut self, vlan: &VlanConfig) -> Result<(), PortError> {
+        self.dpdk_port.vlan_update(vlan)
+    }
+
+    fn vlan_filter(&mut self, vlan_id: u16, on: bool) -> Result<(), PortError> {
+        self.dpdk_port.vlan_filter(vlan_id, on)
+    }
+
     fn port_id(&self) -> u16 {
         self.dpdk_port.port_id()
     }
@@ -152,12 +312,15 @@ const MLX5_PCI_DEVICES: [PciDevice;5] = [
 ];
 
 
-fn mlx5_init_port(port_id: u16, device: PciDevice, port_conf: &DpdkPortConf) -> Result<Box<dyn DpdkPort>, String> {
+fn mlx5_init_port(port_id: u16, device: PciDevice, port_conf: &DpdkPortConf) -> Result<Box<dyn DpdkPort>, PortError> {
     if MLX5_PCI_DEVICES.contains(&device) {
         println!("mlx5: initializing port {} for  device {:x}", port_id, device);
-        return Ok(Box::new(Mlx5Port::from(port_id, port_conf)))
+        return Ok(Box::new(Mlx5Port::from(port_id, port_conf)?))
     }
-    Err(format!("mlx5: Unsupported Mellanox device {:x}", device))
+    Err(PortError::Unsupported {
+        port_id,
+        reason: format!("mlx5: Unsupported Mellanox device {:x}", device),
+    })
 }
 
 // Add this function to the `.init_array` section
@@ -7,3 +7,6 @@ pub mod dpdk_raw;
 #[path = "lib/port/port.rs"] // 2018 flat model
 pub mod port;
 
+
+#[path = "lib/flow/flow.rs"] // 2018 flat model
+pub mod flow;